@@ -0,0 +1,4 @@
+// 迁移文件变化时重新编译，使 sqlx::migrate! 嵌入最新的迁移
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
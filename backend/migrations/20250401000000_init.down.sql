@@ -0,0 +1,9 @@
+-- 回滚初始表结构（按依赖关系逆序删除）
+DROP TABLE IF EXISTS announcements;
+DROP TABLE IF EXISTS activities;
+DROP TABLE IF EXISTS homework;
+DROP TABLE IF EXISTS exam_records;
+DROP TABLE IF EXISTS exams;
+DROP TABLE IF EXISTS course_records;
+DROP TABLE IF EXISTS courses;
+DROP TABLE IF EXISTS users;
@@ -0,0 +1,112 @@
+-- 初始表结构
+--
+-- 与旧版 init_db 创建的表结构保持一致，使用 IF NOT EXISTS
+-- 以便已有数据库在首次启用迁移时能够直接记录为已应用
+
+-- 创建用户表（整合学生信息，使用role字段区分用户类型）
+CREATE TABLE IF NOT EXISTS users (
+    id UUID PRIMARY KEY,
+    username VARCHAR(50) NOT NULL UNIQUE,
+    email VARCHAR(100) NOT NULL UNIQUE,
+    password_hash VARCHAR(100) NOT NULL,
+    display_name VARCHAR(100),
+    avatar_url TEXT,
+    bio TEXT,
+    role VARCHAR(20) NOT NULL DEFAULT 'student', -- 用户角色：'teacher'或'student'
+    -- 学生特有字段
+    grade INT, -- 年级：1, 2, 3（仅学生用户）
+    parent_name VARCHAR(100), -- 家长姓名（仅学生用户）
+    parent_phone VARCHAR(20), -- 家长电话（仅学生用户）
+    address TEXT, -- 地址（仅学生用户）
+    notes TEXT, -- 备注（仅学生用户）
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 创建课程表
+CREATE TABLE IF NOT EXISTS courses (
+    id UUID PRIMARY KEY,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    keywords TEXT[], -- 课程关键词，用于检索
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 创建课程记录表（记录学生上课情况）
+CREATE TABLE IF NOT EXISTS course_records (
+    id UUID PRIMARY KEY,
+    student_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- 引用用户表中的学生
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    class_date DATE NOT NULL, -- 上课日期
+    content TEXT NOT NULL, -- 上课内容
+    performance TEXT, -- 上课表现
+    teacher_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 创建试卷表
+CREATE TABLE IF NOT EXISTS exams (
+    id UUID PRIMARY KEY,
+    title VARCHAR(200) NOT NULL,
+    description TEXT,
+    keywords TEXT[], -- 试卷关键词，用于检索
+    file_path TEXT, -- 试卷文件路径
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 创建试卷记录表（记录学生做试卷情况）
+CREATE TABLE IF NOT EXISTS exam_records (
+    id UUID PRIMARY KEY,
+    student_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- 引用用户表中的学生
+    exam_id UUID NOT NULL REFERENCES exams(id) ON DELETE CASCADE,
+    score DECIMAL(5,2), -- 分数
+    completion_date DATE NOT NULL, -- 完成日期
+    notes TEXT, -- 备注
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 创建作业表（记录学生上传的作业）
+CREATE TABLE IF NOT EXISTS homework (
+    id UUID PRIMARY KEY,
+    student_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- 引用用户表中的学生
+    title VARCHAR(200) NOT NULL,
+    description TEXT,
+    file_path TEXT, -- 作业文件路径
+    submission_date DATE NOT NULL, -- 提交日期
+    grade VARCHAR(10), -- 评分
+    feedback TEXT, -- 反馈
+    teacher_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 创建活动记录表（记录系统活动）
+CREATE TABLE IF NOT EXISTS activities (
+    id UUID PRIMARY KEY,
+    activity_type VARCHAR(50) NOT NULL,
+    description TEXT NOT NULL,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    user_name VARCHAR(100) NOT NULL,
+    user_role VARCHAR(20) NOT NULL,
+    resource_id UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- 创建公告表
+CREATE TABLE IF NOT EXISTS announcements (
+    id UUID PRIMARY KEY,
+    title VARCHAR(200) NOT NULL,
+    content TEXT NOT NULL,
+    publisher_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    publisher_name VARCHAR(100) NOT NULL,
+    publisher_role VARCHAR(20) NOT NULL,
+    is_important BOOLEAN NOT NULL DEFAULT false,
+    published_at TIMESTAMPTZ NOT NULL,
+    expired_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
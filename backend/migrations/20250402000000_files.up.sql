@@ -0,0 +1,11 @@
+-- 创建文件表（记录上传到存储后端的文件元数据）
+CREATE TABLE IF NOT EXISTS files (
+    id UUID PRIMARY KEY,
+    storage_key TEXT NOT NULL UNIQUE, -- 存储后端中的文件键
+    original_name VARCHAR(255) NOT NULL, -- 原始文件名
+    content_type VARCHAR(100) NOT NULL, -- MIME类型
+    size BIGINT NOT NULL, -- 文件大小（字节）
+    sha256 CHAR(64) NOT NULL, -- 文件内容哈希
+    uploaded_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,15 @@
+-- 创建考勤表（记录学生每节课的出勤情况）
+CREATE TABLE IF NOT EXISTS attendance (
+    id UUID PRIMARY KEY,
+    student_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- 引用用户表中的学生
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    class_date DATE NOT NULL, -- 上课日期（课次）
+    status VARCHAR(20) NOT NULL CHECK (status IN ('present', 'absent', 'late', 'excused')), -- 出勤状态
+    reason TEXT, -- 缺勤/迟到/请假原因
+    recorded_by UUID REFERENCES users(id) ON DELETE SET NULL, -- 记录人（教师）
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (student_id, course_id, class_date)
+);
+
+CREATE INDEX IF NOT EXISTS idx_attendance_course_date ON attendance (course_id, class_date);
@@ -0,0 +1,94 @@
+-- 角色与权限
+--
+-- users.role 限定为已知角色，权限通过 role_permissions 表分配给角色
+
+-- 规范化已有角色值，未知角色按学生处理（与旧版 From<String> 行为一致）
+UPDATE users SET role = LOWER(role);
+UPDATE users SET role = 'student' WHERE role NOT IN ('admin', 'teacher', 'student', 'parent');
+
+ALTER TABLE users
+    ADD CONSTRAINT users_role_check CHECK (role IN ('admin', 'teacher', 'student', 'parent'));
+
+-- 创建权限表
+CREATE TABLE IF NOT EXISTS permissions (
+    code VARCHAR(50) PRIMARY KEY, -- 权限代码，如 'student:read'
+    description TEXT NOT NULL
+);
+
+-- 创建角色权限表
+CREATE TABLE IF NOT EXISTS role_permissions (
+    role VARCHAR(20) NOT NULL CHECK (role IN ('admin', 'teacher', 'student', 'parent')),
+    permission_code VARCHAR(50) NOT NULL REFERENCES permissions(code) ON DELETE CASCADE,
+    PRIMARY KEY (role, permission_code)
+);
+
+INSERT INTO permissions (code, description) VALUES
+    ('user:manage', '管理用户账号'),
+    ('student:read', '查看学生信息'),
+    ('student:write', '创建和修改学生信息'),
+    ('student:delete', '删除学生'),
+    ('course:read', '查看课程'),
+    ('course:write', '创建、修改和删除课程'),
+    ('course_record:read', '查看课程记录'),
+    ('course_record:write', '创建、修改和删除课程记录'),
+    ('exam:read', '查看试卷'),
+    ('exam:write', '创建、修改和删除试卷'),
+    ('exam_record:read', '查看试卷记录'),
+    ('exam_record:write', '创建、修改和删除试卷记录'),
+    ('homework:read', '查看作业'),
+    ('homework:submit', '提交作业'),
+    ('homework:write', '修改和删除作业'),
+    ('homework:grade', '评分作业'),
+    ('attendance:read', '查看考勤'),
+    ('attendance:write', '记录考勤'),
+    ('activity:read', '查看活动记录'),
+    ('activity:write', '创建活动记录'),
+    ('announcement:write', '发布公告'),
+    ('file:read', '下载文件')
+ON CONFLICT (code) DO NOTHING;
+
+-- 管理员拥有全部权限
+INSERT INTO role_permissions (role, permission_code)
+SELECT 'admin', code FROM permissions
+ON CONFLICT DO NOTHING;
+
+INSERT INTO role_permissions (role, permission_code) VALUES
+    ('teacher', 'student:read'),
+    ('teacher', 'student:write'),
+    ('teacher', 'student:delete'),
+    ('teacher', 'course:read'),
+    ('teacher', 'course:write'),
+    ('teacher', 'course_record:read'),
+    ('teacher', 'course_record:write'),
+    ('teacher', 'exam:read'),
+    ('teacher', 'exam:write'),
+    ('teacher', 'exam_record:read'),
+    ('teacher', 'exam_record:write'),
+    ('teacher', 'homework:read'),
+    ('teacher', 'homework:submit'),
+    ('teacher', 'homework:write'),
+    ('teacher', 'homework:grade'),
+    ('teacher', 'attendance:read'),
+    ('teacher', 'attendance:write'),
+    ('teacher', 'activity:read'),
+    ('teacher', 'activity:write'),
+    ('teacher', 'announcement:write'),
+    ('teacher', 'file:read'),
+    ('student', 'student:read'),
+    ('student', 'course:read'),
+    ('student', 'course_record:read'),
+    ('student', 'exam:read'),
+    ('student', 'exam_record:read'),
+    ('student', 'homework:read'),
+    ('student', 'homework:submit'),
+    ('student', 'attendance:read'),
+    ('student', 'file:read'),
+    ('parent', 'student:read'),
+    ('parent', 'course:read'),
+    ('parent', 'course_record:read'),
+    ('parent', 'exam:read'),
+    ('parent', 'exam_record:read'),
+    ('parent', 'homework:read'),
+    ('parent', 'attendance:read'),
+    ('parent', 'file:read')
+ON CONFLICT DO NOTHING;
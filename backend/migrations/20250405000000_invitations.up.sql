@@ -0,0 +1,16 @@
+-- 注册邀请
+--
+-- 教师和管理员账号只能由管理员创建或通过一次性邀请注册，邀请令牌只保存哈希
+
+CREATE TABLE IF NOT EXISTS invitations (
+    id UUID PRIMARY KEY,
+    token_hash CHAR(64) NOT NULL UNIQUE, -- 邀请令牌的SHA-256哈希
+    role VARCHAR(20) NOT NULL CHECK (role IN ('admin', 'teacher', 'student', 'parent')), -- 注册后的角色
+    email VARCHAR(100), -- 限定注册邮箱（可选）
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    expires_at TIMESTAMPTZ NOT NULL,
+    used_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    used_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,18 @@
+-- 刷新令牌
+--
+-- 刷新令牌为不透明字符串，只保存哈希。每次刷新都会轮换令牌，
+-- 同一登录会话轮换出的令牌属于同一族（family_id），已轮换的令牌被再次使用时整族撤销
+
+CREATE TABLE IF NOT EXISTS refresh_tokens (
+    id UUID PRIMARY KEY,
+    user_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    family_id UUID NOT NULL, -- 令牌族，即一次登录会话
+    token_hash CHAR(64) NOT NULL UNIQUE, -- 刷新令牌的SHA-256哈希
+    expires_at TIMESTAMPTZ NOT NULL,
+    rotated_at TIMESTAMPTZ, -- 被轮换（已使用）的时间
+    revoked_at TIMESTAMPTZ, -- 被撤销的时间
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_user ON refresh_tokens (user_id);
+CREATE INDEX IF NOT EXISTS idx_refresh_tokens_family ON refresh_tokens (family_id);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_activities_resource;
+
+ALTER TABLE activities DROP COLUMN IF EXISTS changes;
@@ -0,0 +1,8 @@
+-- 活动记录的变更内容
+--
+-- 审计中间件记录资源变更前后的差异：{"before": {...}, "after": {...}}，
+-- 新增时 before 为 null，删除时 after 为 null
+
+ALTER TABLE activities ADD COLUMN IF NOT EXISTS changes JSONB;
+
+CREATE INDEX IF NOT EXISTS idx_activities_resource ON activities (resource_id);
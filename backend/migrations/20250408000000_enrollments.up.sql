@@ -0,0 +1,36 @@
+-- 班级与选课
+--
+-- 班级是课程在某个学期的一个教学班，由一名教师负责并限定人数；
+-- 选课记录学生加入课程（及其班级）和退课的日期
+
+CREATE TABLE IF NOT EXISTS class_groups (
+    id UUID PRIMARY KEY,
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL, -- 班级名称
+    term VARCHAR(50) NOT NULL, -- 学期，如 2025春
+    teacher_id UUID REFERENCES users(id) ON DELETE SET NULL, -- 负责教师
+    capacity INTEGER CHECK (capacity > 0), -- 人数上限，为空时不限
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (course_id, term, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_class_groups_teacher ON class_groups (teacher_id);
+
+CREATE TABLE IF NOT EXISTS enrollments (
+    id UUID PRIMARY KEY,
+    student_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- 引用用户表中的学生
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    class_group_id UUID REFERENCES class_groups(id) ON DELETE SET NULL, -- 所在班级（可选）
+    enrolled_on DATE NOT NULL, -- 选课日期
+    dropped_on DATE, -- 退课日期，为空表示在读
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (dropped_on IS NULL OR dropped_on >= enrolled_on)
+);
+
+-- 同一学生同一课程只能有一条在读的选课
+CREATE UNIQUE INDEX IF NOT EXISTS idx_enrollments_active
+    ON enrollments (student_id, course_id) WHERE dropped_on IS NULL;
+CREATE INDEX IF NOT EXISTS idx_enrollments_course ON enrollments (course_id);
+CREATE INDEX IF NOT EXISTS idx_enrollments_class_group ON enrollments (class_group_id);
@@ -0,0 +1,25 @@
+-- 恢复 homework 表，只保留已提交的作业
+CREATE TABLE IF NOT EXISTS homework (
+    id UUID PRIMARY KEY,
+    student_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    title VARCHAR(200) NOT NULL,
+    description TEXT,
+    file_path TEXT,
+    submission_date DATE NOT NULL,
+    grade VARCHAR(10),
+    feedback TEXT,
+    teacher_id UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+INSERT INTO homework (id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at)
+SELECT s.id, s.student_id, a.title, a.instructions, s.file_path, s.submission_date, s.grade, s.feedback,
+       a.teacher_id, s.created_at, s.updated_at
+FROM submissions s
+JOIN assignments a ON a.id = s.assignment_id
+WHERE s.submission_date IS NOT NULL;
+
+DROP TABLE IF EXISTS submissions;
+DROP TABLE IF EXISTS assignment_attachments;
+DROP TABLE IF EXISTS assignments;
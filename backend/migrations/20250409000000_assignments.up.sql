@@ -0,0 +1,57 @@
+-- 作业任务与提交
+--
+-- 原 homework 表的每一行同时包含作业内容和一个学生的提交，现拆分为作业任务（assignments）
+-- 和每个学生的提交（submissions）。原有数据每行迁移为一个作业任务及其提交，
+-- 两者都沿用原作业ID，原有的 /homework 接口按提交ID继续使用
+
+CREATE TABLE IF NOT EXISTS assignments (
+    id UUID PRIMARY KEY,
+    course_id UUID REFERENCES courses(id) ON DELETE CASCADE, -- 所属课程，由原作业迁移或通过兼容接口创建的为空
+    title VARCHAR(200) NOT NULL,
+    instructions TEXT, -- 作业要求
+    due_date DATE, -- 截止日期
+    max_score DECIMAL(5, 2) CHECK (max_score > 0), -- 满分
+    teacher_id UUID REFERENCES users(id) ON DELETE SET NULL, -- 布置作业的教师
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_assignments_course ON assignments (course_id);
+CREATE INDEX IF NOT EXISTS idx_assignments_teacher ON assignments (teacher_id);
+
+-- 作业任务的附件，文件本身记录在 files 表中
+CREATE TABLE IF NOT EXISTS assignment_attachments (
+    assignment_id UUID NOT NULL REFERENCES assignments(id) ON DELETE CASCADE,
+    file_id UUID NOT NULL REFERENCES files(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (assignment_id, file_id)
+);
+
+CREATE TABLE IF NOT EXISTS submissions (
+    id UUID PRIMARY KEY,
+    assignment_id UUID NOT NULL REFERENCES assignments(id) ON DELETE CASCADE,
+    student_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE, -- 引用用户表中的学生
+    status VARCHAR(20) NOT NULL DEFAULT 'assigned'
+        CHECK (status IN ('assigned', 'submitted', 'late', 'graded', 'returned')), -- 提交状态
+    file_path TEXT, -- 作业文件路径
+    submission_date DATE, -- 提交日期，未提交时为空
+    grade VARCHAR(10), -- 评分
+    feedback TEXT, -- 反馈
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (assignment_id, student_id),
+    CHECK (status = 'assigned' OR submission_date IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_submissions_student ON submissions (student_id);
+
+INSERT INTO assignments (id, title, instructions, teacher_id, created_at, updated_at)
+SELECT id, title, description, teacher_id, created_at, updated_at
+FROM homework;
+
+INSERT INTO submissions (id, assignment_id, student_id, status, file_path, submission_date, grade, feedback, created_at, updated_at)
+SELECT id, id, student_id, CASE WHEN grade IS NULL THEN 'submitted' ELSE 'graded' END,
+       file_path, submission_date, grade, feedback, created_at, updated_at
+FROM homework;
+
+DROP TABLE homework;
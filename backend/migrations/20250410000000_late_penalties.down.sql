@@ -0,0 +1,9 @@
+DROP INDEX IF EXISTS idx_assignments_due_date;
+
+ALTER TABLE submissions
+    DROP COLUMN IF EXISTS score,
+    DROP COLUMN IF EXISTS late_penalty,
+    DROP COLUMN IF EXISTS raw_score,
+    DROP COLUMN IF EXISTS days_late;
+
+DROP TABLE IF EXISTS late_policies;
@@ -0,0 +1,26 @@
+-- 作业迟交扣分
+--
+-- 每个课程可以设置迟交扣分规则：每迟交一天扣除的百分比、扣分上限和不扣分的宽限天数。
+-- 提交作业时记录迟交天数，评分时按规则从原始分数中扣分
+
+CREATE TABLE IF NOT EXISTS late_policies (
+    course_id UUID PRIMARY KEY REFERENCES courses(id) ON DELETE CASCADE,
+    percent_per_day DECIMAL(5, 2) NOT NULL CHECK (percent_per_day >= 0 AND percent_per_day <= 100), -- 每天扣除的百分比
+    max_percent DECIMAL(5, 2) NOT NULL CHECK (max_percent >= 0 AND max_percent <= 100), -- 扣分上限（百分比）
+    grace_days INTEGER NOT NULL DEFAULT 0 CHECK (grace_days >= 0), -- 宽限天数
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE submissions
+    ADD COLUMN IF NOT EXISTS days_late INTEGER NOT NULL DEFAULT 0 CHECK (days_late >= 0), -- 提交时的迟交天数
+    ADD COLUMN IF NOT EXISTS raw_score DECIMAL(5, 2), -- 扣分前的分数
+    ADD COLUMN IF NOT EXISTS late_penalty DECIMAL(5, 2), -- 扣除的百分比
+    ADD COLUMN IF NOT EXISTS score DECIMAL(5, 2); -- 扣分后的分数
+
+UPDATE submissions s
+SET days_late = s.submission_date - a.due_date
+FROM assignments a
+WHERE a.id = s.assignment_id AND s.submission_date > a.due_date;
+
+CREATE INDEX IF NOT EXISTS idx_assignments_due_date ON assignments (due_date);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS grade_levels;
+DROP TABLE IF EXISTS grading_scales;
@@ -0,0 +1,24 @@
+-- 评分等级制
+--
+-- 每个课程可以设置一种评分等级制：百分制、A-F 字母等级、优/良/中/差或通过/不通过。
+-- 除百分制外，每个等级有一个最低得分率，得分率不低于该值的最高等级即为显示的等级
+
+CREATE TABLE IF NOT EXISTS grading_scales (
+    course_id UUID PRIMARY KEY REFERENCES courses(id) ON DELETE CASCADE,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('percentage', 'letter', 'tier', 'pass_fail')), -- 等级制类型
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS grade_levels (
+    course_id UUID NOT NULL REFERENCES grading_scales(course_id) ON DELETE CASCADE,
+    label VARCHAR(10) NOT NULL, -- 等级名称
+    min_percent DECIMAL(5, 2) NOT NULL CHECK (min_percent >= 0 AND min_percent <= 100), -- 最低得分率
+    PRIMARY KEY (course_id, label),
+    UNIQUE (course_id, min_percent)
+);
+
+-- 原有评分中的数字作为分数
+UPDATE submissions
+SET raw_score = grade::DECIMAL(5, 2), late_penalty = 0, score = grade::DECIMAL(5, 2)
+WHERE score IS NULL AND grade ~ '^\d{1,3}(\.\d{1,2})?$';
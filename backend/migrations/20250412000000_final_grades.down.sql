@@ -0,0 +1,16 @@
+DELETE FROM permissions WHERE code IN ('final_grade:read', 'final_grade:write', 'final_grade:unlock');
+
+DROP TABLE IF EXISTS final_grades;
+DROP TABLE IF EXISTS grade_excusals;
+
+DROP INDEX IF EXISTS idx_exams_category_id;
+DROP INDEX IF EXISTS idx_assignments_category_id;
+
+ALTER TABLE exams
+    DROP COLUMN IF EXISTS max_score,
+    DROP COLUMN IF EXISTS category_id;
+
+ALTER TABLE assignments
+    DROP COLUMN IF EXISTS category_id;
+
+DROP TABLE IF EXISTS grade_categories;
@@ -0,0 +1,77 @@
+-- 课程总评成绩
+--
+-- 课程每个学期按成绩类别（如作业、测验、考试）设置权重，作业任务和试卷归入类别后参与总评。
+-- 每个类别可以去掉最低的若干项，免做的项目不计入。教师可以预览计算结果、说明原因后调整总评，
+-- 确定后锁定，锁定的总评不再随成绩变化，只有拥有 final_grade:unlock 权限的用户可以解锁
+
+CREATE TABLE IF NOT EXISTS grade_categories (
+    id UUID PRIMARY KEY,
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    term VARCHAR(50) NOT NULL, -- 学期
+    name VARCHAR(50) NOT NULL, -- 类别名称
+    weight DECIMAL(5, 2) NOT NULL CHECK (weight > 0 AND weight <= 100), -- 权重（百分比）
+    drop_lowest INTEGER NOT NULL DEFAULT 0 CHECK (drop_lowest >= 0), -- 去掉最低的项数
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (course_id, term, name)
+);
+
+ALTER TABLE assignments
+    ADD COLUMN IF NOT EXISTS category_id UUID REFERENCES grade_categories(id) ON DELETE SET NULL; -- 成绩类别
+
+ALTER TABLE exams
+    ADD COLUMN IF NOT EXISTS category_id UUID REFERENCES grade_categories(id) ON DELETE SET NULL, -- 成绩类别
+    ADD COLUMN IF NOT EXISTS max_score DECIMAL(5, 2) CHECK (max_score > 0); -- 满分
+
+CREATE INDEX IF NOT EXISTS idx_assignments_category_id ON assignments (category_id);
+CREATE INDEX IF NOT EXISTS idx_exams_category_id ON exams (category_id);
+
+-- 免做：学生的某个作业任务或试卷不计入总评
+CREATE TABLE IF NOT EXISTS grade_excusals (
+    id UUID PRIMARY KEY,
+    student_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    assignment_id UUID REFERENCES assignments(id) ON DELETE CASCADE,
+    exam_id UUID REFERENCES exams(id) ON DELETE CASCADE,
+    reason TEXT NOT NULL, -- 免做原因
+    excused_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK (num_nonnulls(assignment_id, exam_id) = 1),
+    UNIQUE (student_id, assignment_id),
+    UNIQUE (student_id, exam_id)
+);
+
+CREATE TABLE IF NOT EXISTS final_grades (
+    id UUID PRIMARY KEY,
+    student_id UUID NOT NULL REFERENCES users(id) ON DELETE CASCADE,
+    course_id UUID NOT NULL REFERENCES courses(id) ON DELETE CASCADE,
+    term VARCHAR(50) NOT NULL,
+    computed_score DECIMAL(5, 2), -- 按权重计算的总评（百分制）
+    override_score DECIMAL(5, 2) CHECK (override_score >= 0 AND override_score <= 100), -- 调整后的总评
+    override_reason TEXT, -- 调整原因
+    score DECIMAL(5, 2) GENERATED ALWAYS AS (COALESCE(override_score, computed_score)) STORED, -- 最终总评
+    grade VARCHAR(10), -- 按评分等级制显示的等级
+    locked_at TIMESTAMPTZ, -- 锁定时间，为空表示未锁定
+    locked_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (student_id, course_id, term),
+    CHECK (override_score IS NULL OR override_reason IS NOT NULL)
+);
+
+CREATE INDEX IF NOT EXISTS idx_final_grades_course_term ON final_grades (course_id, term);
+
+INSERT INTO permissions (code, description) VALUES
+    ('final_grade:read', '查看总评成绩'),
+    ('final_grade:write', '设置成绩类别、免做，计算、调整和锁定总评成绩'),
+    ('final_grade:unlock', '解锁总评成绩')
+ON CONFLICT (code) DO NOTHING;
+
+INSERT INTO role_permissions (role, permission_code) VALUES
+    ('admin', 'final_grade:read'),
+    ('admin', 'final_grade:write'),
+    ('admin', 'final_grade:unlock'),
+    ('teacher', 'final_grade:read'),
+    ('teacher', 'final_grade:write'),
+    ('student', 'final_grade:read'),
+    ('parent', 'final_grade:read')
+ON CONFLICT DO NOTHING;
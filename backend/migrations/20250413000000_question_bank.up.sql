@@ -0,0 +1,42 @@
+-- 题库与小题得分
+--
+-- 题目保存在可复用的题库中，包括题型、分值、知识点和参考答案。试卷由题库中的题目按顺序组成，
+-- 每道题在试卷中可以使用不同的分值。试卷记录可以按题目登记得分，用于统计学生在各题目和知识点上的掌握情况
+
+CREATE TABLE IF NOT EXISTS questions (
+    id UUID PRIMARY KEY,
+    question_type VARCHAR(20) NOT NULL
+        CHECK (question_type IN ('single_choice', 'multiple_choice', 'true_false', 'fill_blank', 'short_answer')), -- 题型
+    content TEXT NOT NULL, -- 题目内容
+    options TEXT[], -- 选择题的选项
+    answer_key TEXT, -- 参考答案
+    points DECIMAL(5, 2) NOT NULL CHECK (points > 0), -- 默认分值
+    knowledge_points TEXT[] NOT NULL DEFAULT '{}', -- 知识点
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_questions_knowledge_points ON questions USING GIN (knowledge_points);
+
+-- 试卷中的题目，已被试卷使用的题目不能删除
+CREATE TABLE IF NOT EXISTS exam_questions (
+    exam_id UUID NOT NULL REFERENCES exams(id) ON DELETE CASCADE,
+    question_id UUID NOT NULL REFERENCES questions(id) ON DELETE RESTRICT,
+    position INTEGER NOT NULL CHECK (position > 0), -- 题号
+    points DECIMAL(5, 2) NOT NULL CHECK (points > 0), -- 在试卷中的分值
+    PRIMARY KEY (exam_id, question_id),
+    UNIQUE (exam_id, position)
+);
+
+CREATE INDEX IF NOT EXISTS idx_exam_questions_question_id ON exam_questions (question_id);
+
+-- 试卷记录的小题得分
+CREATE TABLE IF NOT EXISTS exam_question_scores (
+    exam_record_id UUID NOT NULL REFERENCES exam_records(id) ON DELETE CASCADE,
+    question_id UUID NOT NULL REFERENCES questions(id) ON DELETE RESTRICT,
+    score DECIMAL(5, 2) NOT NULL CHECK (score >= 0), -- 得分
+    PRIMARY KEY (exam_record_id, question_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_exam_question_scores_question_id ON exam_question_scores (question_id);
@@ -0,0 +1,4 @@
+-- 已去掉的个人信息无法恢复
+INSERT INTO role_permissions (role, permission_code) VALUES
+    ('teacher', 'activity:read')
+ON CONFLICT DO NOTHING;
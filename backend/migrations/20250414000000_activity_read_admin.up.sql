@@ -0,0 +1,24 @@
+-- 活动记录只允许管理员查看
+--
+-- 活动记录的描述和变更差异涉及所有学生的数据，不受数据访问范围限制，
+-- 因此 activity:read 只保留给管理员。已有的学生活动记录中去掉快照不再保留的个人信息
+
+DELETE FROM role_permissions WHERE permission_code = 'activity:read' AND role <> 'admin';
+
+UPDATE activities
+SET changes = jsonb_build_object(
+    'before',
+    CASE WHEN jsonb_typeof(changes -> 'before') = 'object'
+        THEN (changes -> 'before')
+            - ARRAY['email', 'password_hash', 'avatar_url', 'bio', 'parent_name', 'parent_phone', 'address', 'notes']
+        ELSE changes -> 'before'
+    END,
+    'after',
+    CASE WHEN jsonb_typeof(changes -> 'after') = 'object'
+        THEN (changes -> 'after')
+            - ARRAY['email', 'password_hash', 'avatar_url', 'bio', 'parent_name', 'parent_phone', 'address', 'notes']
+        ELSE changes -> 'after'
+    END
+)
+WHERE activity_type IN ('add_student', 'update_student', 'delete_student')
+  AND changes IS NOT NULL;
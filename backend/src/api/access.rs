@@ -0,0 +1,82 @@
+//! 数据访问范围
+//!
+//! 根据当前用户的角色确定其可以读取哪些学生的数据：
+//! 管理员可以读取全部学生，教师只能读取其授课的学生，学生只能读取自己的数据
+
+use sqlx::postgres::PgPool;
+use std::collections::HashSet;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppErrorType};
+use crate::middleware::auth::Claims;
+use crate::model::models::user::{User, UserRole};
+
+/// 当前用户可以读取的学生范围
+#[derive(Debug, Clone)]
+pub enum StudentScope {
+    /// 全部学生
+    All,
+    /// 指定的学生
+    Students(HashSet<Uuid>),
+}
+
+impl StudentScope {
+    /// 根据令牌中的用户信息确定访问范围
+    pub async fn of(pool: &PgPool, claims: &Claims) -> Result<Self, AppError> {
+        let user_id = claims.user_id()?;
+
+        let scope = match claims.user_role()? {
+            UserRole::Admin => StudentScope::All,
+            UserRole::Teacher => StudentScope::Students(
+                User::find_taught_student_ids(pool, user_id)
+                    .await?
+                    .into_iter()
+                    .collect(),
+            ),
+            UserRole::Student => StudentScope::Students(HashSet::from([user_id])),
+            // 家长账号尚未与学生关联
+            UserRole::Parent => StudentScope::Students(HashSet::new()),
+        };
+
+        Ok(scope)
+    }
+
+    /// 判断是否可以读取指定学生的数据
+    pub fn contains(&self, student_id: Uuid) -> bool {
+        match self {
+            StudentScope::All => true,
+            StudentScope::Students(ids) => ids.contains(&student_id),
+        }
+    }
+
+    /// 要求可以读取指定学生的数据
+    pub fn check(&self, student_id: Uuid) -> Result<(), AppError> {
+        if self.contains(student_id) {
+            Ok(())
+        } else {
+            Err(AppError::new_message(
+                "无权访问该学生的数据",
+                AppErrorType::Forbidden,
+            ))
+        }
+    }
+
+    /// 可以读取的学生ID，全部学生时为空，用于在查询中限定范围
+    pub fn student_ids(&self) -> Option<Vec<Uuid>> {
+        match self {
+            StudentScope::All => None,
+            StudentScope::Students(ids) => Some(ids.iter().copied().collect()),
+        }
+    }
+
+    /// 过滤掉不在访问范围内的记录
+    pub fn filter<T>(&self, items: Vec<T>, student_id: impl Fn(&T) -> Uuid) -> Vec<T> {
+        match self {
+            StudentScope::All => items,
+            StudentScope::Students(_) => items
+                .into_iter()
+                .filter(|item| self.contains(student_id(item)))
+                .collect(),
+        }
+    }
+}
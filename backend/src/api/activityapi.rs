@@ -3,85 +3,87 @@
 //! 提供活动记录相关的API接口
 
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Query, State},
-    http::StatusCode,
 };
 use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::model::models::activity::{Activity, CreateActivityRequest};
+use crate::error::{AppError, FieldError};
+use crate::middleware::auth::Claims;
+use crate::middleware::validation::ValidatedJson;
+use crate::model::models::activity::{
+    Activity, ActivityType, CreateActivityRequest, LogActivityRequest,
+};
+use crate::model::query::{Page, PageParams};
 
 /// 获取活动记录的查询参数
 #[derive(Debug, Deserialize)]
 pub struct ActivityQuery {
-    /// 限制返回的记录数量，默认为20
-    #[serde(default = "default_limit")]
-    pub limit: i64,
     /// 活动类型，可选
     pub activity_type: Option<String>,
     /// 用户ID，可选
     pub user_id: Option<Uuid>,
 }
 
-/// 默认的记录数量限制
-fn default_limit() -> i64 {
-    20
-}
-
 /// 获取最近的活动记录
 ///
-/// 根据查询参数获取活动记录，可以按照活动类型或用户ID进行筛选
+/// 根据查询参数分页获取活动记录，活动类型和用户ID可以同时筛选
 pub async fn get_activities(
     State(pool): State<Arc<Pool<Postgres>>>,
     Query(query): Query<ActivityQuery>,
-) -> Result<Json<Vec<Activity>>, (StatusCode, String)> {
-    let activities = if let Some(user_id) = query.user_id {
-        // 按用户ID筛选
-        Activity::find_by_user_id(&pool, user_id, query.limit)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("获取用户活动记录失败: {}", e),
-                )
-            })?
-    } else if let Some(activity_type) = &query.activity_type {
-        // 按活动类型筛选
-        Activity::find_by_activity_type(&pool, activity_type, query.limit)
-            .await
-            .map_err(|e| {
-                (
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    format!("获取活动类型记录失败: {}", e),
-                )
-            })?
-    } else {
-        // 获取所有活动记录
-        Activity::find_all(&pool, query.limit).await.map_err(|e| {
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                format!("获取活动记录失败: {}", e),
-            )
-        })?
-    };
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Activity>>, AppError> {
+    let activities =
+        Activity::query(&pool, query.user_id, query.activity_type.as_deref(), &page).await?;
 
     Ok(Json(activities))
 }
 
 /// 创建新的活动记录
+///
+/// 操作用户为当前登录用户。服务端自动写入的活动类型不能由客户端记录，以免伪造审计记录
 pub async fn create_activity(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Json(req): Json<CreateActivityRequest>,
-) -> Result<Json<Activity>, (StatusCode, String)> {
-    let activity = Activity::create(&pool, req).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("创建活动记录失败: {}", e),
-        )
-    })?;
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(req): ValidatedJson<LogActivityRequest>,
+) -> Result<Json<Activity>, AppError> {
+    if !req.activity_type.client_loggable() {
+        let allowed: Vec<&str> = ActivityType::CLIENT_LOGGABLE
+            .iter()
+            .map(AsRef::as_ref)
+            .collect();
+        return Err(AppError::validation(vec![FieldError::new(
+            "activity_type",
+            &format!("只能记录以下类型的活动: {}", allowed.join("、")),
+        )]));
+    }
+
+    let activity = Activity::create(
+        &*pool,
+        actor_activity(&claims, req.activity_type, req.description, req.resource_id)?,
+    )
+    .await?;
 
     Ok(Json(activity))
 }
+
+/// 以当前登录用户为操作用户构造活动记录
+pub fn actor_activity(
+    claims: &Claims,
+    activity_type: ActivityType,
+    description: String,
+    resource_id: Option<Uuid>,
+) -> Result<CreateActivityRequest, AppError> {
+    Ok(CreateActivityRequest {
+        activity_type: activity_type.as_ref().to_string(),
+        description,
+        user_id: claims.user_id()?,
+        user_name: claims.username.clone(),
+        user_role: claims.role.clone(),
+        resource_id,
+        changes: None,
+    })
+}
@@ -2,23 +2,24 @@
 //!
 //! 提供系统公告相关的API接口
 
-use axum::{Json, extract::State, http::StatusCode};
+use axum::{
+    Json,
+    extract::{Query, State},
+};
 use sqlx::{Pool, Postgres};
 use std::sync::Arc;
 
+use crate::error::AppError;
+use crate::middleware::validation::ValidatedJson;
 use crate::model::models::announcement::{Announcement, CreateAnnouncementRequest};
+use crate::model::query::{Page, PageParams};
 
 /// 创建新的公告
 pub async fn create_announcement(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Json(req): Json<CreateAnnouncementRequest>,
-) -> Result<Json<Announcement>, (StatusCode, String)> {
-    let announcement = Announcement::create(&pool, req).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("创建公告失败: {}", e),
-        )
-    })?;
+    ValidatedJson(req): ValidatedJson<CreateAnnouncementRequest>,
+) -> Result<Json<Announcement>, AppError> {
+    let announcement = Announcement::create(&pool, req).await?;
 
     Ok(Json(announcement))
 }
@@ -26,13 +27,9 @@ pub async fn create_announcement(
 /// 获取所有有效公告
 pub async fn get_all_announcements(
     State(pool): State<Arc<Pool<Postgres>>>,
-) -> Result<Json<Vec<Announcement>>, (StatusCode, String)> {
-    let announcements = Announcement::find_all(&pool, 20).await.map_err(|e| {
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            format!("获取公告失败: {}", e),
-        )
-    })?;
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Announcement>>, AppError> {
+    let announcements = Announcement::find_all(&pool, &page).await?;
 
     Ok(Json(announcements))
 }
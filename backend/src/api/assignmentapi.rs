@@ -0,0 +1,490 @@
+//! 作业任务API模块
+//!
+//! 提供作业任务的布置、附件管理，以及学生提交、评分和发还相关的API端点
+
+use axum::{
+    Extension, Json,
+    extract::{Multipart, Path, Query, State},
+    http::StatusCode,
+    response::Response,
+};
+use rust_decimal::Decimal;
+use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
+use std::sync::Arc;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::access::StudentScope;
+use super::activityapi::actor_activity;
+use super::fileapi;
+use crate::error::{AppError, AppErrorType, FieldError};
+use crate::middleware::auth::Claims;
+use crate::middleware::validation::ValidatedJson;
+use crate::model::models::activity::{Activity, ActivityType};
+use crate::model::models::assignment::{
+    Assignment, AssignmentFilter, AssignmentWithAttachments, CreateAssignmentRequest,
+    UpdateAssignmentRequest,
+};
+use crate::model::models::enrollment::Enrollment;
+use crate::model::models::file::StoredFile;
+use crate::model::models::grade_category::GradeCategory;
+use crate::model::models::submission::{
+    AssignRequest, GradeSubmissionRequest, MissingSubmission, OverdueWork, Submission,
+    SubmissionFilter, SubmitRequest,
+};
+use crate::model::models::user::{User, UserRole};
+use crate::model::query::{Page, PageParams};
+
+/// 查找作业任务，不存在时返回 404
+async fn find_assignment(pool: &Pool<Postgres>, id: Uuid) -> Result<Assignment, AppError> {
+    Assignment::find_by_id(pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("作业任务不存在", AppErrorType::Notfound))
+}
+
+/// 查找提交，不存在时返回 404
+async fn find_submission(pool: &Pool<Postgres>, id: Uuid) -> Result<Submission, AppError> {
+    Submission::find_by_id(pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("作业提交不存在", AppErrorType::Notfound))
+}
+
+/// 要求成绩类别存在且属于作业任务所在的课程
+async fn check_category(
+    pool: &Pool<Postgres>,
+    category_id: Option<Uuid>,
+    course_id: Option<Uuid>,
+) -> Result<(), AppError> {
+    let Some(category_id) = category_id else {
+        return Ok(());
+    };
+    let belongs = GradeCategory::find_by_id(pool, category_id)
+        .await?
+        .is_some_and(|category| Some(category.course_id) == course_id);
+    if !belongs {
+        return Err(AppError::validation(vec![FieldError::new(
+            "category_id",
+            "成绩类别不存在或不属于该课程",
+        )]));
+    }
+    Ok(())
+}
+
+/// 拒绝同时填写评分和分数，填写分数时评分按课程的评分等级制换算
+pub(super) fn reject_grade_with_score(
+    grade: Option<&str>,
+    score: Option<Decimal>,
+) -> Result<(), AppError> {
+    if grade.is_some() && score.is_some() {
+        return Err(AppError::validation(vec![FieldError::new(
+            "grade",
+            "填写分数时评分按评分等级制换算，不能同时填写评分",
+        )]));
+    }
+    Ok(())
+}
+
+/// 要求扣分前的分数不超过作业任务的满分，没有满分时按100分计
+pub(super) async fn check_raw_score(
+    pool: &Pool<Postgres>,
+    assignment_id: Uuid,
+    raw_score: Option<Decimal>,
+) -> Result<(), AppError> {
+    let Some(raw_score) = raw_score else {
+        return Ok(());
+    };
+    let max_score = Assignment::find_by_id(pool, assignment_id)
+        .await?
+        .and_then(|assignment| assignment.max_score)
+        .unwrap_or(Decimal::ONE_HUNDRED);
+    if raw_score > max_score {
+        return Err(AppError::validation(vec![FieldError::new(
+            "raw_score",
+            &format!("分数不能超过满分 {}", max_score.normalize()),
+        )]));
+    }
+    Ok(())
+}
+
+// ===== 作业任务API =====
+
+/// 创建作业任务
+///
+/// 未指定教师时由当前用户布置
+pub async fn create_assignment(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(req): ValidatedJson<CreateAssignmentRequest>,
+) -> Result<Json<Assignment>, AppError> {
+    let teacher_id = match req.teacher_id {
+        Some(teacher_id) => teacher_id,
+        None => claims.user_id()?,
+    };
+    check_category(&pool, req.category_id, Some(req.course_id)).await?;
+
+    let assignment = Assignment::create(&*pool, req, Some(teacher_id)).await?;
+    Ok(Json(assignment))
+}
+
+/// 查询作业任务
+pub async fn get_assignments(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Query(filter): Query<AssignmentFilter>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Assignment>>, AppError> {
+    let assignments = Assignment::query(&pool, &filter, &page).await?;
+    Ok(Json(assignments))
+}
+
+/// 获取作业任务及其附件
+pub async fn get_assignment(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<AssignmentWithAttachments>, AppError> {
+    let assignment = find_assignment(&pool, id).await?;
+    let attachments = Assignment::attachments(&pool, id).await?;
+    Ok(Json(AssignmentWithAttachments {
+        assignment,
+        attachments,
+    }))
+}
+
+/// 更新作业任务
+pub async fn update_assignment(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<UpdateAssignmentRequest>,
+) -> Result<Json<Assignment>, AppError> {
+    let assignment = find_assignment(&pool, id).await?;
+    check_category(&pool, req.category_id, assignment.course_id).await?;
+
+    let assignment = Assignment::update(&pool, id, req).await?;
+    Ok(Json(assignment))
+}
+
+/// 删除作业任务，同时删除其提交和附件文件
+pub async fn delete_assignment(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let attachments = Assignment::attachments(&pool, id).await?;
+    if !Assignment::delete(&pool, id).await? {
+        return Err(AppError::new_message(
+            "作业任务不存在",
+            AppErrorType::Notfound,
+        ));
+    }
+
+    for file in attachments {
+        fileapi::remove_by_storage_key(&pool, &file.storage_key).await?;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 上传作业任务附件
+///
+/// 接收 multipart 表单中的 `file` 字段，附件通过 `/files/{id}` 下载
+pub async fn upload_assignment_file(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<AssignmentWithAttachments>, AppError> {
+    let assignment = find_assignment(&pool, id).await?;
+
+    let uploaded_by = claims.user_id().ok();
+    let file = fileapi::receive_upload(&pool, &mut multipart, "assignment", uploaded_by).await?;
+    Assignment::add_attachment(&pool, id, file.id).await?;
+
+    let attachments = Assignment::attachments(&pool, id).await?;
+    Ok(Json(AssignmentWithAttachments {
+        assignment,
+        attachments,
+    }))
+}
+
+/// 删除作业任务附件
+pub async fn delete_assignment_file(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path((id, file_id)): Path<(Uuid, Uuid)>,
+) -> Result<StatusCode, AppError> {
+    if !Assignment::remove_attachment(&pool, id, file_id).await? {
+        return Err(AppError::new_message("附件不存在", AppErrorType::Notfound));
+    }
+
+    if let Some(file) = StoredFile::find_by_id(&pool, file_id).await? {
+        fileapi::remove_by_storage_key(&pool, &file.storage_key).await?;
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 布置作业
+///
+/// 未指定学生时布置给课程中所有在读的学生，已布置过的学生跳过，返回新布置的提交
+pub async fn assign_homework(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<AssignRequest>,
+) -> Result<Json<Vec<Submission>>, AppError> {
+    let assignment = find_assignment(&pool, id).await?;
+
+    let student_ids = match (req.student_ids, assignment.course_id) {
+        (Some(ids), _) => {
+            let students: HashSet<Uuid> = User::find_student_ids(&pool, &ids)
+                .await?
+                .into_iter()
+                .collect();
+            let errors: Vec<FieldError> = ids
+                .iter()
+                .enumerate()
+                .filter(|(_, id)| !students.contains(id))
+                .map(|(i, _)| FieldError::new(&format!("student_ids[{}]", i), "学生不存在"))
+                .collect();
+            if !errors.is_empty() {
+                return Err(AppError::validation(errors));
+            }
+            ids
+        }
+        (None, Some(course_id)) => Enrollment::active_student_ids(&pool, course_id).await?,
+        (None, None) => {
+            return Err(AppError::validation(vec![FieldError::new(
+                "student_ids",
+                "作业任务没有关联课程，必须指定学生",
+            )]));
+        }
+    };
+
+    let mut tx = pool.begin().await?;
+    let submissions = Submission::assign(&mut *tx, id, &student_ids).await?;
+
+    let description = format!(
+        "布置作业：{}，新布置给 {} 名学生",
+        assignment.title,
+        submissions.len()
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(&claims, ActivityType::AssignHomework, description, Some(id))?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(submissions))
+}
+
+/// 获取作业任务的提交
+///
+/// 只返回当前用户可以查看的学生的提交
+pub async fn get_submissions(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Query(filter): Query<SubmissionFilter>,
+) -> Result<Json<Vec<Submission>>, AppError> {
+    find_assignment(&pool, id).await?;
+
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let submissions =
+        Submission::find_by_assignment(&pool, id, &filter, scope.student_ids().as_deref()).await?;
+    Ok(Json(submissions))
+}
+
+/// 获取尚未提交作业的学生
+pub async fn get_missing_submissions(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<MissingSubmission>>, AppError> {
+    find_assignment(&pool, id).await?;
+
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let missing = Submission::find_missing(&pool, id, scope.student_ids().as_deref()).await?;
+    Ok(Json(missing))
+}
+
+/// 获取学生逾期未交的作业
+pub async fn get_student_overdue(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(student_id): Path<Uuid>,
+) -> Result<Json<Vec<OverdueWork>>, AppError> {
+    StudentScope::of(&pool, &claims).await?.check(student_id)?;
+
+    let today = OffsetDateTime::now_utc().date();
+    let overdue = Submission::find_overdue(&pool, Some(student_id), None, today, None).await?;
+    Ok(Json(overdue))
+}
+
+/// 获取教师布置的作业中逾期未交的学生
+///
+/// 只返回当前用户可访问的学生
+pub async fn get_teacher_overdue(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(teacher_id): Path<Uuid>,
+) -> Result<Json<Vec<OverdueWork>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let today = OffsetDateTime::now_utc().date();
+    let overdue = Submission::find_overdue(
+        &pool,
+        None,
+        Some(teacher_id),
+        today,
+        scope.student_ids().as_deref(),
+    )
+    .await?;
+    Ok(Json(overdue))
+}
+
+// ===== 作业提交API =====
+
+/// 提交作业
+///
+/// 学生只能为自己按当天提交，晚于截止日期的提交标记为逾期。
+/// 只能提交已布置给该学生的作业，或学生在读课程的作业
+pub async fn submit_assignment(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(req): ValidatedJson<SubmitRequest>,
+) -> Result<Json<Submission>, AppError> {
+    let today = OffsetDateTime::now_utc().date();
+    let submission_date = if claims.user_role()? == UserRole::Student {
+        if req.student_id != claims.user_id()? {
+            return Err(AppError::new_message(
+                "学生只能提交自己的作业",
+                AppErrorType::Forbidden,
+            ));
+        }
+        today
+    } else {
+        req.submission_date.unwrap_or(today)
+    };
+
+    let assignment = find_assignment(&pool, req.assignment_id).await?;
+    if User::find_student_ids(&pool, &[req.student_id])
+        .await?
+        .is_empty()
+    {
+        return Err(AppError::validation(vec![FieldError::new(
+            "student_id",
+            "学生不存在",
+        )]));
+    }
+    if !Submission::can_submit(&pool, assignment.id, req.student_id).await? {
+        return Err(AppError::new_message(
+            "作业未布置给该学生，学生也未选修该课程",
+            AppErrorType::Forbidden,
+        ));
+    }
+
+    let submission = Submission::submit(
+        &pool,
+        assignment.id,
+        req.student_id,
+        submission_date,
+        assignment.due_date,
+    )
+    .await?
+    .ok_or_else(|| {
+        AppError::new_message("作业已评分，发还后才能重新提交", AppErrorType::Conflict)
+    })?;
+
+    Ok(Json(submission))
+}
+
+/// 获取作业提交
+pub async fn get_submission(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Submission>, AppError> {
+    let submission = find_submission(&pool, id).await?;
+
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(submission.student_id)?;
+    Ok(Json(submission))
+}
+
+/// 评分作业提交
+pub async fn grade_submission(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<GradeSubmissionRequest>,
+) -> Result<Json<Submission>, AppError> {
+    if req.grade.is_none() && req.score.is_none() && req.feedback.is_none() {
+        return Err(AppError::validation(vec![FieldError::new(
+            "grade",
+            "评分、分数或反馈不能为空",
+        )]));
+    }
+    reject_grade_with_score(req.grade.as_deref(), req.score)?;
+
+    let submission = find_submission(&pool, id).await?;
+    check_raw_score(&pool, submission.assignment_id, req.score).await?;
+    let submission = Submission::grade(&pool, &submission, req)
+        .await?
+        .ok_or_else(|| AppError::new_message("作业尚未提交，不能评分", AppErrorType::Conflict))?;
+    Ok(Json(submission))
+}
+
+/// 将已评分的作业发还学生
+pub async fn return_submission(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Submission>, AppError> {
+    find_submission(&pool, id).await?;
+    let submission = Submission::return_to_student(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("只能发还已评分的作业", AppErrorType::Conflict))?;
+    Ok(Json(submission))
+}
+
+/// 上传作业提交的文件
+///
+/// 接收 multipart 表单中的 `file` 字段，保存后替换原有文件
+pub async fn upload_submission_file(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<Submission>, AppError> {
+    let submission = find_submission(&pool, id).await?;
+
+    // 学生只能为自己的作业上传文件
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(submission.student_id)?;
+
+    let uploaded_by = claims.user_id().ok();
+    let file = fileapi::receive_upload(&pool, &mut multipart, "homework", uploaded_by).await?;
+    let updated = Submission::update_file_path(&pool, id, Some(file.storage_key.as_str())).await?;
+
+    // 清理被替换的旧文件
+    if let Some(old_path) = submission.file_path {
+        fileapi::remove_by_storage_key(&pool, &old_path).await?;
+    }
+
+    Ok(Json(updated))
+}
+
+/// 下载作业提交的文件
+pub async fn download_submission_file(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let submission = find_submission(&pool, id).await?;
+
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(submission.student_id)?;
+
+    let file_path = submission
+        .file_path
+        .ok_or_else(|| AppError::new_message("作业没有上传文件", AppErrorType::Notfound))?;
+
+    fileapi::download_by_storage_key(&pool, &file_path).await
+}
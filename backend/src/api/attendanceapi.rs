@@ -0,0 +1,284 @@
+//! 考勤API模块
+//!
+//! 提供考勤记录、批量点名和出勤率统计相关的API端点
+//! 记录、修改或删除考勤时会在同一事务中写入对应的活动记录
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
+use std::sync::Arc;
+use time::Date;
+use uuid::Uuid;
+
+use super::access::StudentScope;
+use super::activityapi::actor_activity;
+use crate::error::{AppError, AppErrorType};
+use crate::middleware::auth::Claims;
+use crate::middleware::validation::ValidatedJson;
+use crate::model::date_format;
+use crate::model::models::activity::{Activity, ActivityType};
+use crate::model::models::attendance::{
+    Attendance, AttendanceRate, RecordAttendanceRequest, RollCallRequest, UpdateAttendanceRequest,
+};
+use crate::model::query::{Page, PageParams};
+
+/// 某节课考勤的查询参数
+#[derive(Debug, Deserialize)]
+pub struct SessionQuery {
+    pub course_id: Uuid,
+    #[serde(with = "date_format::date")]
+    pub class_date: Date,
+}
+
+/// 日期范围查询参数
+#[derive(Debug, Deserialize)]
+pub struct DateRangeQuery {
+    #[serde(default, with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    #[serde(default, with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+}
+
+/// 记录单个学生的考勤
+pub async fn record_attendance(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(req): ValidatedJson<RecordAttendanceRequest>,
+) -> Result<Json<Attendance>, AppError> {
+    let user_id = claims.user_id()?;
+
+    let mut tx = pool.begin().await?;
+    let record = Attendance::upsert(
+        &mut *tx,
+        req.student_id,
+        req.course_id,
+        req.class_date,
+        req.status,
+        req.reason.as_deref(),
+        Some(user_id),
+    )
+    .await?;
+
+    let description = format!(
+        "记录考勤：学生 {} 于 {} {}",
+        record.student_id, record.class_date, record.status
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::RecordAttendance,
+            description,
+            Some(record.id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(record))
+}
+
+/// 整节课批量点名
+///
+/// 所有记录在同一事务中写入，任一失败则全部回滚
+pub async fn roll_call(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(req): ValidatedJson<RollCallRequest>,
+) -> Result<Json<Vec<Attendance>>, AppError> {
+    let user_id = claims.user_id()?;
+
+    if req.records.is_empty() {
+        return Err(AppError::new_message(
+            "点名记录不能为空",
+            AppErrorType::BadRequest,
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    if let Some(dup) = req.records.iter().find(|r| !seen.insert(r.student_id)) {
+        return Err(AppError::new_message(
+            &format!("学生 {} 在点名记录中重复", dup.student_id),
+            AppErrorType::BadRequest,
+        ));
+    }
+
+    let mut tx = pool.begin().await?;
+    let mut records = Vec::with_capacity(req.records.len());
+    for entry in &req.records {
+        let record = Attendance::upsert(
+            &mut *tx,
+            entry.student_id,
+            req.course_id,
+            req.class_date,
+            entry.status,
+            entry.reason.as_deref(),
+            Some(user_id),
+        )
+        .await?;
+        records.push(record);
+    }
+
+    let count_of = |status: &str| records.iter().filter(|r| r.status == status).count();
+    let description = format!(
+        "课程点名：{}，共 {} 人（出勤 {}，缺勤 {}，迟到 {}，请假 {}）",
+        req.class_date,
+        records.len(),
+        count_of("present"),
+        count_of("absent"),
+        count_of("late"),
+        count_of("excused")
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::RecordAttendance,
+            description,
+            Some(req.course_id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(records))
+}
+
+/// 获取考勤记录
+pub async fn get_attendance(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Attendance>, AppError> {
+    let record = Attendance::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("考勤记录不存在", AppErrorType::Notfound))?;
+
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(record.student_id)?;
+    Ok(Json(record))
+}
+
+/// 更新考勤记录
+pub async fn update_attendance(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<UpdateAttendanceRequest>,
+) -> Result<Json<Attendance>, AppError> {
+    let user_id = claims.user_id()?;
+
+    let current = Attendance::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("考勤记录不存在", AppErrorType::Notfound))?;
+
+    let mut tx = pool.begin().await?;
+    let record = Attendance::update(&mut *tx, current, req, Some(user_id)).await?;
+
+    let description = format!(
+        "修改考勤：学生 {} 于 {} {}",
+        record.student_id, record.class_date, record.status
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::RecordAttendance,
+            description,
+            Some(record.id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(record))
+}
+
+/// 删除考勤记录
+pub async fn delete_attendance(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut tx = pool.begin().await?;
+    let record = Attendance::delete(&mut *tx, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("考勤记录不存在", AppErrorType::Notfound))?;
+
+    let description = format!(
+        "删除考勤：学生 {} 于 {} {}",
+        record.student_id, record.class_date, record.status
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::DeleteAttendance,
+            description,
+            Some(record.id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 获取某节课的考勤记录
+///
+/// 只返回当前用户可以查看的学生的考勤
+pub async fn get_session_attendance(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<SessionQuery>,
+) -> Result<Json<Vec<Attendance>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let records = Attendance::find_by_session(&pool, query.course_id, query.class_date).await?;
+    Ok(Json(scope.filter(records, |r| r.student_id)))
+}
+
+/// 获取学生的考勤记录
+pub async fn get_student_attendance(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(student_id): Path<Uuid>,
+    Query(query): Query<DateRangeQuery>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Attendance>>, AppError> {
+    StudentScope::of(&pool, &claims).await?.check(student_id)?;
+
+    let records =
+        Attendance::find_by_student_id(&pool, student_id, query.start_date, query.end_date, &page)
+            .await?;
+    Ok(Json(records))
+}
+
+/// 获取学生的出勤率
+pub async fn get_student_attendance_rate(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(student_id): Path<Uuid>,
+    Query(query): Query<DateRangeQuery>,
+) -> Result<Json<AttendanceRate>, AppError> {
+    StudentScope::of(&pool, &claims).await?.check(student_id)?;
+
+    let rate =
+        Attendance::student_rate(&pool, student_id, query.start_date, query.end_date).await?;
+    Ok(Json(rate))
+}
+
+/// 获取课程的出勤率
+pub async fn get_course_attendance_rate(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(course_id): Path<Uuid>,
+    Query(query): Query<DateRangeQuery>,
+) -> Result<Json<AttendanceRate>, AppError> {
+    let rate = Attendance::course_rate(&pool, course_id, query.start_date, query.end_date).await?;
+    Ok(Json(rate))
+}
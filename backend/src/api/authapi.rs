@@ -0,0 +1,112 @@
+//! 认证API模块
+//!
+//! 提供刷新令牌轮换、退出登录和撤销会话相关的API端点
+
+use axum::{
+    Extension, Json,
+    extract::{Path, State},
+    http::StatusCode,
+};
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::error::{AppError, AppErrorType};
+use crate::middleware::auth::{self, Claims, RefreshTokenRequest, RefreshTokenResponse};
+use crate::model::models::refresh_token::RefreshToken;
+use crate::model::models::user::User;
+
+/// 刷新令牌API
+///
+/// 使用刷新令牌换取新的访问令牌，同时轮换刷新令牌。
+/// 已轮换的刷新令牌被再次使用时视为泄露，撤销整个令牌族
+pub async fn refresh_token(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> Result<Json<RefreshTokenResponse>, AppError> {
+    let invalid = || AppError::new_message("刷新令牌无效或已过期", AppErrorType::Forbidden);
+
+    let mut tx = pool.begin().await?;
+    let token = RefreshToken::find_by_token_hash_for_update(
+        &mut *tx,
+        &auth::hash_opaque_token(&req.refresh_token),
+    )
+    .await?
+    .ok_or_else(invalid)?;
+
+    if token.revoked_at.is_some() || token.expires_at <= OffsetDateTime::now_utc() {
+        return Err(invalid());
+    }
+
+    // 重复使用已轮换的令牌，撤销整个令牌族
+    if token.rotated_at.is_some() {
+        RefreshToken::revoke_family(&mut *tx, token.family_id).await?;
+        tx.commit().await?;
+        warn!(
+            "检测到刷新令牌重复使用，已撤销令牌族 {}（用户 {}）",
+            token.family_id, token.user_id
+        );
+        return Err(invalid());
+    }
+
+    let user = User::find_by_id(&pool, token.user_id)
+        .await?
+        .ok_or_else(invalid)?;
+
+    RefreshToken::mark_rotated(&mut *tx, token.id).await?;
+    let refresh_token =
+        auth::issue_refresh_token(&mut *tx, token.user_id, Some(token.family_id)).await?;
+    tx.commit().await?;
+
+    Ok(Json(RefreshTokenResponse {
+        token: auth::generate_token(&user)?,
+        refresh_token,
+    }))
+}
+
+/// 退出登录API
+///
+/// 撤销刷新令牌所在的整个令牌族，即当前登录会话
+pub async fn logout(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Json(req): Json<RefreshTokenRequest>,
+) -> Result<StatusCode, AppError> {
+    let mut tx = pool.begin().await?;
+    if let Some(token) = RefreshToken::find_by_token_hash_for_update(
+        &mut *tx,
+        &auth::hash_opaque_token(&req.refresh_token),
+    )
+    .await?
+    {
+        RefreshToken::revoke_family(&mut *tx, token.family_id).await?;
+    }
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 在所有设备上退出登录API
+///
+/// 撤销当前用户的全部刷新令牌
+pub async fn logout_all(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+) -> Result<StatusCode, AppError> {
+    RefreshToken::revoke_all_for_user(&*pool, claims.user_id()?).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 撤销指定用户的全部会话API
+pub async fn revoke_user_sessions(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(user_id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    if User::find_by_id(&pool, user_id).await?.is_none() {
+        return Err(AppError::new_message("用户不存在", AppErrorType::Notfound));
+    }
+
+    RefreshToken::revoke_all_for_user(&*pool, user_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
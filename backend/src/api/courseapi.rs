@@ -1,108 +1,93 @@
 //! 课程API模块
 //!
-//! 提供课程和课程记录相关的API端点
+//! 提供课程、课程记录、班级、选课，以及课程的迟交扣分规则和评分等级制相关的API端点
 
 use axum::{
-    Json,
+    Extension, Json,
     extract::{Path, Query, State},
     http::StatusCode,
 };
-use serde::Deserialize;
 use sqlx::{Pool, Postgres};
+use std::collections::HashSet;
 use std::sync::Arc;
-use time::Date;
+use time::OffsetDateTime;
 use uuid::Uuid;
+use validator::Validate;
 
+use super::access::StudentScope;
+use super::activityapi::actor_activity;
+use crate::error::{AppError, AppErrorType, FieldError};
+use crate::middleware::auth::Claims;
+use crate::middleware::validation::ValidatedJson;
+use crate::model::models::activity::{Activity, ActivityType};
+use crate::model::models::class_group::{
+    ClassGroup, ClassGroupFilter, CreateClassGroupRequest, UpdateClassGroupRequest,
+};
 use crate::model::models::course::{Course, CreateCourseRequest, UpdateCourseRequest};
 use crate::model::models::course_record::{
-    CourseRecord, CreateCourseRecordRequest, UpdateCourseRecordRequest,
+    CourseRecord, CourseRecordFilter, CreateCourseRecordRequest, UpdateCourseRecordRequest,
+};
+use crate::model::models::enrollment::{
+    CreateEnrollmentRequest, DropEnrollmentRequest, EnrolledCourse, Enrollment, EnrollmentFilter,
+    RosterEntry,
+};
+use crate::model::models::grading_scale::{
+    ConvertScoreParams, GradeLevel, GradingScale, ScaleKind, ScaleValue, SetGradingScaleRequest,
 };
+use crate::model::models::late_policy::{LatePolicy, SetLatePolicyRequest};
+use crate::model::models::user::{User, UserRole};
+use crate::model::query::{Page, PageParams};
 
 // ===== 课程API =====
 
 /// 创建课程
 pub async fn create_course(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Json(req): Json<CreateCourseRequest>,
-) -> Result<Json<Course>, (StatusCode, String)> {
-    match Course::create(&pool, req).await {
-        Ok(course) => Ok(Json(course)),
-        Err(e) => {
-            eprintln!("创建课程失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "创建课程失败".to_string(),
-            ))
-        }
-    }
+    ValidatedJson(req): ValidatedJson<CreateCourseRequest>,
+) -> Result<Json<Course>, AppError> {
+    let course = Course::create(&pool, req).await?;
+    Ok(Json(course))
 }
 
 /// 获取所有课程
 pub async fn get_all_courses(
     State(pool): State<Arc<Pool<Postgres>>>,
-) -> Result<Json<Vec<Course>>, (StatusCode, String)> {
-    match Course::find_all(&pool).await {
-        Ok(courses) => Ok(Json(courses)),
-        Err(e) => {
-            eprintln!("获取所有课程失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取所有课程失败".to_string(),
-            ))
-        }
-    }
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Course>>, AppError> {
+    let courses = Course::find_all(&pool, &page).await?;
+    Ok(Json(courses))
 }
 
 /// 获取课程信息
 pub async fn get_course(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Course>, (StatusCode, String)> {
-    match Course::find_by_id(&pool, id).await {
-        Ok(Some(course)) => Ok(Json(course)),
-        Ok(None) => Err((StatusCode::NOT_FOUND, "课程不存在".to_string())),
-        Err(e) => {
-            eprintln!("获取课程失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取课程失败".to_string(),
-            ))
-        }
-    }
+) -> Result<Json<Course>, AppError> {
+    let course = Course::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("课程不存在", AppErrorType::Notfound))?;
+    Ok(Json(course))
 }
 
 /// 更新课程信息
 pub async fn update_course(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateCourseRequest>,
-) -> Result<Json<Course>, (StatusCode, String)> {
-    match Course::update(&pool, id, req).await {
-        Ok(course) => Ok(Json(course)),
-        Err(e) => {
-            eprintln!("更新课程失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "更新课程失败".to_string(),
-            ))
-        }
-    }
+    ValidatedJson(req): ValidatedJson<UpdateCourseRequest>,
+) -> Result<Json<Course>, AppError> {
+    let course = Course::update(&pool, id, req).await?;
+    Ok(Json(course))
 }
 
 /// 删除课程
 pub async fn delete_course(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    match Course::delete(&pool, id).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(e) => {
-            eprintln!("删除课程失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "删除课程失败".to_string(),
-            ))
-        }
+) -> Result<StatusCode, AppError> {
+    if Course::delete(&pool, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::new_message("课程不存在", AppErrorType::Notfound))
     }
 }
 
@@ -110,17 +95,10 @@ pub async fn delete_course(
 pub async fn search_courses_by_keyword(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(keyword): Path<String>,
-) -> Result<Json<Vec<Course>>, (StatusCode, String)> {
-    match Course::find_by_keyword(&pool, &keyword).await {
-        Ok(courses) => Ok(Json(courses)),
-        Err(e) => {
-            eprintln!("查询课程失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "查询课程失败".to_string(),
-            ))
-        }
-    }
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Course>>, AppError> {
+    let courses = Course::find_by_keyword(&pool, &keyword, &page).await?;
+    Ok(Json(courses))
 }
 
 // ===== 课程记录API =====
@@ -128,169 +106,517 @@ pub async fn search_courses_by_keyword(
 /// 创建课程记录
 pub async fn create_course_record(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Json(req): Json<CreateCourseRecordRequest>,
-) -> Result<Json<CourseRecord>, (StatusCode, String)> {
-    match CourseRecord::create(&pool, req).await {
-        Ok(record) => Ok(Json(record)),
-        Err(e) => {
-            eprintln!("创建课程记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "创建课程记录失败".to_string(),
-            ))
-        }
-    }
+    ValidatedJson(req): ValidatedJson<CreateCourseRecordRequest>,
+) -> Result<Json<CourseRecord>, AppError> {
+    let record = CourseRecord::create(&pool, req).await?;
+    Ok(Json(record))
 }
 
 /// 获取课程记录
 pub async fn get_course_record(
     State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
-) -> Result<Json<CourseRecord>, (StatusCode, String)> {
-    match CourseRecord::find_by_id(&pool, id).await {
-        Ok(Some(record)) => Ok(Json(record)),
-        Ok(None) => Err((StatusCode::NOT_FOUND, "课程记录不存在".to_string())),
-        Err(e) => {
-            eprintln!("获取课程记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取课程记录失败".to_string(),
-            ))
-        }
-    }
+) -> Result<Json<CourseRecord>, AppError> {
+    let record = CourseRecord::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("课程记录不存在", AppErrorType::Notfound))?;
+
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(record.student_id)?;
+    Ok(Json(record))
 }
 
 /// 更新课程记录
 pub async fn update_course_record(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateCourseRecordRequest>,
-) -> Result<Json<CourseRecord>, (StatusCode, String)> {
-    match CourseRecord::update(&pool, id, req).await {
-        Ok(record) => Ok(Json(record)),
-        Err(e) => {
-            eprintln!("更新课程记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "更新课程记录失败".to_string(),
-            ))
-        }
-    }
+    ValidatedJson(req): ValidatedJson<UpdateCourseRecordRequest>,
+) -> Result<Json<CourseRecord>, AppError> {
+    let record = CourseRecord::update(&pool, id, req).await?;
+    Ok(Json(record))
 }
 
 /// 删除课程记录
 pub async fn delete_course_record(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    match CourseRecord::delete(&pool, id).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(e) => {
-            eprintln!("删除课程记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "删除课程记录失败".to_string(),
-            ))
-        }
+) -> Result<StatusCode, AppError> {
+    if CourseRecord::delete(&pool, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::new_message(
+            "课程记录不存在",
+            AppErrorType::Notfound,
+        ))
     }
 }
 
-/// 查询参数结构体
-#[derive(Debug, Deserialize)]
-pub struct CourseRecordQuery {
-    pub student_id: Option<Uuid>,
-    pub course_id: Option<Uuid>,
-    pub start_date: Option<Date>,
-    pub end_date: Option<Date>,
-    pub keyword: Option<String>,
-}
-
 /// 查询课程记录
+///
+/// 所有查询条件以 AND 组合，只返回当前用户可以查看的学生的课程记录
 pub async fn query_course_records(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Query(query): Query<CourseRecordQuery>,
-) -> Result<Json<Vec<CourseRecord>>, (StatusCode, String)> {
-    // 根据课程关键词查询学生的课程记录
-    if let Some(keyword) = query.keyword {
-        match CourseRecord::find_by_course_keyword(&pool, &keyword).await {
-            Ok(records) => return Ok(Json(records)),
-            Err(e) => {
-                eprintln!("查询课程记录失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询课程记录失败".to_string(),
-                ));
-            }
-        }
+    Extension(claims): Extension<Claims>,
+    Query(filter): Query<CourseRecordFilter>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<CourseRecord>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    if let Some(student_id) = filter.student_id {
+        scope.check(student_id)?;
     }
 
-    // 根据学生ID查询课程记录
-    if let Some(student_id) = query.student_id {
-        match CourseRecord::find_by_student_id(&pool, student_id).await {
-            Ok(records) => return Ok(Json(records)),
-            Err(e) => {
-                eprintln!("查询课程记录失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询课程记录失败".to_string(),
-                ));
-            }
+    let records =
+        CourseRecord::query(&pool, &filter, scope.student_ids().as_deref(), &page).await?;
+    Ok(Json(records))
+}
+
+//获取所有课程记录
+pub async fn get_all_course_records(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<CourseRecord>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let records = CourseRecord::query(
+        &pool,
+        &CourseRecordFilter::default(),
+        scope.student_ids().as_deref(),
+        &page,
+    )
+    .await?;
+    Ok(Json(records))
+}
+
+// ===== 班级API =====
+
+/// 要求负责教师是教师或管理员账号
+async fn check_teacher(pool: &Pool<Postgres>, teacher_id: Option<Uuid>) -> Result<(), AppError> {
+    let Some(teacher_id) = teacher_id else {
+        return Ok(());
+    };
+
+    let is_teacher = User::find_by_id(pool, teacher_id)
+        .await?
+        .and_then(|user| user.role.parse::<UserRole>().ok())
+        .is_some_and(|role| matches!(role, UserRole::Teacher | UserRole::Admin));
+    if is_teacher {
+        Ok(())
+    } else {
+        Err(AppError::validation(vec![FieldError::new(
+            "teacher_id",
+            "负责教师必须是教师账号",
+        )]))
+    }
+}
+
+/// 创建班级
+pub async fn create_class_group(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    ValidatedJson(req): ValidatedJson<CreateClassGroupRequest>,
+) -> Result<Json<ClassGroup>, AppError> {
+    check_teacher(&pool, req.teacher_id).await?;
+
+    let group = ClassGroup::create(&pool, req).await?;
+    Ok(Json(group))
+}
+
+/// 查询班级
+pub async fn get_class_groups(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Query(filter): Query<ClassGroupFilter>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<ClassGroup>>, AppError> {
+    let groups = ClassGroup::query(&pool, &filter, &page).await?;
+    Ok(Json(groups))
+}
+
+/// 获取班级信息
+pub async fn get_class_group(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ClassGroup>, AppError> {
+    let group = ClassGroup::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("班级不存在", AppErrorType::Notfound))?;
+    Ok(Json(group))
+}
+
+/// 更新班级信息
+///
+/// 人数上限不能低于当前在读人数
+pub async fn update_class_group(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<UpdateClassGroupRequest>,
+) -> Result<Json<ClassGroup>, AppError> {
+    check_teacher(&pool, req.teacher_id).await?;
+
+    if let Some(capacity) = req.capacity {
+        let enrolled = Enrollment::count_active(&*pool, id).await?;
+        if i64::from(capacity) < enrolled {
+            return Err(AppError::validation(vec![FieldError::new(
+                "capacity",
+                &format!("人数上限不能低于当前在读人数 {}", enrolled),
+            )]));
         }
     }
 
-    // 根据课程ID查询课程记录
-    if let Some(course_id) = query.course_id {
-        match CourseRecord::find_by_course_id(&pool, course_id).await {
-            Ok(records) => return Ok(Json(records)),
-            Err(e) => {
-                eprintln!("查询课程记录失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询课程记录失败".to_string(),
+    let group = ClassGroup::update(&pool, id, req).await?;
+    Ok(Json(group))
+}
+
+/// 删除班级
+pub async fn delete_class_group(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    if ClassGroup::delete(&pool, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::new_message("班级不存在", AppErrorType::Notfound))
+    }
+}
+
+// ===== 选课API =====
+
+/// 学生选课
+///
+/// 指定班级时班级必须属于该课程且未满员，同一学生同一课程只能有一条在读的选课
+pub async fn enroll_student(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    ValidatedJson(req): ValidatedJson<CreateEnrollmentRequest>,
+) -> Result<Json<Enrollment>, AppError> {
+    let is_student = User::find_by_id(&pool, req.student_id)
+        .await?
+        .is_some_and(|user| user.role.to_lowercase() == "student");
+    if !is_student {
+        return Err(AppError::validation(vec![FieldError::new(
+            "student_id",
+            "学生不存在",
+        )]));
+    }
+    let enrolled_on = req
+        .enrolled_on
+        .unwrap_or_else(|| OffsetDateTime::now_utc().date());
+
+    let mut tx = pool.begin().await?;
+    if let Some(class_group_id) = req.class_group_id {
+        // 锁定班级，避免并发选课超出人数上限
+        let group = ClassGroup::lock(&mut *tx, class_group_id)
+            .await?
+            .filter(|group| group.course_id == req.course_id)
+            .ok_or_else(|| {
+                AppError::validation(vec![FieldError::new(
+                    "class_group_id",
+                    "班级不存在或不属于该课程",
+                )])
+            })?;
+
+        if let Some(capacity) = group.capacity {
+            let enrolled = Enrollment::count_active(&mut *tx, group.id).await?;
+            if enrolled >= i64::from(capacity) {
+                return Err(AppError::new_message(
+                    &format!("班级 {} 已满员（{} 人）", group.name, capacity),
+                    AppErrorType::Conflict,
                 ));
             }
         }
     }
 
-    // 根据日期范围查询课程记录
-    if query.start_date.is_some() || query.end_date.is_some() {
-        match CourseRecord::find_by_date_range(&pool, query.start_date, query.end_date).await {
-            Ok(records) => return Ok(Json(records)),
-            Err(e) => {
-                eprintln!("查询课程记录失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询课程记录失败".to_string(),
-                ));
+    let enrollment = Enrollment::create(&mut *tx, &req, enrolled_on)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                AppError::new_message("该学生已在读此课程", AppErrorType::Duplicate)
             }
+            e => e.into(),
+        })?;
+    tx.commit().await?;
+
+    Ok(Json(enrollment))
+}
+
+/// 学生退课
+pub async fn drop_enrollment(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<DropEnrollmentRequest>,
+) -> Result<Json<Enrollment>, AppError> {
+    let enrollment = Enrollment::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("选课记录不存在", AppErrorType::Notfound))?;
+    if enrollment.dropped_on.is_some() {
+        return Err(AppError::new_message(
+            "该学生已退课",
+            AppErrorType::Conflict,
+        ));
+    }
+
+    let dropped_on = req
+        .dropped_on
+        .unwrap_or_else(|| OffsetDateTime::now_utc().date());
+    if dropped_on < enrollment.enrolled_on {
+        return Err(AppError::validation(vec![FieldError::new(
+            "dropped_on",
+            "退课日期不能早于选课日期",
+        )]));
+    }
+
+    let enrollment = Enrollment::withdraw(&pool, id, dropped_on).await?;
+    Ok(Json(enrollment))
+}
+
+/// 获取课程名单
+///
+/// 只返回当前用户可以查看的学生
+pub async fn get_course_roster(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Query(filter): Query<EnrollmentFilter>,
+) -> Result<Json<Vec<RosterEntry>>, AppError> {
+    Course::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("课程不存在", AppErrorType::Notfound))?;
+
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let roster = Enrollment::roster(&pool, id, &filter, scope.student_ids().as_deref()).await?;
+    Ok(Json(roster))
+}
+
+/// 获取学生所选的课程
+pub async fn get_student_courses(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Query(filter): Query<EnrollmentFilter>,
+) -> Result<Json<Vec<EnrolledCourse>>, AppError> {
+    StudentScope::of(&pool, &claims).await?.check(id)?;
+
+    let courses = Enrollment::courses_of_student(&pool, id, &filter).await?;
+    Ok(Json(courses))
+}
+
+// ===== 迟交扣分规则API =====
+
+/// 获取课程的迟交扣分规则
+pub async fn get_late_policy(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<LatePolicy>, AppError> {
+    let policy = LatePolicy::find_by_course(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("课程没有设置迟交扣分规则", AppErrorType::Notfound))?;
+    Ok(Json(policy))
+}
+
+/// 设置课程的迟交扣分规则，已有规则时覆盖
+///
+/// 只影响之后记录的分数，已扣分的提交不重新计算
+pub async fn set_late_policy(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<SetLatePolicyRequest>,
+) -> Result<Json<LatePolicy>, AppError> {
+    let course = Course::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("课程不存在", AppErrorType::Notfound))?;
+
+    let mut tx = pool.begin().await?;
+    let policy = LatePolicy::upsert(&mut *tx, id, &req).await?;
+
+    let description = format!(
+        "设置迟交扣分规则：{}，每天扣{}%，最多扣{}%，宽限{}天",
+        course.name, policy.percent_per_day, policy.max_percent, policy.grace_days
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(&claims, ActivityType::SetLatePolicy, description, Some(id))?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(policy))
+}
+
+/// 删除课程的迟交扣分规则
+pub async fn delete_late_policy(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut tx = pool.begin().await?;
+    if !LatePolicy::delete(&mut *tx, id).await? {
+        return Err(AppError::new_message(
+            "课程没有设置迟交扣分规则",
+            AppErrorType::Notfound,
+        ));
+    }
+
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::DeleteLatePolicy,
+            "删除迟交扣分规则".to_string(),
+            Some(id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ===== 评分等级制API =====
+
+/// 检查评分等级
+///
+/// 百分制不能有等级，其余类型省略等级时使用默认等级；
+/// 等级名称和最低得分率不能重复，且必须有一个等级的最低得分率为0
+fn check_grade_levels(
+    kind: ScaleKind,
+    levels: Option<Vec<GradeLevel>>,
+) -> Result<Vec<GradeLevel>, AppError> {
+    let levels = match (kind, levels) {
+        (ScaleKind::Percentage, Some(levels)) if !levels.is_empty() => {
+            return Err(AppError::validation(vec![FieldError::new(
+                "levels",
+                "百分制不能设置等级",
+            )]));
         }
+        (kind, None) => kind.default_levels(),
+        (_, Some(levels)) => levels,
+    };
+    if kind == ScaleKind::Percentage {
+        return Ok(levels);
     }
 
-    // 如果没有指定查询条件，返回所有记录
-    match CourseRecord::find_all(&pool).await {
-        Ok(records) => Ok(Json(records)),
-        Err(e) => {
-            eprintln!("查询课程记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "查询课程记录失败".to_string(),
-            ))
+    let mut errors = Vec::new();
+    let mut labels = HashSet::new();
+    let mut min_percents = HashSet::new();
+    for (i, level) in levels.iter().enumerate() {
+        if !labels.insert(level.label.trim()) {
+            errors.push(FieldError::new(
+                &format!("levels[{}].label", i),
+                "等级名称不能重复",
+            ));
         }
+        if !min_percents.insert(level.min_percent.normalize()) {
+            errors.push(FieldError::new(
+                &format!("levels[{}].min_percent", i),
+                "最低得分率不能重复",
+            ));
+        }
+    }
+    if !levels.iter().any(|level| level.min_percent.is_zero()) {
+        errors.push(FieldError::new("levels", "必须有一个等级的最低得分率为0"));
     }
+    if !errors.is_empty() {
+        return Err(AppError::validation(errors));
+    }
+
+    Ok(levels)
 }
 
-//获取所有课程记录
-pub async fn get_all_course_records(
+/// 获取课程的评分等级制
+pub async fn get_grading_scale(
     State(pool): State<Arc<Pool<Postgres>>>,
-) -> Result<Json<Vec<CourseRecord>>, (StatusCode, String)> {
-    match CourseRecord::find_all(&pool).await {
-        Ok(records) => Ok(Json(records)),
-        Err(e) => {
-            eprintln!("查询课程记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "查询课程记录失败".to_string(),
-            ))
-        }
+    Path(id): Path<Uuid>,
+) -> Result<Json<GradingScale>, AppError> {
+    let scale = GradingScale::find_by_course(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("课程没有设置评分等级制", AppErrorType::Notfound))?;
+    Ok(Json(scale))
+}
+
+/// 设置课程的评分等级制，已有时覆盖
+///
+/// 只影响之后记录的分数，已有的评分不重新换算
+pub async fn set_grading_scale(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<SetGradingScaleRequest>,
+) -> Result<Json<GradingScale>, AppError> {
+    let course = Course::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("课程不存在", AppErrorType::Notfound))?;
+    let levels = check_grade_levels(req.kind, req.levels)?;
+
+    let mut tx = pool.begin().await?;
+    let scale = GradingScale::upsert(&mut tx, id, req.kind, &levels).await?;
+
+    let levels: Vec<String> = scale
+        .levels
+        .iter()
+        .map(|level| format!("{}≥{}%", level.label, level.min_percent.normalize()))
+        .collect();
+    let description = format!(
+        "设置评分等级制：{}，{} {}",
+        course.name,
+        scale.kind,
+        levels.join("、")
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::SetGradingScale,
+            description,
+            Some(id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(scale))
+}
+
+/// 删除课程的评分等级制，删除后按百分制换算
+pub async fn delete_grading_scale(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let mut tx = pool.begin().await?;
+    if !GradingScale::delete(&mut *tx, id).await? {
+        return Err(AppError::new_message(
+            "课程没有设置评分等级制",
+            AppErrorType::Notfound,
+        ));
     }
+
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::DeleteGradingScale,
+            "删除评分等级制".to_string(),
+            Some(id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// 按课程的评分等级制换算分数，课程没有设置时按百分制
+pub async fn convert_score(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ConvertScoreParams>,
+) -> Result<Json<ScaleValue>, AppError> {
+    params.validate()?;
+    Course::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("课程不存在", AppErrorType::Notfound))?;
+
+    let scale = GradingScale::find_by_course(&pool, id).await?;
+    let value = GradingScale::convert(scale.as_ref(), params.score, params.max_score);
+    Ok(Json(value))
 }
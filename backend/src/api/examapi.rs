@@ -3,90 +3,114 @@
 //! 提供试卷和试卷记录相关的API端点
 
 use axum::{
-    Json,
-    extract::{Path, Query, State},
+    Extension, Json,
+    extract::{Multipart, Path, Query, State},
     http::StatusCode,
+    response::Response,
 };
-use serde::Deserialize;
+use rust_decimal::{Decimal, RoundingStrategy};
 use sqlx::{Pool, Postgres};
 use std::sync::Arc;
-use time::Date;
 use uuid::Uuid;
+use validator::Validate;
 
+use super::access::StudentScope;
+use super::fileapi;
+use crate::error::{AppError, AppErrorType, FieldError};
+use crate::middleware::auth::Claims;
+use crate::middleware::validation::ValidatedJson;
 use crate::model::models::exam::{CreateExamRequest, Exam, UpdateExamRequest};
 use crate::model::models::exam_record::{
-    CreateExamRecordRequest, ExamRecord, UpdateExamRecordRequest,
+    CreateExamRecordRequest, ExamRecord, ExamRecordFilter, ExamStats, ExamStatsFilter,
+    MAX_SCORE_BUCKETS, UpdateExamRecordRequest,
 };
+use crate::model::models::grade_category::GradeCategory;
+use crate::model::query::{Page, PageParams};
+
+/// 要求成绩类别存在
+async fn check_category(pool: &Pool<Postgres>, category_id: Option<Uuid>) -> Result<(), AppError> {
+    let Some(category_id) = category_id else {
+        return Ok(());
+    };
+    if GradeCategory::find_by_id(pool, category_id)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::validation(vec![FieldError::new(
+            "category_id",
+            "成绩类别不存在",
+        )]));
+    }
+    Ok(())
+}
+
+/// 要求分数不超过试卷的满分，没有满分时按100分计
+async fn check_score(
+    pool: &Pool<Postgres>,
+    exam_id: Uuid,
+    score: Option<Decimal>,
+) -> Result<(), AppError> {
+    let Some(score) = score else {
+        return Ok(());
+    };
+    let max_score = Exam::find_by_id(pool, exam_id)
+        .await?
+        .and_then(|exam| exam.max_score)
+        .unwrap_or(Decimal::ONE_HUNDRED);
+    if score > max_score {
+        return Err(AppError::validation(vec![FieldError::new(
+            "score",
+            &format!("分数不能超过满分 {}", max_score.normalize()),
+        )]));
+    }
+    Ok(())
+}
 
 // ===== 试卷API =====
 
 /// 创建试卷
 pub async fn create_exam(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Json(req): Json<CreateExamRequest>,
-) -> Result<Json<Exam>, (StatusCode, String)> {
-    match Exam::create(&pool, req).await {
-        Ok(exam) => Ok(Json(exam)),
-        Err(e) => {
-            eprintln!("创建试卷失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "创建试卷失败".to_string(),
-            ))
-        }
-    }
+    ValidatedJson(req): ValidatedJson<CreateExamRequest>,
+) -> Result<Json<Exam>, AppError> {
+    check_category(&pool, req.category_id).await?;
+
+    let exam = Exam::create(&pool, req).await?;
+    Ok(Json(exam))
 }
 
 /// 获取试卷信息
 pub async fn get_exam(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Exam>, (StatusCode, String)> {
-    match Exam::find_by_id(&pool, id).await {
-        Ok(Some(exam)) => Ok(Json(exam)),
-        Ok(None) => Err((StatusCode::NOT_FOUND, "试卷不存在".to_string())),
-        Err(e) => {
-            eprintln!("获取试卷失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取试卷失败".to_string(),
-            ))
-        }
-    }
+) -> Result<Json<Exam>, AppError> {
+    let exam = Exam::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("试卷不存在", AppErrorType::Notfound))?;
+    Ok(Json(exam))
 }
 
 /// 更新试卷信息
 pub async fn update_exam(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateExamRequest>,
-) -> Result<Json<Exam>, (StatusCode, String)> {
-    match Exam::update(&pool, id, req).await {
-        Ok(exam) => Ok(Json(exam)),
-        Err(e) => {
-            eprintln!("更新试卷失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "更新试卷失败".to_string(),
-            ))
-        }
-    }
+    ValidatedJson(req): ValidatedJson<UpdateExamRequest>,
+) -> Result<Json<Exam>, AppError> {
+    check_category(&pool, req.category_id).await?;
+
+    let exam = Exam::update(&pool, id, req).await?;
+    Ok(Json(exam))
 }
 
 /// 删除试卷
 pub async fn delete_exam(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    match Exam::delete(&pool, id).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(e) => {
-            eprintln!("删除试卷失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "删除试卷失败".to_string(),
-            ))
-        }
+) -> Result<StatusCode, AppError> {
+    if Exam::delete(&pool, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::new_message("考试不存在", AppErrorType::Notfound))
     }
 }
 
@@ -94,172 +118,188 @@ pub async fn delete_exam(
 pub async fn search_exams_by_keyword(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(keyword): Path<String>,
-) -> Result<Json<Vec<Exam>>, (StatusCode, String)> {
-    match Exam::find_by_keyword(&pool, &keyword).await {
-        Ok(exams) => Ok(Json(exams)),
-        Err(e) => {
-            eprintln!("查询试卷失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "查询试卷失败".to_string(),
-            ))
-        }
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Exam>>, AppError> {
+    let exams = Exam::find_by_keyword(&pool, &keyword, &page).await?;
+    Ok(Json(exams))
+}
+
+/// 上传试卷文件
+///
+/// 接收 multipart 表单中的 `file` 字段，保存后替换试卷原有文件
+pub async fn upload_exam_file(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<Exam>, AppError> {
+    let exam = Exam::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("试卷不存在", AppErrorType::Notfound))?;
+
+    let uploaded_by = claims.user_id().ok();
+    let file = fileapi::receive_upload(&pool, &mut multipart, "exams", uploaded_by).await?;
+    let exam_updated = Exam::update_file_path(&pool, id, Some(file.storage_key.as_str())).await?;
+
+    // 清理被替换的旧文件
+    if let Some(old_path) = exam.file_path {
+        fileapi::remove_by_storage_key(&pool, &old_path).await?;
     }
+
+    Ok(Json(exam_updated))
+}
+
+/// 下载试卷文件
+pub async fn download_exam_file(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let exam = Exam::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("试卷不存在", AppErrorType::Notfound))?;
+
+    let file_path = exam
+        .file_path
+        .ok_or_else(|| AppError::new_message("试卷没有上传文件", AppErrorType::Notfound))?;
+
+    fileapi::download_by_storage_key(&pool, &file_path).await
 }
 
 // ===== 试卷记录API =====
 
 /// 创建试卷记录
+///
+/// 分数不能超过试卷的满分
 pub async fn create_exam_record(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Json(req): Json<CreateExamRecordRequest>,
-) -> Result<Json<ExamRecord>, (StatusCode, String)> {
-    match ExamRecord::create(&pool, req).await {
-        Ok(record) => Ok(Json(record)),
-        Err(e) => {
-            eprintln!("创建试卷记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "创建试卷记录失败".to_string(),
-            ))
-        }
-    }
+    ValidatedJson(req): ValidatedJson<CreateExamRecordRequest>,
+) -> Result<Json<ExamRecord>, AppError> {
+    check_score(&pool, req.exam_id, req.score).await?;
+
+    let record = ExamRecord::create(&pool, req).await?;
+    Ok(Json(record))
 }
 
 /// 获取试卷记录
 pub async fn get_exam_record(
     State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ExamRecord>, AppError> {
+    let record = ExamRecord::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("试卷记录不存在", AppErrorType::Notfound))?;
+
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(record.student_id)?;
+    Ok(Json(record))
+}
+
+/// 获取试卷的成绩统计
+///
+/// 只统计当前用户可访问的学生，可按年级和完成日期筛选。
+/// 按试卷满分（没有满分时为100）划分的分数段不能超过 [`MAX_SCORE_BUCKETS`] 个
+pub async fn get_exam_stats(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
-) -> Result<Json<ExamRecord>, (StatusCode, String)> {
-    match ExamRecord::find_by_id(&pool, id).await {
-        Ok(Some(record)) => Ok(Json(record)),
-        Ok(None) => Err((StatusCode::NOT_FOUND, "试卷记录不存在".to_string())),
-        Err(e) => {
-            eprintln!("获取试卷记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取试卷记录失败".to_string(),
-            ))
+    Query(filter): Query<ExamStatsFilter>,
+) -> Result<Json<ExamStats>, AppError> {
+    filter.validate()?;
+    let exam = Exam::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("试卷不存在", AppErrorType::Notfound))?;
+
+    if let Some(bucket_size) = filter.bucket_size {
+        let full_score = exam.max_score.unwrap_or(Decimal::ONE_HUNDRED);
+        let min_size = (full_score / Decimal::from(MAX_SCORE_BUCKETS))
+            .round_dp_with_strategy(2, RoundingStrategy::AwayFromZero);
+        if bucket_size < min_size {
+            return Err(AppError::validation(vec![FieldError::new(
+                "bucket_size",
+                &format!(
+                    "分数段不能超过{}个，分数段宽度至少为{}",
+                    MAX_SCORE_BUCKETS,
+                    min_size.normalize()
+                ),
+            )]));
         }
     }
+
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let stats = ExamRecord::stats(&pool, id, &filter, scope.student_ids().as_deref()).await?;
+    Ok(Json(stats))
 }
 
 /// 更新试卷记录
+///
+/// 修改后的分数不能超过修改后试卷的满分
 pub async fn update_exam_record(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateExamRecordRequest>,
-) -> Result<Json<ExamRecord>, (StatusCode, String)> {
-    match ExamRecord::update(&pool, id, req).await {
-        Ok(record) => Ok(Json(record)),
-        Err(e) => {
-            eprintln!("更新试卷记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "更新试卷记录失败".to_string(),
-            ))
-        }
-    }
+    ValidatedJson(req): ValidatedJson<UpdateExamRecordRequest>,
+) -> Result<Json<ExamRecord>, AppError> {
+    let record = ExamRecord::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("试卷记录不存在", AppErrorType::Notfound))?;
+    check_score(
+        &pool,
+        req.exam_id.unwrap_or(record.exam_id),
+        req.score.or(record.score),
+    )
+    .await?;
+
+    let record = ExamRecord::update(&pool, id, req).await?;
+    Ok(Json(record))
 }
 
 /// 删除试卷记录
 pub async fn delete_exam_record(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    match ExamRecord::delete(&pool, id).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(e) => {
-            eprintln!("删除试卷记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "删除试卷记录失败".to_string(),
-            ))
-        }
+) -> Result<StatusCode, AppError> {
+    if ExamRecord::delete(&pool, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::new_message(
+            "考试记录不存在",
+            AppErrorType::Notfound,
+        ))
     }
 }
 
-/// 查询参数结构体
-#[derive(Debug, Deserialize)]
-pub struct ExamRecordQuery {
-    pub student_id: Option<Uuid>,
-    pub exam_id: Option<Uuid>,
-    pub start_date: Option<Date>,
-    pub end_date: Option<Date>,
-}
-
 /// 查询试卷记录
+///
+/// 所有查询条件以 AND 组合，只返回当前用户可以查看的学生的试卷记录
 pub async fn query_exam_records(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Query(query): Query<ExamRecordQuery>,
-) -> Result<Json<Vec<ExamRecord>>, (StatusCode, String)> {
-    // 根据学生ID查询试卷记录
-    if let Some(student_id) = query.student_id {
-        match ExamRecord::find_by_student_id(&pool, student_id).await {
-            Ok(records) => return Ok(Json(records)),
-            Err(e) => {
-                eprintln!("查询试卷记录失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询试卷记录失败".to_string(),
-                ));
-            }
-        }
-    }
-
-    // 根据试卷ID查询试卷记录 - 查询做了该试卷的学生
-    if let Some(exam_id) = query.exam_id {
-        match ExamRecord::find_by_exam_id(&pool, exam_id).await {
-            Ok(records) => return Ok(Json(records)),
-            Err(e) => {
-                eprintln!("查询试卷记录失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询试卷记录失败".to_string(),
-                ));
-            }
-        }
+    Extension(claims): Extension<Claims>,
+    Query(filter): Query<ExamRecordFilter>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<ExamRecord>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    if let Some(student_id) = filter.student_id {
+        scope.check(student_id)?;
     }
 
-    // 根据日期范围查询试卷记录
-    if query.start_date.is_some() || query.end_date.is_some() {
-        match ExamRecord::find_by_date_range(&pool, query.start_date, query.end_date).await {
-            Ok(records) => return Ok(Json(records)),
-            Err(e) => {
-                eprintln!("查询试卷记录失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询试卷记录失败".to_string(),
-                ));
-            }
-        }
-    }
-
-    // 如果没有指定查询条件，返回所有记录
-    match ExamRecord::find_all(&pool).await {
-        Ok(records) => Ok(Json(records)),
-        Err(e) => {
-            eprintln!("查询试卷记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "查询试卷记录失败".to_string(),
-            ))
-        }
-    }
+    let records = ExamRecord::query(&pool, &filter, scope.student_ids().as_deref(), &page).await?;
+    Ok(Json(records))
 }
 
 //获取所有试卷记录
 pub async fn get_all_exam_records(
     State(pool): State<Arc<Pool<Postgres>>>,
-) -> Result<Json<Vec<ExamRecord>>, (StatusCode, String)> {
-    match ExamRecord::find_all(&pool).await {
-        Ok(records) => Ok(Json(records)),
-        Err(e) => {
-            eprintln!("查询试卷记录失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "查询试卷记录失败".to_string(),
-            ))
-        }
-    }
+    Extension(claims): Extension<Claims>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<ExamRecord>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let records = ExamRecord::query(
+        &pool,
+        &ExamRecordFilter::default(),
+        scope.student_ids().as_deref(),
+        &page,
+    )
+    .await?;
+    Ok(Json(records))
 }
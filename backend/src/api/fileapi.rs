@@ -0,0 +1,220 @@
+//! 文件API模块
+//!
+//! 提供文件上传、下载的通用处理逻辑和端点
+
+use axum::{
+    Extension,
+    extract::{Multipart, Path, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::access::StudentScope;
+use crate::config;
+use crate::error::{AppError, AppErrorType};
+use crate::middleware::auth::Claims;
+use crate::model::models::file::{CreateFileRequest, FileOwner, StoredFile};
+use crate::model::models::permission::Permission;
+use crate::model::models::user::UserRole;
+use crate::storage;
+
+/// multipart 表单中文件字段的名称
+const FILE_FIELD: &str = "file";
+
+/// 接收 multipart 表单中的文件并保存到存储后端
+///
+/// 校验文件大小和MIME类型，计算内容哈希后写入存储并记录文件元数据
+pub async fn receive_upload(
+    pool: &Pool<Postgres>,
+    multipart: &mut Multipart,
+    category: &str,
+    uploaded_by: Option<Uuid>,
+) -> Result<StoredFile, AppError> {
+    let storage_config = &config::get_config().storage;
+
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::new(e, AppErrorType::BadRequest))?
+    {
+        if field.name() != Some(FILE_FIELD) {
+            continue;
+        }
+
+        let original_name = sanitize_file_name(field.file_name().unwrap_or("unnamed"));
+        let content_type = field
+            .content_type()
+            .unwrap_or("application/octet-stream")
+            .to_string();
+        storage::check_mime_type(storage_config, &content_type)?;
+
+        // 分块读取，超过大小上限立即拒绝
+        let mut data = Vec::new();
+        while let Some(chunk) = field
+            .chunk()
+            .await
+            .map_err(|e| AppError::new(e, AppErrorType::BadRequest))?
+        {
+            if (data.len() + chunk.len()) as u64 > storage_config.max_file_size {
+                return Err(AppError::new_message(
+                    &format!("文件大小超过上限 {} 字节", storage_config.max_file_size),
+                    AppErrorType::FileTooLarge,
+                ));
+            }
+            data.extend_from_slice(&chunk);
+        }
+
+        if data.is_empty() {
+            return Err(AppError::new_message(
+                "上传的文件为空",
+                AppErrorType::BadRequest,
+            ));
+        }
+
+        let id = Uuid::new_v4();
+        let storage_key = match extension_of(&original_name) {
+            Some(ext) => format!("{}/{}.{}", category, id, ext),
+            None => format!("{}/{}", category, id),
+        };
+
+        let backend = storage::get_storage();
+        backend.put(&storage_key, &data).await?;
+
+        let req = CreateFileRequest {
+            storage_key: storage_key.clone(),
+            original_name,
+            content_type,
+            size: data.len() as i64,
+            sha256: storage::content_hash(&data),
+            uploaded_by,
+        };
+
+        return match StoredFile::create(pool, id, req).await {
+            Ok(file) => Ok(file),
+            Err(e) => {
+                // 元数据写入失败时清理已保存的文件
+                let _ = backend.delete(&storage_key).await;
+                Err(e.into())
+            }
+        };
+    }
+
+    Err(AppError::new_message(
+        &format!("缺少文件字段: {}", FILE_FIELD),
+        AppErrorType::BadRequest,
+    ))
+}
+
+/// 删除文件及其元数据（按存储键）
+///
+/// 用于替换作业、试卷的文件时清理旧文件，找不到记录时忽略
+pub async fn remove_by_storage_key(
+    pool: &Pool<Postgres>,
+    storage_key: &str,
+) -> Result<(), AppError> {
+    if let Some(file) = StoredFile::find_by_storage_key(pool, storage_key).await? {
+        storage::get_storage().delete(&file.storage_key).await?;
+        StoredFile::delete(pool, file.id).await?;
+    }
+    Ok(())
+}
+
+/// 按存储键读取文件并生成下载响应
+pub async fn download_by_storage_key(
+    pool: &Pool<Postgres>,
+    storage_key: &str,
+) -> Result<Response, AppError> {
+    let file = StoredFile::find_by_storage_key(pool, storage_key)
+        .await?
+        .ok_or_else(|| AppError::new_message("文件不存在", AppErrorType::Notfound))?;
+    file_response(file).await
+}
+
+/// 下载文件
+///
+/// 按文件的归属检查权限：提交的文件受数据访问范围限制，试卷文件和作业任务附件分别需要
+/// `exam:read` 和 `homework:read` 权限，未被引用的文件只有上传者和管理员可以下载
+pub async fn download_file(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let file = StoredFile::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("文件不存在", AppErrorType::Notfound))?;
+
+    match file.owner(&pool).await? {
+        FileOwner::Submission { student_id } => {
+            StudentScope::of(&pool, &claims).await?.check(student_id)?
+        }
+        FileOwner::Exam => claims.require(Permission::ExamRead)?,
+        FileOwner::Assignment => claims.require(Permission::HomeworkRead)?,
+        FileOwner::None => {
+            if claims.user_role()? != UserRole::Admin && file.uploaded_by != Some(claims.user_id()?)
+            {
+                return Err(AppError::new_message(
+                    "无权下载该文件",
+                    AppErrorType::Forbidden,
+                ));
+            }
+        }
+    }
+
+    file_response(file).await
+}
+
+/// 从存储后端读取文件内容并构造带下载头的响应
+async fn file_response(file: StoredFile) -> Result<Response, AppError> {
+    let data = storage::get_storage().get(&file.storage_key).await?;
+
+    let disposition = format!(
+        "attachment; filename*=UTF-8''{}",
+        percent_encode(&file.original_name)
+    );
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, file.content_type),
+            (header::CONTENT_DISPOSITION, disposition),
+            (header::ETAG, format!("\"{}\"", file.sha256)),
+        ],
+        data,
+    )
+        .into_response())
+}
+
+/// 去掉客户端文件名中的路径部分
+fn sanitize_file_name(name: &str) -> String {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or_default().trim();
+    if name.is_empty() {
+        "unnamed".to_string()
+    } else {
+        name.chars().take(255).collect()
+    }
+}
+
+/// 获取文件扩展名（仅保留字母数字）
+fn extension_of(name: &str) -> Option<String> {
+    let (_, ext) = name.rsplit_once('.')?;
+    if !ext.is_empty() && ext.len() <= 10 && ext.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Some(ext.to_lowercase())
+    } else {
+        None
+    }
+}
+
+/// 按 RFC 5987 对文件名进行百分号编码
+pub(super) fn percent_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
@@ -0,0 +1,462 @@
+//! 总评成绩API模块
+//!
+//! 提供成绩类别、免做记录，以及总评的预览、调整、锁定和解锁相关的API端点
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+use super::access::StudentScope;
+use super::activityapi::actor_activity;
+use crate::error::{AppError, AppErrorType, FieldError};
+use crate::middleware::auth::Claims;
+use crate::middleware::validation::ValidatedJson;
+use crate::model::models::activity::{Activity, ActivityType};
+use crate::model::models::assignment::Assignment;
+use crate::model::models::course::Course;
+use crate::model::models::enrollment::Enrollment;
+use crate::model::models::exam::Exam;
+use crate::model::models::final_grade::{
+    FinalGrade, FinalGradeParams, FinalGradePreview, LockFinalGradesRequest,
+    OverrideFinalGradeRequest,
+};
+use crate::model::models::grade_category::{
+    CreateGradeCategoryRequest, GradeCategory, GradeCategoryFilter, UpdateGradeCategoryRequest,
+};
+use crate::model::models::grade_excusal::{CreateGradeExcusalRequest, GradeExcusal};
+use crate::model::models::grading_scale::GradingScale;
+use crate::model::models::user::{User, UserRole};
+
+/// 查找课程，不存在时返回 404
+async fn find_course(pool: &Pool<Postgres>, id: Uuid) -> Result<Course, AppError> {
+    Course::find_by_id(pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("课程不存在", AppErrorType::Notfound))
+}
+
+/// 学生和家长只能查看已锁定的总评
+fn locked_only(claims: &Claims) -> Result<bool, AppError> {
+    Ok(matches!(
+        claims.user_role()?,
+        UserRole::Student | UserRole::Parent
+    ))
+}
+
+/// 同一课程同一学期的成绩类别名称重复时返回自定义的错误信息
+fn map_category_error(e: sqlx::Error) -> AppError {
+    match e {
+        sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+            AppError::new_message("该课程在此学期已有同名的成绩类别", AppErrorType::Duplicate)
+        }
+        e => e.into(),
+    }
+}
+
+/// 确定参与总评的学生
+///
+/// 指定学生时要求在访问范围内，否则为课程中所有在读且在访问范围内的学生
+async fn course_students(
+    pool: &Pool<Postgres>,
+    claims: &Claims,
+    course_id: Uuid,
+    student_ids: Option<Vec<Uuid>>,
+) -> Result<Vec<Uuid>, AppError> {
+    let scope = StudentScope::of(pool, claims).await?;
+    match student_ids {
+        Some(student_ids) => {
+            for &student_id in &student_ids {
+                scope.check(student_id)?;
+            }
+            Ok(student_ids)
+        }
+        None => {
+            let student_ids = Enrollment::active_student_ids(pool, course_id).await?;
+            Ok(scope.filter(student_ids, |&id| id))
+        }
+    }
+}
+
+// ===== 成绩类别API =====
+
+/// 创建成绩类别
+pub async fn create_grade_category(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    ValidatedJson(req): ValidatedJson<CreateGradeCategoryRequest>,
+) -> Result<Json<GradeCategory>, AppError> {
+    if Course::find_by_id(&pool, req.course_id).await?.is_none() {
+        return Err(AppError::validation(vec![FieldError::new(
+            "course_id",
+            "课程不存在",
+        )]));
+    }
+
+    let category = GradeCategory::create(&pool, req)
+        .await
+        .map_err(map_category_error)?;
+    Ok(Json(category))
+}
+
+/// 获取课程的成绩类别，可按学期筛选
+pub async fn get_grade_categories(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+    Query(filter): Query<GradeCategoryFilter>,
+) -> Result<Json<Vec<GradeCategory>>, AppError> {
+    find_course(&pool, id).await?;
+
+    let categories = GradeCategory::find_by_course(&pool, id, filter.term.as_deref()).await?;
+    Ok(Json(categories))
+}
+
+/// 更新成绩类别
+pub async fn update_grade_category(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<UpdateGradeCategoryRequest>,
+) -> Result<Json<GradeCategory>, AppError> {
+    let category = GradeCategory::update(&pool, id, req)
+        .await
+        .map_err(map_category_error)?;
+    Ok(Json(category))
+}
+
+/// 删除成绩类别，其中的作业任务和试卷不再计入总评
+pub async fn delete_grade_category(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    if !GradeCategory::delete(&pool, id).await? {
+        return Err(AppError::new_message(
+            "成绩类别不存在",
+            AppErrorType::Notfound,
+        ));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ===== 免做API =====
+
+/// 登记免做
+///
+/// 作业任务和试卷必须二选一，同一学生的同一项目只能登记一次
+pub async fn create_grade_excusal(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(req): ValidatedJson<CreateGradeExcusalRequest>,
+) -> Result<Json<GradeExcusal>, AppError> {
+    match (req.assignment_id, req.exam_id) {
+        (Some(assignment_id), None) => {
+            if Assignment::find_by_id(&pool, assignment_id)
+                .await?
+                .is_none()
+            {
+                return Err(AppError::validation(vec![FieldError::new(
+                    "assignment_id",
+                    "作业任务不存在",
+                )]));
+            }
+        }
+        (None, Some(exam_id)) => {
+            if Exam::find_by_id(&pool, exam_id).await?.is_none() {
+                return Err(AppError::validation(vec![FieldError::new(
+                    "exam_id",
+                    "试卷不存在",
+                )]));
+            }
+        }
+        _ => {
+            return Err(AppError::validation(vec![FieldError::new(
+                "assignment_id",
+                "作业任务和试卷必须二选一",
+            )]));
+        }
+    }
+
+    let is_student = User::find_by_id(&pool, req.student_id)
+        .await?
+        .is_some_and(|user| user.role.to_lowercase() == "student");
+    if !is_student {
+        return Err(AppError::validation(vec![FieldError::new(
+            "student_id",
+            "学生不存在",
+        )]));
+    }
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(req.student_id)?;
+
+    let excusal = GradeExcusal::create(&pool, req, claims.user_id()?)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
+                AppError::new_message("该学生已免做此项目", AppErrorType::Duplicate)
+            }
+            e => e.into(),
+        })?;
+    Ok(Json(excusal))
+}
+
+/// 获取学生的免做记录
+pub async fn get_student_grade_excusals(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<GradeExcusal>>, AppError> {
+    StudentScope::of(&pool, &claims).await?.check(id)?;
+
+    let excusals = GradeExcusal::find_by_student(&pool, id).await?;
+    Ok(Json(excusals))
+}
+
+/// 撤销免做
+pub async fn delete_grade_excusal(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let excusal = GradeExcusal::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("免做记录不存在", AppErrorType::Notfound))?;
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(excusal.student_id)?;
+
+    GradeExcusal::delete(&pool, id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ===== 总评API =====
+
+/// 预览课程某学期的总评
+///
+/// 按当前的成绩类别、作业和试卷成绩计算，不保存；未指定学生时为课程中所有在读的学生
+pub async fn preview_final_grades(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<FinalGradeParams>,
+) -> Result<Json<Vec<FinalGradePreview>>, AppError> {
+    find_course(&pool, id).await?;
+
+    let student_ids =
+        course_students(&pool, &claims, id, params.student_id.map(|id| vec![id])).await?;
+    let today = OffsetDateTime::now_utc().date();
+    let previews = FinalGradePreview::compute(&pool, id, &params.term, &student_ids, today).await?;
+    Ok(Json(previews))
+}
+
+/// 获取课程某学期已保存的总评
+///
+/// 学生和家长只能查看已锁定的总评
+pub async fn get_final_grades(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<FinalGradeParams>,
+) -> Result<Json<Vec<FinalGrade>>, AppError> {
+    find_course(&pool, id).await?;
+
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let students = match params.student_id {
+        Some(student_id) => {
+            scope.check(student_id)?;
+            Some(vec![student_id])
+        }
+        None => scope.student_ids(),
+    };
+    let grades = FinalGrade::find_by_course(
+        &pool,
+        id,
+        &params.term,
+        students.as_deref(),
+        locked_only(&claims)?,
+    )
+    .await?;
+    Ok(Json(grades))
+}
+
+/// 获取学生各课程已保存的总评
+///
+/// 学生和家长只能查看已锁定的总评
+pub async fn get_student_final_grades(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<FinalGrade>>, AppError> {
+    StudentScope::of(&pool, &claims).await?.check(id)?;
+
+    let grades = FinalGrade::find_by_student(&pool, id, locked_only(&claims)?).await?;
+    Ok(Json(grades))
+}
+
+/// 调整学生的总评，分数为空时取消调整
+///
+/// 调整必须填写原因，已锁定的总评需要先解锁
+pub async fn override_final_grade(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path((id, student_id)): Path<(Uuid, Uuid)>,
+    ValidatedJson(req): ValidatedJson<OverrideFinalGradeRequest>,
+) -> Result<Json<FinalGrade>, AppError> {
+    let course = find_course(&pool, id).await?;
+    StudentScope::of(&pool, &claims).await?.check(student_id)?;
+
+    let reason = req
+        .reason
+        .as_deref()
+        .map(str::trim)
+        .filter(|reason| !reason.is_empty());
+    if req.score.is_some() && reason.is_none() {
+        return Err(AppError::validation(vec![FieldError::new(
+            "reason",
+            "调整总评必须填写原因",
+        )]));
+    }
+    let reason = req.score.and(reason);
+
+    let today = OffsetDateTime::now_utc().date();
+    let preview = FinalGradePreview::compute(&pool, id, &req.term, &[student_id], today)
+        .await?
+        .pop()
+        .ok_or_else(|| AppError::new_message("学生不存在", AppErrorType::Notfound))?;
+    let scale = GradingScale::find_by_course(&pool, id).await?;
+    let grade = req
+        .score
+        .or(preview.computed_score)
+        .map(|score| GradingScale::convert(scale.as_ref(), score, None).value);
+
+    let mut tx = pool.begin().await?;
+    let final_grade = FinalGrade::save_override(
+        &mut *tx,
+        student_id,
+        id,
+        &req.term,
+        preview.computed_score,
+        req.score,
+        reason,
+        grade.as_deref(),
+    )
+    .await?
+    .ok_or_else(|| AppError::new_message("总评成绩已锁定", AppErrorType::Conflict))?;
+
+    let description = match req.score {
+        Some(score) => format!(
+            "调整总评：{} {} {}，{}分，原因：{}",
+            course.name,
+            req.term,
+            preview.username,
+            score.normalize(),
+            reason.unwrap_or_default()
+        ),
+        None => format!(
+            "取消总评调整：{} {} {}",
+            course.name, req.term, preview.username
+        ),
+    };
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::OverrideFinalGrade,
+            description,
+            Some(final_grade.id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(final_grade))
+}
+
+/// 锁定课程某学期的总评
+///
+/// 保存锁定时的总评和等级，之后成绩变化不再影响总评；
+/// 没有成绩的学生和已锁定的总评跳过，返回本次锁定的总评
+pub async fn lock_final_grades(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<LockFinalGradesRequest>,
+) -> Result<Json<Vec<FinalGrade>>, AppError> {
+    let course = find_course(&pool, id).await?;
+    let locked_by = claims.user_id()?;
+
+    let student_ids = course_students(&pool, &claims, id, req.student_ids).await?;
+    let today = OffsetDateTime::now_utc().date();
+    let previews = FinalGradePreview::compute(&pool, id, &req.term, &student_ids, today).await?;
+
+    let mut tx = pool.begin().await?;
+    let mut locked = Vec::new();
+    for preview in previews.iter().filter(|preview| preview.score.is_some()) {
+        let final_grade = FinalGrade::lock(
+            &mut *tx,
+            preview.student_id,
+            id,
+            &req.term,
+            preview.computed_score,
+            preview.grade.as_deref(),
+            locked_by,
+        )
+        .await?;
+        locked.extend(final_grade);
+    }
+
+    let description = format!(
+        "锁定总评：{} {}，{}名学生",
+        course.name,
+        req.term,
+        locked.len()
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::LockFinalGrades,
+            description,
+            Some(id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(locked))
+}
+
+/// 解锁课程某学期的总评，未指定学生时解锁全部，返回解锁的总评
+pub async fn unlock_final_grades(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<LockFinalGradesRequest>,
+) -> Result<Json<Vec<FinalGrade>>, AppError> {
+    let course = find_course(&pool, id).await?;
+
+    let mut tx = pool.begin().await?;
+    let unlocked = FinalGrade::unlock(&mut *tx, id, &req.term, req.student_ids.as_deref()).await?;
+
+    let description = format!(
+        "解锁总评：{} {}，{}名学生",
+        course.name,
+        req.term,
+        unlocked.len()
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::UnlockFinalGrades,
+            description,
+            Some(id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(unlocked))
+}
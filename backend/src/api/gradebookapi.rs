@@ -0,0 +1,49 @@
+//! 成绩册API模块
+//!
+//! 提供导出成绩册表格文件的API端点
+
+use axum::{
+    Extension,
+    extract::{Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+
+use super::access::StudentScope;
+use crate::error::AppError;
+use crate::middleware::auth::Claims;
+use crate::model::models::gradebook::{Gradebook, GradebookFilter};
+
+/// 导出成绩册
+///
+/// 以学生为行、试卷和作业为列，按 `format` 导出为 CSV 或 XLSX，只包含当前用户可访问的学生。
+/// 文件在内存中完整生成后一次性返回，不是流式输出：XLSX 需要整体打包，
+/// 导出范围可用筛选条件缩小
+pub async fn export_gradebook(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Query(filter): Query<GradebookFilter>,
+) -> Result<Response, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let gradebook = Gradebook::build(&pool, &filter, scope.student_ids().as_deref()).await?;
+    let data = gradebook.to_table().write(filter.format, "成绩册")?;
+
+    let disposition = format!(
+        "attachment; filename=\"gradebook.{}\"",
+        filter.format.extension()
+    );
+
+    Ok((
+        [
+            (
+                header::CONTENT_TYPE,
+                filter.format.content_type().to_string(),
+            ),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        data,
+    )
+        .into_response())
+}
@@ -3,207 +3,233 @@
 //! 提供作业相关的API端点
 
 use axum::{
-    Json,
-    extract::{Path, Query, State},
+    Extension, Json,
+    extract::{Multipart, Path, Query, State},
     http::StatusCode,
+    response::Response,
 };
-use serde::Deserialize;
 use sqlx::{Pool, Postgres};
 use std::sync::Arc;
-use time::Date;
 use uuid::Uuid;
 
-use crate::model::models::homework::{CreateHomeworkRequest, Homework, UpdateHomeworkRequest};
+use super::access::StudentScope;
+use super::assignmentapi::{check_raw_score, reject_grade_with_score};
+use super::fileapi;
+use crate::error::{AppError, AppErrorType, FieldError};
+use crate::middleware::auth::Claims;
+use crate::middleware::validation::ValidatedJson;
+use crate::model::models::assignment::Assignment;
+use crate::model::models::homework::{
+    CreateHomeworkRequest, Homework, HomeworkFilter, UpdateHomeworkRequest,
+};
+use crate::model::models::user::UserRole;
+use crate::model::query::{Page, PageParams};
 
 /// 创建作业
+///
+/// 学生只能为自己提交作业
 pub async fn create_homework(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Json(req): Json<CreateHomeworkRequest>,
-) -> Result<Json<Homework>, (StatusCode, String)> {
-    match Homework::create(&pool, req).await {
-        Ok(homework) => Ok(Json(homework)),
-        Err(e) => {
-            eprintln!("创建作业失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "创建作业失败".to_string(),
-            ))
-        }
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(req): ValidatedJson<CreateHomeworkRequest>,
+) -> Result<Json<Homework>, AppError> {
+    if claims.user_role()? == UserRole::Student && req.student_id != claims.user_id()? {
+        return Err(AppError::new_message(
+            "学生只能提交自己的作业",
+            AppErrorType::Forbidden,
+        ));
     }
+
+    let homework = Homework::create(&pool, req).await?;
+    Ok(Json(homework))
 }
 
 /// 获取作业信息
 pub async fn get_homework(
     State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
-) -> Result<Json<Homework>, (StatusCode, String)> {
-    match Homework::find_by_id(&pool, id).await {
-        Ok(Some(homework)) => Ok(Json(homework)),
-        Ok(None) => Err((StatusCode::NOT_FOUND, "作业不存在".to_string())),
-        Err(e) => {
-            eprintln!("获取作业失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取作业失败".to_string(),
-            ))
-        }
+) -> Result<Json<Homework>, AppError> {
+    let homework = Homework::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("作业不存在", AppErrorType::Notfound))?;
+
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(homework.student_id)?;
+    Ok(Json(homework))
+}
+
+/// 校验作业任务的字段
+///
+/// 标题、描述、截止日期和教师属于作业任务。作业任务共享时这些字段会影响其他提交，需通过作业任务接口修改
+async fn check_assignment_fields(
+    pool: &Pool<Postgres>,
+    assignment_id: Uuid,
+    req: &UpdateHomeworkRequest,
+) -> Result<(), AppError> {
+    let fields = req.assignment_fields();
+    if fields.is_empty() || !Assignment::is_shared(pool, assignment_id).await? {
+        return Ok(());
     }
+
+    Err(AppError::validation(
+        fields
+            .into_iter()
+            .map(|field| {
+                FieldError::new(
+                    field,
+                    "该字段属于多份提交共享的作业任务，请通过 /assignments/{id} 修改",
+                )
+            })
+            .collect(),
+    ))
+}
+
+/// 校验修改作业的请求：评分和分数不能同时填写，共享的作业任务不能在此修改，分数不能超过作业任务的满分
+async fn check_update(
+    pool: &Pool<Postgres>,
+    id: Uuid,
+    req: &UpdateHomeworkRequest,
+) -> Result<(), AppError> {
+    reject_grade_with_score(req.grade.as_deref(), req.score)?;
+    let homework = Homework::find_by_id(pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("作业不存在", AppErrorType::Notfound))?;
+    check_assignment_fields(pool, homework.assignment_id, req).await?;
+    check_raw_score(pool, homework.assignment_id, req.score).await
 }
 
 /// 更新作业信息
+///
+/// 作业任务共享时只能修改提交的字段，作业任务的字段需通过作业任务接口修改
 pub async fn update_homework(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateHomeworkRequest>,
-) -> Result<Json<Homework>, (StatusCode, String)> {
-    match Homework::update(&pool, id, req).await {
-        Ok(homework) => Ok(Json(homework)),
-        Err(e) => {
-            eprintln!("更新作业失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "更新作业失败".to_string(),
-            ))
-        }
-    }
+    ValidatedJson(req): ValidatedJson<UpdateHomeworkRequest>,
+) -> Result<Json<Homework>, AppError> {
+    check_update(&pool, id, &req).await?;
+
+    let homework = Homework::update(&pool, id, req).await?;
+    Ok(Json(homework))
 }
 
 /// 删除作业
 pub async fn delete_homework(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
-    match Homework::delete(&pool, id).await {
-        Ok(_) => Ok(StatusCode::NO_CONTENT),
-        Err(e) => {
-            eprintln!("删除作业失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "删除作业失败".to_string(),
-            ))
-        }
+) -> Result<StatusCode, AppError> {
+    if Homework::delete(&pool, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::new_message("作业不存在", AppErrorType::Notfound))
     }
 }
 
-/// 查询参数结构体
-#[derive(Debug, Deserialize)]
-pub struct HomeworkQuery {
-    pub student_id: Option<Uuid>,
-    pub teacher_id: Option<Uuid>,
-    pub start_date: Option<Date>,
-    pub end_date: Option<Date>,
-    pub title: Option<String>,
-}
-
 /// 查询作业
+///
+/// 所有查询条件以 AND 组合，只返回当前用户可以查看的学生的作业
 pub async fn query_homework(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Query(query): Query<HomeworkQuery>,
-) -> Result<Json<Vec<Homework>>, (StatusCode, String)> {
-    // 根据学生ID查询作业
-    if let Some(student_id) = query.student_id {
-        match Homework::find_by_student_id(&pool, student_id).await {
-            Ok(homework) => return Ok(Json(homework)),
-            Err(e) => {
-                eprintln!("查询作业失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询作业失败".to_string(),
-                ));
-            }
-        }
-    }
-
-    // 根据教师ID查询作业
-    if let Some(teacher_id) = query.teacher_id {
-        match Homework::find_by_teacher_id(&pool, teacher_id).await {
-            Ok(homework) => return Ok(Json(homework)),
-            Err(e) => {
-                eprintln!("查询作业失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询作业失败".to_string(),
-                ));
-            }
-        }
-    }
-
-    // 根据标题查询作业
-    if let Some(title) = &query.title {
-        match Homework::find_by_title(&pool, title).await {
-            Ok(homework) => return Ok(Json(homework)),
-            Err(e) => {
-                eprintln!("查询作业失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询作业失败".to_string(),
-                ));
-            }
-        }
-    }
-
-    // 根据日期范围查询作业
-    if query.start_date.is_some() || query.end_date.is_some() {
-        match Homework::find_by_date_range(&pool, query.start_date, query.end_date).await {
-            Ok(homework) => return Ok(Json(homework)),
-            Err(e) => {
-                eprintln!("查询作业失败: {}", e);
-                return Err((
-                    StatusCode::INTERNAL_SERVER_ERROR,
-                    "查询作业失败".to_string(),
-                ));
-            }
-        }
+    Extension(claims): Extension<Claims>,
+    Query(filter): Query<HomeworkFilter>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Homework>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    if let Some(student_id) = filter.student_id {
+        scope.check(student_id)?;
     }
 
-    // 如果没有指定查询条件，返回所有作业
-    match Homework::find_all(&pool).await {
-        Ok(homework) => Ok(Json(homework)),
-        Err(e) => {
-            eprintln!("查询作业失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "查询作业失败".to_string(),
-            ))
-        }
-    }
+    let homework = Homework::query(&pool, &filter, scope.student_ids().as_deref(), &page).await?;
+    Ok(Json(homework))
 }
 
 /// 教师评分作业
+///
+/// 填写分数时按作业所属课程的迟交扣分规则扣分，没有规则时不扣分
 pub async fn grade_homework(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateHomeworkRequest>,
-) -> Result<Json<Homework>, (StatusCode, String)> {
-    // 确保请求中包含评分和反馈
-    if req.grade.is_none() && req.feedback.is_none() {
-        return Err((StatusCode::BAD_REQUEST, "评分或反馈不能为空".to_string()));
+    ValidatedJson(req): ValidatedJson<UpdateHomeworkRequest>,
+) -> Result<Json<Homework>, AppError> {
+    // 确保请求中包含评分、分数或反馈
+    if req.grade.is_none() && req.score.is_none() && req.feedback.is_none() {
+        return Err(AppError::validation(vec![FieldError::new(
+            "grade",
+            "评分、分数或反馈不能为空",
+        )]));
     }
+    check_update(&pool, id, &req).await?;
 
-    match Homework::update(&pool, id, req).await {
-        Ok(homework) => Ok(Json(homework)),
-        Err(e) => {
-            eprintln!("评分作业失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "评分作业失败".to_string(),
-            ))
-        }
-    }
+    let homework = Homework::update(&pool, id, req).await?;
+    Ok(Json(homework))
 }
 
 //获取所有作业
 pub async fn get_all_homework(
     State(pool): State<Arc<Pool<Postgres>>>,
-) -> Result<Json<Vec<Homework>>, (StatusCode, String)> {
-    match Homework::find_all(&pool).await {
-        Ok(homework) => Ok(Json(homework)),
-        Err(e) => {
-            eprintln!("获取所有作业失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取所有作业失败".to_string(),
-            ))
-        }
+    Extension(claims): Extension<Claims>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Homework>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let homework = Homework::query(
+        &pool,
+        &HomeworkFilter::default(),
+        scope.student_ids().as_deref(),
+        &page,
+    )
+    .await?;
+    Ok(Json(homework))
+}
+
+/// 上传作业文件
+///
+/// 接收 multipart 表单中的 `file` 字段，保存后替换作业原有文件
+pub async fn upload_homework_file(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    mut multipart: Multipart,
+) -> Result<Json<Homework>, AppError> {
+    let homework = Homework::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("作业不存在", AppErrorType::Notfound))?;
+
+    // 学生只能为自己的作业上传文件
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(homework.student_id)?;
+
+    let uploaded_by = claims.user_id().ok();
+    let file = fileapi::receive_upload(&pool, &mut multipart, "homework", uploaded_by).await?;
+    let homework_updated =
+        Homework::update_file_path(&pool, id, Some(file.storage_key.as_str())).await?;
+
+    // 清理被替换的旧文件
+    if let Some(old_path) = homework.file_path {
+        fileapi::remove_by_storage_key(&pool, &old_path).await?;
     }
+
+    Ok(Json(homework_updated))
+}
+
+/// 下载作业文件
+pub async fn download_homework_file(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Response, AppError> {
+    let homework = Homework::find_by_id(&pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("作业不存在", AppErrorType::Notfound))?;
+
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(homework.student_id)?;
+
+    let file_path = homework
+        .file_path
+        .ok_or_else(|| AppError::new_message("作业没有上传文件", AppErrorType::Notfound))?;
+
+    fileapi::download_by_storage_key(&pool, &file_path).await
 }
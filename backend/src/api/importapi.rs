@@ -0,0 +1,432 @@
+//! 批量导入API模块
+//!
+//! 提供从 CSV 或 XLSX 文件批量导入学生的API端点
+
+use axum::{
+    Extension, Json,
+    extract::{Multipart, Query, State},
+};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Postgres};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::task::JoinSet;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::activityapi::actor_activity;
+use super::userapi::hash_password;
+use crate::config;
+use crate::error::{self, AppError, AppErrorType, FieldError};
+use crate::middleware::auth::{self, Claims};
+use crate::model::models::activity::{Activity, ActivityType};
+use crate::model::models::user::{CreateUserRequest, User};
+use crate::model::sheet::{Sheet, SheetFormat};
+use crate::model::validate;
+
+/// multipart 表单中文件字段的名称
+const FILE_FIELD: &str = "file";
+/// multipart 表单中列映射字段的名称
+const MAPPING_FIELD: &str = "mapping";
+
+/// 可导入的字段
+const IMPORT_FIELDS: &[&str] = &[
+    "username",
+    "email",
+    "password",
+    "display_name",
+    "grade",
+    "parent_name",
+    "parent_phone",
+    "address",
+    "notes",
+];
+/// 表格中必须包含的字段
+const REQUIRED_FIELDS: &[&str] = &["username", "email"];
+
+/// 初始密码长度
+const INITIAL_PASSWORD_LEN: usize = 16;
+
+/// 导入参数
+#[derive(Debug, Default, Deserialize)]
+pub struct ImportQuery {
+    /// 只校验不导入
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+/// 导入的学生
+#[derive(Debug, Serialize)]
+pub struct ImportedStudent {
+    /// 表格中的行号，表头为第1行
+    pub row: usize,
+    /// 学生ID，预检时为空
+    pub id: Option<Uuid>,
+    /// 用户名
+    pub username: String,
+    /// 系统生成的初始密码，表格中提供了密码或预检时为空
+    pub initial_password: Option<String>,
+}
+
+/// 导入结果
+#[derive(Debug, Serialize)]
+pub struct ImportReport {
+    /// 是否为预检
+    pub dry_run: bool,
+    /// 数据行数
+    pub total: usize,
+    /// 导入的学生数
+    pub imported: usize,
+    /// 各行的校验错误，字段为 `rows[行号].字段名` 形式
+    pub errors: Vec<FieldError>,
+    /// 通过校验的学生
+    pub students: Vec<ImportedStudent>,
+}
+
+/// 通过校验的一行
+struct ImportRow {
+    /// 行号
+    row: usize,
+    /// 创建学生的请求
+    req: CreateUserRequest,
+    /// 是否使用系统生成的密码
+    generated_password: bool,
+}
+
+/// 批量导入学生
+///
+/// 接收 multipart 表单中的 `file` 字段（CSV 或 XLSX）和可选的 `mapping` 字段（字段名到列名的 JSON 映射）。
+/// 预检时只返回校验结果；正式导入时任何一行有错误都不导入，全部通过后在同一事务中写入
+pub async fn import_students(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Query(query): Query<ImportQuery>,
+    mut multipart: Multipart,
+) -> Result<Json<ImportReport>, AppError> {
+    let (sheet, mapping) = read_import_form(&mut multipart).await?;
+    let columns = resolve_columns(&sheet, &mapping)?;
+
+    let mut errors = Vec::new();
+    let mut rows = Vec::new();
+    for (index, cells) in sheet.rows.iter().enumerate() {
+        let row = index + 2;
+        match parse_row(&columns, cells, row) {
+            Ok(import_row) => rows.push(import_row),
+            Err(row_errors) => errors.extend(row_errors),
+        }
+    }
+    check_duplicates(&pool, &mut rows, &mut errors).await?;
+
+    if !errors.is_empty() && !query.dry_run {
+        return Err(AppError::validation(errors));
+    }
+
+    if query.dry_run {
+        return Ok(Json(ImportReport {
+            dry_run: true,
+            total: sheet.rows.len(),
+            imported: 0,
+            errors,
+            students: rows
+                .into_iter()
+                .map(|row| ImportedStudent {
+                    row: row.row,
+                    id: None,
+                    username: row.req.username,
+                    initial_password: None,
+                })
+                .collect(),
+        }));
+    }
+
+    let initial_passwords: Vec<Option<String>> = rows
+        .iter()
+        .map(|row| row.generated_password.then(|| row.req.password.clone()))
+        .collect();
+    let rows = hash_passwords(rows).await?;
+
+    let mut tx = pool.begin().await?;
+    let mut students = Vec::with_capacity(rows.len());
+    for (row, initial_password) in rows.into_iter().zip(initial_passwords) {
+        let user = User::create(&mut *tx, row.req).await?;
+        students.push(ImportedStudent {
+            row: row.row,
+            id: Some(user.id),
+            username: user.username,
+            initial_password,
+        });
+    }
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::ImportStudents,
+            format!("批量导入学生 {} 人", students.len()),
+            None,
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(ImportReport {
+        dry_run: false,
+        total: sheet.rows.len(),
+        imported: students.len(),
+        errors,
+        students,
+    }))
+}
+
+/// 读取导入表单中的表格文件和列映射
+async fn read_import_form(
+    multipart: &mut Multipart,
+) -> Result<(Sheet, HashMap<String, String>), AppError> {
+    let max_file_size = config::get_config().storage.max_file_size;
+    let mut sheet = None;
+    let mut mapping = HashMap::new();
+
+    while let Some(mut field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| AppError::new(e, AppErrorType::BadRequest))?
+    {
+        match field.name() {
+            Some(FILE_FIELD) => {
+                let format = field
+                    .file_name()
+                    .and_then(SheetFormat::from_file_name)
+                    .ok_or_else(|| {
+                        AppError::new_message(
+                            "只支持 .csv 和 .xlsx 文件",
+                            AppErrorType::UnsupportedFileType,
+                        )
+                    })?;
+
+                let mut data = Vec::new();
+                while let Some(chunk) = field
+                    .chunk()
+                    .await
+                    .map_err(|e| AppError::new(e, AppErrorType::BadRequest))?
+                {
+                    if (data.len() + chunk.len()) as u64 > max_file_size {
+                        return Err(AppError::new_message(
+                            &format!("文件大小超过上限 {} 字节", max_file_size),
+                            AppErrorType::FileTooLarge,
+                        ));
+                    }
+                    data.extend_from_slice(&chunk);
+                }
+                sheet = Some(Sheet::parse(format, &data)?);
+            }
+            Some(MAPPING_FIELD) => {
+                let text = field
+                    .text()
+                    .await
+                    .map_err(|e| AppError::new(e, AppErrorType::BadRequest))?;
+                mapping = serde_json::from_str(&text).map_err(|e| {
+                    AppError::new_message(
+                        &format!("列映射格式错误: {}", e),
+                        AppErrorType::BadRequest,
+                    )
+                })?;
+            }
+            _ => {}
+        }
+    }
+
+    let sheet = sheet.ok_or_else(|| {
+        AppError::new_message(
+            &format!("缺少文件字段: {}", FILE_FIELD),
+            AppErrorType::BadRequest,
+        )
+    })?;
+    Ok((sheet, mapping))
+}
+
+/// 根据列映射确定各字段所在的列
+///
+/// 未映射的字段使用与字段名相同的列名
+fn resolve_columns(
+    sheet: &Sheet,
+    mapping: &HashMap<String, String>,
+) -> Result<HashMap<&'static str, usize>, AppError> {
+    let mut errors = Vec::new();
+    for field in mapping.keys() {
+        if !IMPORT_FIELDS.contains(&field.as_str()) {
+            errors.push(FieldError::new(
+                &format!("mapping.{}", field),
+                "不支持导入该字段",
+            ));
+        }
+    }
+
+    let mut columns = HashMap::new();
+    for &field in IMPORT_FIELDS {
+        let header = mapping.get(field).map_or(field, String::as_str);
+        match sheet.column(header) {
+            Some(column) => {
+                columns.insert(field, column);
+            }
+            None if mapping.contains_key(field) || REQUIRED_FIELDS.contains(&field) => {
+                errors.push(FieldError::new(
+                    &format!("mapping.{}", field),
+                    &format!("表格中没有列: {}", header),
+                ));
+            }
+            None => {}
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(columns)
+    } else {
+        Err(AppError::validation(errors))
+    }
+}
+
+/// 解析并校验一行数据，没有提供密码时生成初始密码
+fn parse_row(
+    columns: &HashMap<&'static str, usize>,
+    cells: &[String],
+    row: usize,
+) -> Result<ImportRow, Vec<FieldError>> {
+    let cell = |field: &str| {
+        columns
+            .get(field)
+            .and_then(|&column| cells.get(column))
+            .filter(|value| !value.is_empty())
+            .cloned()
+    };
+
+    let mut errors = Vec::new();
+    let grade = match cell("grade").map(|grade| grade.parse::<i32>()) {
+        Some(Ok(grade)) => Some(grade),
+        Some(Err(_)) => {
+            errors.push(FieldError::new(
+                &format!("rows[{}].grade", row),
+                "年级必须为整数",
+            ));
+            None
+        }
+        None => None,
+    };
+
+    let password = cell("password");
+    let generated_password = password.is_none();
+    let req = CreateUserRequest {
+        username: cell("username").unwrap_or_default(),
+        email: cell("email").unwrap_or_default(),
+        password: password.unwrap_or_else(initial_password),
+        display_name: cell("display_name"),
+        avatar_url: None,
+        bio: None,
+        role: Some("student".to_string()),
+        grade,
+        parent_name: cell("parent_name"),
+        parent_phone: cell("parent_phone"),
+        address: cell("address"),
+        notes: cell("notes"),
+        invitation_token: None,
+    };
+
+    if let Err(e) = req.validate() {
+        error::collect_field_errors(&e, &format!("rows[{}]", row), &mut errors);
+    }
+
+    if errors.is_empty() {
+        Ok(ImportRow {
+            row,
+            req,
+            generated_password,
+        })
+    } else {
+        Err(errors)
+    }
+}
+
+/// 检查表格内以及与已有用户重复的用户名和电子邮件，重复的行从待导入行中移除
+async fn check_duplicates(
+    pool: &Pool<Postgres>,
+    rows: &mut Vec<ImportRow>,
+    errors: &mut Vec<FieldError>,
+) -> Result<(), AppError> {
+    let usernames: Vec<String> = rows.iter().map(|row| row.req.username.clone()).collect();
+    let emails: Vec<String> = rows.iter().map(|row| row.req.email.clone()).collect();
+    let existing = User::find_by_usernames_or_emails(pool, &usernames, &emails).await?;
+    let taken_usernames: HashSet<&str> = existing.iter().map(|u| u.username.as_str()).collect();
+    let taken_emails: HashSet<&str> = existing.iter().map(|u| u.email.as_str()).collect();
+
+    let mut seen_usernames: HashMap<String, usize> = HashMap::new();
+    let mut seen_emails: HashMap<String, usize> = HashMap::new();
+    let mut duplicated = HashSet::new();
+    for row in rows.iter() {
+        let username = &row.req.username;
+        let email = &row.req.email;
+        let field = |name: &str| format!("rows[{}].{}", row.row, name);
+
+        if taken_usernames.contains(username.as_str()) {
+            errors.push(FieldError::new(&field("username"), "用户名已存在"));
+            duplicated.insert(row.row);
+        } else if let Some(first) = seen_usernames.get(username) {
+            errors.push(FieldError::new(
+                &field("username"),
+                &format!("与第{}行的用户名重复", first),
+            ));
+            duplicated.insert(row.row);
+        }
+        if taken_emails.contains(email.as_str()) {
+            errors.push(FieldError::new(&field("email"), "电子邮件已被使用"));
+            duplicated.insert(row.row);
+        } else if let Some(first) = seen_emails.get(email) {
+            errors.push(FieldError::new(
+                &field("email"),
+                &format!("与第{}行的电子邮件重复", first),
+            ));
+            duplicated.insert(row.row);
+        }
+
+        seen_usernames.entry(username.clone()).or_insert(row.row);
+        seen_emails.entry(email.clone()).or_insert(row.row);
+    }
+
+    rows.retain(|row| !duplicated.contains(&row.row));
+    errors.sort_by_key(|error| row_number(&error.field));
+    Ok(())
+}
+
+/// 从 `rows[行号].字段名` 形式的字段中取出行号
+fn row_number(field: &str) -> usize {
+    field
+        .strip_prefix("rows[")
+        .and_then(|rest| rest.split_once(']'))
+        .and_then(|(row, _)| row.parse().ok())
+        .unwrap_or(0)
+}
+
+/// 并行计算各行密码的哈希
+async fn hash_passwords(rows: Vec<ImportRow>) -> Result<Vec<ImportRow>, AppError> {
+    let mut tasks = JoinSet::new();
+    for (index, mut row) in rows.into_iter().enumerate() {
+        tasks.spawn_blocking(move || {
+            row.req.password = hash_password(&row.req.password)?;
+            Ok::<_, AppError>((index, row))
+        });
+    }
+
+    let mut hashed = Vec::with_capacity(tasks.len());
+    while let Some(result) = tasks.join_next().await {
+        hashed.push(result.map_err(|e| AppError::new(e, AppErrorType::Internal))??);
+    }
+    hashed.sort_by_key(|(index, _)| *index);
+    Ok(hashed.into_iter().map(|(_, row)| row).collect())
+}
+
+/// 生成同时包含字母和数字的初始密码
+fn initial_password() -> String {
+    loop {
+        let password = auth::generate_opaque_token()[..INITIAL_PASSWORD_LEN].to_string();
+        if validate::password_strength(&password).is_ok() {
+            return password;
+        }
+    }
+}
@@ -0,0 +1,86 @@
+//! 注册邀请API
+//!
+//! 提供管理员签发、查看和撤销注册邀请的API接口
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use serde::Serialize;
+use sqlx::{Pool, Postgres};
+use std::sync::Arc;
+use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::config;
+use crate::error::{AppError, AppErrorType};
+use crate::middleware::auth::{self, Claims};
+use crate::middleware::validation::ValidatedJson;
+use crate::model::models::invitation::{CreateInvitationRequest, Invitation};
+use crate::model::query::{Page, PageParams};
+
+/// 签发邀请的响应，邀请令牌只在签发时返回一次
+#[derive(Debug, Serialize)]
+pub struct IssuedInvitation {
+    /// 邀请信息
+    #[serde(flatten)]
+    pub invitation: Invitation,
+    /// 邀请令牌
+    pub token: String,
+}
+
+/// 签发注册邀请
+pub async fn create_invitation(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(req): ValidatedJson<CreateInvitationRequest>,
+) -> Result<Json<IssuedInvitation>, AppError> {
+    let hours = req
+        .expires_in_hours
+        .unwrap_or(config::get_config().registration.invitation_expiration);
+    if hours == 0 {
+        return Err(AppError::new_message(
+            "邀请有效期必须大于0",
+            AppErrorType::BadRequest,
+        ));
+    }
+    let expires_at = OffsetDateTime::now_utc() + Duration::hours(hours as i64);
+
+    let token = auth::generate_opaque_token();
+    let invitation = Invitation::create(
+        &pool,
+        &auth::hash_opaque_token(&token),
+        req.role,
+        req.email.as_deref(),
+        claims.user_id().ok(),
+        expires_at,
+    )
+    .await?;
+
+    Ok(Json(IssuedInvitation { invitation, token }))
+}
+
+/// 获取所有注册邀请
+pub async fn get_invitations(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Invitation>>, AppError> {
+    let invitations = Invitation::find_all(&pool, &page).await?;
+    Ok(Json(invitations))
+}
+
+/// 撤销注册邀请
+pub async fn revoke_invitation(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    if Invitation::revoke(&pool, id).await? {
+        Ok(StatusCode::NO_CONTENT)
+    } else {
+        Err(AppError::new_message(
+            "邀请不存在或已失效",
+            AppErrorType::Notfound,
+        ))
+    }
+}
@@ -1,122 +1,654 @@
 //! API路由模块
 //!
 //! 包含所有API端点的路由定义
+mod access;
 mod activityapi;
 mod announcementapi;
+mod assignmentapi;
+mod attendanceapi;
+mod authapi;
 mod courseapi;
 mod examapi;
+mod fileapi;
+mod finalgradeapi;
+mod gradebookapi;
 mod homeworkapi;
+mod importapi;
+mod invitationapi;
+mod questionapi;
+mod reportapi;
 mod studentapi;
 mod userapi;
 
 use axum::{
     Router,
-    middleware::from_fn,
+    extract::DefaultBodyLimit,
+    middleware::{from_fn, from_fn_with_state},
     routing::{delete, get, post, put},
 };
 
 use sqlx::{Pool, Postgres};
 use std::sync::Arc;
 
-use crate::middleware::auth;
+use crate::config;
+use crate::middleware::audit::{Audit, audit_middleware};
+use crate::middleware::{auth, permission::permission_middleware};
+use crate::model::models::activity::ActivityType;
+use crate::model::models::permission::Permission;
+
+// 各路由组所需的权限
+const USER_MANAGE: &[Permission] = &[Permission::UserManage];
+const STUDENT_READ: &[Permission] = &[Permission::StudentRead];
+const STUDENT_WRITE: &[Permission] = &[Permission::StudentWrite];
+const STUDENT_DELETE: &[Permission] = &[Permission::StudentDelete];
+const COURSE_READ: &[Permission] = &[Permission::CourseRead];
+const COURSE_WRITE: &[Permission] = &[Permission::CourseWrite];
+const COURSE_RECORD_READ: &[Permission] = &[Permission::CourseRecordRead];
+const COURSE_RECORD_WRITE: &[Permission] = &[Permission::CourseRecordWrite];
+const EXAM_READ: &[Permission] = &[Permission::ExamRead];
+const EXAM_WRITE: &[Permission] = &[Permission::ExamWrite];
+const EXAM_RECORD_READ: &[Permission] = &[Permission::ExamRecordRead];
+const EXAM_RECORD_WRITE: &[Permission] = &[Permission::ExamRecordWrite];
+const HOMEWORK_READ: &[Permission] = &[Permission::HomeworkRead];
+const HOMEWORK_SUBMIT: &[Permission] = &[Permission::HomeworkSubmit];
+const HOMEWORK_WRITE: &[Permission] = &[Permission::HomeworkWrite];
+const HOMEWORK_GRADE: &[Permission] = &[Permission::HomeworkGrade];
+const GRADEBOOK_READ: &[Permission] = &[Permission::ExamRecordRead, Permission::HomeworkRead];
+const REPORT_CARD_READ: &[Permission] = &[
+    Permission::StudentRead,
+    Permission::CourseRecordRead,
+    Permission::HomeworkRead,
+    Permission::ExamRecordRead,
+];
+const FILE_READ: &[Permission] = &[Permission::FileRead];
+const FINAL_GRADE_READ: &[Permission] = &[Permission::FinalGradeRead];
+const FINAL_GRADE_WRITE: &[Permission] = &[Permission::FinalGradeWrite];
+const FINAL_GRADE_UNLOCK: &[Permission] = &[Permission::FinalGradeUnlock];
+const ATTENDANCE_READ: &[Permission] = &[Permission::AttendanceRead];
+const ATTENDANCE_WRITE: &[Permission] = &[Permission::AttendanceWrite];
+const ACTIVITY_READ: &[Permission] = &[Permission::ActivityRead];
+const ACTIVITY_WRITE: &[Permission] = &[Permission::ActivityWrite];
+const ANNOUNCEMENT_WRITE: &[Permission] = &[Permission::AnnouncementWrite];
 
 /// 创建API路由
-pub fn create_routes() -> Router<Arc<Pool<Postgres>>> {
+///
+/// 除公共路由外，每个路由组都通过权限中间件声明所需的权限，
+/// 修改资源的路由通过审计中间件自动写入活动记录。
+/// 导入学生、考勤、布置作业、迟交扣分规则、评分等级制、试卷组卷和总评的调整、锁定、解锁
+/// 一次涉及多行或多张表，不挂审计中间件，由处理函数在同一事务中写入带有说明的活动记录
+pub fn create_routes(pool: &Arc<Pool<Postgres>>) -> Router<Arc<Pool<Postgres>>> {
+    // 上传请求体上限：文件大小上限加上 multipart 表单的额外开销
+    let upload_limit = DefaultBodyLimit::max(
+        (config::get_config().storage.max_file_size as usize).saturating_add(64 * 1024),
+    );
+
+    // 审计中间件，放在权限中间件之内
+    let audit =
+        |activity_type| from_fn_with_state(Audit::new(pool, activity_type), audit_middleware);
+
     // 公共路由 - 不需要认证
     let public_routes = Router::new()
         .route("/users/register", post(userapi::register_user))
         .route("/users/login", post(userapi::login_user))
-        .route("/auth/refresh", post(auth::refresh_token_handler))
-        .route("/students", get(studentapi::get_all_students))
-        .route("/homeworks", get(homeworkapi::get_all_homework))
-        .route("/courses", get(courseapi::get_all_course_records))
-        .route("/exams", get(examapi::get_all_exam_records))
-        .route("/course", get(courseapi::get_all_courses))
-        .route("/username/{id}", get(userapi::get_username_by_id))
+        .route("/auth/refresh", post(authapi::refresh_token))
+        .route("/auth/logout", post(authapi::logout))
         .route(
             "/announcements",
             get(announcementapi::get_all_announcements),
         );
 
-    // 学生相关路由 - 需要用户认证
-    let student_routes = Router::new()
-        .route("/students", post(studentapi::create_student))
+    // 会话路由 - 只需要用户认证
+    let session_routes = Router::new()
+        .route("/auth/logout-all", post(authapi::logout_all))
+        .route("/username/{id}", get(userapi::get_username_by_id))
+        .layer(from_fn(auth::auth_middleware));
+
+    // 用户管理路由 - 需要 user:manage
+    let user_manage_routes = Router::new()
+        .route("/users", post(userapi::create_user))
+        .route(
+            "/users/{id}/sessions",
+            delete(authapi::revoke_user_sessions),
+        )
+        // 处理函数在同一事务中写入 import_students 活动记录，列出导入的人数
+        .route(
+            "/students/import",
+            post(importapi::import_students).layer(upload_limit),
+        )
+        .route("/invitations", post(invitationapi::create_invitation))
+        .route("/invitations", get(invitationapi::get_invitations))
+        .route(
+            "/invitations/{id}",
+            delete(invitationapi::revoke_invitation),
+        )
+        .layer(from_fn_with_state(USER_MANAGE, permission_middleware));
+
+    // 学生查询路由 - 需要 student:read
+    let student_read_routes = Router::new()
+        .route("/students", get(studentapi::get_all_students))
         .route("/students/{id}", get(studentapi::get_student))
-        .route("/students/{id}", put(studentapi::update_student))
+        .route(
+            "/students/{id}/timeline",
+            get(studentapi::get_student_timeline),
+        )
         .route(
             "/students/grade/{grade}",
             get(studentapi::get_students_by_grade),
         )
-        .layer(from_fn(auth::auth_middleware));
+        .route(
+            "/students/{id}/courses",
+            get(courseapi::get_student_courses),
+        )
+        .route("/courses/{id}/roster", get(courseapi::get_course_roster))
+        .layer(from_fn_with_state(STUDENT_READ, permission_middleware));
+
+    // 学生管理路由 - 需要 student:write
+    let student_write_routes = Router::new()
+        .route(
+            "/students",
+            post(studentapi::create_student).layer(audit(ActivityType::AddStudent)),
+        )
+        .route(
+            "/students/{id}",
+            put(studentapi::update_student).layer(audit(ActivityType::UpdateStudent)),
+        )
+        .layer(from_fn_with_state(STUDENT_WRITE, permission_middleware));
+
+    // 学生删除路由 - 需要 student:delete
+    let student_delete_routes = Router::new()
+        .route(
+            "/delstudent/{id}",
+            delete(studentapi::delete_student).layer(audit(ActivityType::DeleteStudent)),
+        )
+        .layer(from_fn_with_state(STUDENT_DELETE, permission_middleware));
 
-    // 课程相关路由 - 需要用户认证
-    let course_routes = Router::new()
-        .route("/courses", post(courseapi::create_course))
+    // 课程查询路由 - 需要 course:read
+    let course_read_routes = Router::new()
+        .route("/course", get(courseapi::get_all_courses))
         .route("/courses/{id}", get(courseapi::get_course))
-        .route("/courses/{id}", put(courseapi::update_course))
-        .route("/courses/{id}", delete(courseapi::delete_course))
         .route(
             "/courses/search/{keyword}",
             get(courseapi::search_courses_by_keyword),
         )
-        .route("/course-records", post(courseapi::create_course_record))
-        .route("/course-records/{id}", get(courseapi::get_course_record))
-        .route("/course-records/{id}", put(courseapi::update_course_record))
+        .route("/class-groups", get(courseapi::get_class_groups))
+        .route("/class-groups/{id}", get(courseapi::get_class_group))
+        .route("/courses/{id}/late-policy", get(courseapi::get_late_policy))
         .route(
-            "/course-records/{id}",
-            delete(courseapi::delete_course_record),
+            "/courses/{id}/grading-scale",
+            get(courseapi::get_grading_scale),
+        )
+        .route(
+            "/courses/{id}/grading-scale/convert",
+            get(courseapi::convert_score),
+        )
+        .layer(from_fn_with_state(COURSE_READ, permission_middleware));
+
+    // 课程管理路由 - 需要 course:write
+    let course_write_routes = Router::new()
+        .route(
+            "/courses",
+            post(courseapi::create_course).layer(audit(ActivityType::AddCourse)),
+        )
+        .route(
+            "/courses/{id}",
+            put(courseapi::update_course).layer(audit(ActivityType::UpdateCourse)),
+        )
+        .route(
+            "/courses/{id}",
+            delete(courseapi::delete_course).layer(audit(ActivityType::DeleteCourse)),
+        )
+        .route(
+            "/class-groups",
+            post(courseapi::create_class_group).layer(audit(ActivityType::AddClassGroup)),
+        )
+        .route(
+            "/class-groups/{id}",
+            put(courseapi::update_class_group).layer(audit(ActivityType::UpdateClassGroup)),
+        )
+        .route(
+            "/class-groups/{id}",
+            delete(courseapi::delete_class_group).layer(audit(ActivityType::DeleteClassGroup)),
+        )
+        .route(
+            "/enrollments",
+            post(courseapi::enroll_student).layer(audit(ActivityType::EnrollStudent)),
+        )
+        .route(
+            "/enrollments/{id}/drop",
+            put(courseapi::drop_enrollment).layer(audit(ActivityType::DropEnrollment)),
+        )
+        // 迟交扣分规则和评分等级制按课程保存，处理函数在同一事务中写入列出规则内容的活动记录
+        .route("/courses/{id}/late-policy", put(courseapi::set_late_policy))
+        .route(
+            "/courses/{id}/late-policy",
+            delete(courseapi::delete_late_policy),
+        )
+        .route(
+            "/courses/{id}/grading-scale",
+            put(courseapi::set_grading_scale),
         )
+        .route(
+            "/courses/{id}/grading-scale",
+            delete(courseapi::delete_grading_scale),
+        )
+        .layer(from_fn_with_state(COURSE_WRITE, permission_middleware));
+
+    // 课程记录查询路由 - 需要 course_record:read
+    let course_record_read_routes = Router::new()
+        .route("/courses", get(courseapi::get_all_course_records))
+        .route("/course-records/{id}", get(courseapi::get_course_record))
         .route(
             "/course-records/query",
             get(courseapi::query_course_records),
         )
-        .layer(from_fn(auth::auth_middleware));
+        .layer(from_fn_with_state(
+            COURSE_RECORD_READ,
+            permission_middleware,
+        ));
 
-    // 试卷相关路由 - 需要用户认证
-    let exam_routes = Router::new()
-        .route("/exams", post(examapi::create_exam))
+    // 课程记录管理路由 - 需要 course_record:write
+    let course_record_write_routes = Router::new()
+        .route(
+            "/course-records",
+            post(courseapi::create_course_record).layer(audit(ActivityType::AddCourseRecord)),
+        )
+        .route(
+            "/course-records/{id}",
+            put(courseapi::update_course_record).layer(audit(ActivityType::UpdateCourseRecord)),
+        )
+        .route(
+            "/course-records/{id}",
+            delete(courseapi::delete_course_record).layer(audit(ActivityType::DeleteCourseRecord)),
+        )
+        .layer(from_fn_with_state(
+            COURSE_RECORD_WRITE,
+            permission_middleware,
+        ));
+
+    // 试卷查询路由 - 需要 exam:read
+    let exam_read_routes = Router::new()
         .route("/exams/{id}", get(examapi::get_exam))
-        .route("/exams/{id}", put(examapi::update_exam))
-        .route("/exams/{id}", delete(examapi::delete_exam))
         .route(
             "/exams/search/{keyword}",
             get(examapi::search_exams_by_keyword),
         )
-        .route("/exam-records", post(examapi::create_exam_record))
+        .route("/exams/{id}/file", get(examapi::download_exam_file))
+        .route(
+            "/exams/{id}/questions",
+            get(questionapi::get_exam_questions),
+        )
+        .layer(from_fn_with_state(EXAM_READ, permission_middleware));
+
+    // 试卷管理路由 - 需要 exam:write
+    let exam_write_routes = Router::new()
+        .route(
+            "/exams",
+            post(examapi::create_exam).layer(audit(ActivityType::AddExam)),
+        )
+        .route(
+            "/exams/{id}",
+            put(examapi::update_exam).layer(audit(ActivityType::UpdateExam)),
+        )
+        .route(
+            "/exams/{id}",
+            delete(examapi::delete_exam).layer(audit(ActivityType::DeleteExam)),
+        )
+        .route(
+            "/exams/{id}/file",
+            post(examapi::upload_exam_file)
+                .layer(upload_limit)
+                .layer(audit(ActivityType::UploadExamFile)),
+        )
+        .route(
+            "/exams/{id}/questions",
+            // 替换试卷的全部题目，处理函数在同一事务中写入列出各题分值的活动记录
+            put(questionapi::set_exam_questions),
+        )
+        .route(
+            "/questions",
+            post(questionapi::create_question).layer(audit(ActivityType::AddQuestion)),
+        )
+        .route("/questions", get(questionapi::get_questions))
+        .route("/questions/{id}", get(questionapi::get_question))
+        .route(
+            "/questions/{id}",
+            put(questionapi::update_question).layer(audit(ActivityType::UpdateQuestion)),
+        )
+        .route(
+            "/questions/{id}",
+            delete(questionapi::delete_question).layer(audit(ActivityType::DeleteQuestion)),
+        )
+        .layer(from_fn_with_state(EXAM_WRITE, permission_middleware));
+
+    // 试卷记录查询路由 - 需要 exam_record:read
+    let exam_record_read_routes = Router::new()
+        .route("/exams", get(examapi::get_all_exam_records))
         .route("/exam-records/{id}", get(examapi::get_exam_record))
-        .route("/exam-records/{id}", put(examapi::update_exam_record))
-        .route("/exam-records/{id}", delete(examapi::delete_exam_record))
         .route("/exam-records/query", get(examapi::query_exam_records))
-        .layer(from_fn(auth::auth_middleware));
+        .route("/exams/{id}/stats", get(examapi::get_exam_stats))
+        .route(
+            "/exams/{id}/question-stats",
+            get(questionapi::get_question_stats),
+        )
+        .route(
+            "/exam-records/{id}/question-scores",
+            get(questionapi::get_question_scores),
+        )
+        .route(
+            "/knowledge-points/stats",
+            get(questionapi::get_knowledge_point_stats),
+        )
+        .route(
+            "/students/{id}/progress",
+            get(studentapi::get_student_progress),
+        )
+        .layer(from_fn_with_state(EXAM_RECORD_READ, permission_middleware));
 
-    // 作业相关路由 - 需要用户认证
-    let homework_routes = Router::new()
-        .route("/homework", post(homeworkapi::create_homework))
+    // 试卷记录管理路由 - 需要 exam_record:write
+    let exam_record_write_routes = Router::new()
+        .route(
+            "/exam-records",
+            post(examapi::create_exam_record).layer(audit(ActivityType::AddExamRecord)),
+        )
+        .route(
+            "/exam-records/{id}",
+            put(examapi::update_exam_record).layer(audit(ActivityType::UpdateExamRecord)),
+        )
+        .route(
+            "/exam-records/{id}",
+            delete(examapi::delete_exam_record).layer(audit(ActivityType::DeleteExamRecord)),
+        )
+        .route(
+            "/exam-records/{id}/question-scores",
+            put(questionapi::record_question_scores).layer(audit(ActivityType::UpdateExamRecord)),
+        )
+        .layer(from_fn_with_state(EXAM_RECORD_WRITE, permission_middleware));
+
+    // 作业查询路由 - 需要 homework:read
+    let homework_read_routes = Router::new()
+        .route("/homeworks", get(homeworkapi::get_all_homework))
         .route("/homework/{id}", get(homeworkapi::get_homework))
-        .route("/homework/{id}", put(homeworkapi::update_homework))
-        .route("/homework/{id}", delete(homeworkapi::delete_homework))
         .route("/homework/query", get(homeworkapi::query_homework))
-        .route("/homework/{id}/grade", put(homeworkapi::grade_homework))
-        .layer(from_fn(auth::auth_middleware));
+        .route(
+            "/homework/{id}/file",
+            get(homeworkapi::download_homework_file),
+        )
+        .route("/assignments", get(assignmentapi::get_assignments))
+        .route("/assignments/{id}", get(assignmentapi::get_assignment))
+        .route(
+            "/assignments/{id}/submissions",
+            get(assignmentapi::get_submissions),
+        )
+        .route(
+            "/assignments/{id}/missing",
+            get(assignmentapi::get_missing_submissions),
+        )
+        .route(
+            "/students/{id}/overdue",
+            get(assignmentapi::get_student_overdue),
+        )
+        .route(
+            "/teachers/{id}/overdue",
+            get(assignmentapi::get_teacher_overdue),
+        )
+        .route("/submissions/{id}", get(assignmentapi::get_submission))
+        .route(
+            "/submissions/{id}/file",
+            get(assignmentapi::download_submission_file),
+        )
+        .layer(from_fn_with_state(HOMEWORK_READ, permission_middleware));
+
+    // 作业提交路由 - 需要 homework:submit
+    let homework_submit_routes = Router::new()
+        .route(
+            "/homework",
+            post(homeworkapi::create_homework).layer(audit(ActivityType::SubmitHomework)),
+        )
+        .route(
+            "/homework/{id}/file",
+            post(homeworkapi::upload_homework_file)
+                .layer(upload_limit)
+                .layer(audit(ActivityType::UploadHomeworkFile)),
+        )
+        .route(
+            "/submissions",
+            post(assignmentapi::submit_assignment).layer(audit(ActivityType::SubmitHomework)),
+        )
+        .route(
+            "/submissions/{id}/file",
+            post(assignmentapi::upload_submission_file)
+                .layer(upload_limit)
+                .layer(audit(ActivityType::UploadHomeworkFile)),
+        )
+        .layer(from_fn_with_state(HOMEWORK_SUBMIT, permission_middleware));
+
+    // 作业管理路由 - 需要 homework:write
+    let homework_write_routes = Router::new()
+        .route(
+            "/homework/{id}",
+            put(homeworkapi::update_homework).layer(audit(ActivityType::UpdateHomework)),
+        )
+        .route(
+            "/homework/{id}",
+            delete(homeworkapi::delete_homework).layer(audit(ActivityType::DeleteHomework)),
+        )
+        .route(
+            "/assignments",
+            post(assignmentapi::create_assignment).layer(audit(ActivityType::AddAssignment)),
+        )
+        .route(
+            "/assignments/{id}",
+            put(assignmentapi::update_assignment).layer(audit(ActivityType::UpdateAssignment)),
+        )
+        .route(
+            "/assignments/{id}",
+            delete(assignmentapi::delete_assignment).layer(audit(ActivityType::DeleteAssignment)),
+        )
+        .route(
+            "/assignments/{id}/attachments",
+            post(assignmentapi::upload_assignment_file)
+                .layer(upload_limit)
+                .layer(audit(ActivityType::UploadAssignmentFile)),
+        )
+        .route(
+            "/assignments/{id}/attachments/{file_id}",
+            delete(assignmentapi::delete_assignment_file)
+                .layer(audit(ActivityType::UpdateAssignment)),
+        )
+        // 一次新增多个提交，处理函数在同一事务中写入列出布置人数的活动记录
+        .route(
+            "/assignments/{id}/assign",
+            post(assignmentapi::assign_homework),
+        )
+        .layer(from_fn_with_state(HOMEWORK_WRITE, permission_middleware));
 
-    // 活动记录相关路由 - 需要用户认证
-    let activity_routes = Router::new()
+    // 作业评分路由 - 需要 homework:grade
+    let homework_grade_routes = Router::new()
+        .route(
+            "/homework/{id}/grade",
+            put(homeworkapi::grade_homework).layer(audit(ActivityType::GradeHomework)),
+        )
+        .route(
+            "/submissions/{id}/grade",
+            put(assignmentapi::grade_submission).layer(audit(ActivityType::GradeHomework)),
+        )
+        .route(
+            "/submissions/{id}/return",
+            put(assignmentapi::return_submission).layer(audit(ActivityType::ReturnHomework)),
+        )
+        .layer(from_fn_with_state(HOMEWORK_GRADE, permission_middleware));
+
+    // 成绩册导出路由 - 需要 exam_record:read 和 homework:read
+    let gradebook_routes = Router::new()
+        .route("/gradebook/export", get(gradebookapi::export_gradebook))
+        .layer(from_fn_with_state(GRADEBOOK_READ, permission_middleware));
+
+    // 报告单路由 - 需要 student:read、course_record:read、homework:read 和 exam_record:read
+    let report_card_routes = Router::new()
+        .route(
+            "/students/{id}/report-card",
+            get(reportapi::get_report_card),
+        )
+        .route("/report-cards", get(reportapi::export_report_cards))
+        .layer(from_fn_with_state(REPORT_CARD_READ, permission_middleware));
+
+    // 总评查询路由 - 需要 final_grade:read
+    let final_grade_read_routes = Router::new()
+        .route(
+            "/courses/{id}/grade-categories",
+            get(finalgradeapi::get_grade_categories),
+        )
+        .route(
+            "/courses/{id}/final-grades",
+            get(finalgradeapi::get_final_grades),
+        )
+        .route(
+            "/students/{id}/final-grades",
+            get(finalgradeapi::get_student_final_grades),
+        )
+        .route(
+            "/students/{id}/grade-excusals",
+            get(finalgradeapi::get_student_grade_excusals),
+        )
+        .layer(from_fn_with_state(FINAL_GRADE_READ, permission_middleware));
+
+    // 总评管理路由 - 需要 final_grade:write
+    let final_grade_write_routes = Router::new()
+        .route(
+            "/grade-categories",
+            post(finalgradeapi::create_grade_category).layer(audit(ActivityType::AddGradeCategory)),
+        )
+        .route(
+            "/grade-categories/{id}",
+            put(finalgradeapi::update_grade_category)
+                .layer(audit(ActivityType::UpdateGradeCategory)),
+        )
+        .route(
+            "/grade-categories/{id}",
+            delete(finalgradeapi::delete_grade_category)
+                .layer(audit(ActivityType::DeleteGradeCategory)),
+        )
+        .route(
+            "/grade-excusals",
+            post(finalgradeapi::create_grade_excusal).layer(audit(ActivityType::AddGradeExcusal)),
+        )
+        .route(
+            "/grade-excusals/{id}",
+            delete(finalgradeapi::delete_grade_excusal)
+                .layer(audit(ActivityType::DeleteGradeExcusal)),
+        )
+        .route(
+            "/courses/{id}/final-grades/preview",
+            get(finalgradeapi::preview_final_grades),
+        )
+        // 调整总评需记录原因，锁定涉及课程的全部学生，处理函数在同一事务中写入说明总评变化的活动记录
+        .route(
+            "/courses/{id}/final-grades/{student_id}/override",
+            put(finalgradeapi::override_final_grade),
+        )
+        .route(
+            "/courses/{id}/final-grades/lock",
+            post(finalgradeapi::lock_final_grades),
+        )
+        .layer(from_fn_with_state(FINAL_GRADE_WRITE, permission_middleware));
+
+    // 总评解锁路由 - 需要 final_grade:unlock
+    let final_grade_unlock_routes = Router::new()
+        // 处理函数在同一事务中写入 unlock_final_grades 活动记录
+        .route(
+            "/courses/{id}/final-grades/unlock",
+            post(finalgradeapi::unlock_final_grades),
+        )
+        .layer(from_fn_with_state(
+            FINAL_GRADE_UNLOCK,
+            permission_middleware,
+        ));
+
+    // 文件下载路由 - 需要 file:read
+    let file_routes = Router::new()
+        .route("/files/{id}", get(fileapi::download_file))
+        .layer(from_fn_with_state(FILE_READ, permission_middleware));
+
+    // 考勤查询路由 - 需要 attendance:read
+    let attendance_read_routes = Router::new()
+        .route(
+            "/attendance/session",
+            get(attendanceapi::get_session_attendance),
+        )
+        .route("/attendance/{id}", get(attendanceapi::get_attendance))
+        .route(
+            "/attendance/student/{id}",
+            get(attendanceapi::get_student_attendance),
+        )
+        .route(
+            "/attendance/student/{id}/rate",
+            get(attendanceapi::get_student_attendance_rate),
+        )
+        .route(
+            "/attendance/course/{id}/rate",
+            get(attendanceapi::get_course_attendance_rate),
+        )
+        .layer(from_fn_with_state(ATTENDANCE_READ, permission_middleware));
+
+    // 考勤记录路由 - 需要 attendance:write
+    // 点名一次写入整节课的考勤，各处理函数在同一事务中写入说明学生、日期和状态的活动记录
+    let attendance_write_routes = Router::new()
+        .route("/attendance", post(attendanceapi::record_attendance))
+        .route("/attendance/roll-call", post(attendanceapi::roll_call))
+        .route("/attendance/{id}", put(attendanceapi::update_attendance))
+        .route("/attendance/{id}", delete(attendanceapi::delete_attendance))
+        .layer(from_fn_with_state(ATTENDANCE_WRITE, permission_middleware));
+
+    // 活动记录查询路由 - 需要 activity:read
+    let activity_read_routes = Router::new()
         .route("/activities", get(activityapi::get_activities))
-        .route("/activities", post(activityapi::create_activity));
+        .layer(from_fn_with_state(ACTIVITY_READ, permission_middleware));
 
-    // 管理员路由 - 需要管理员权限
-    let admin_routes = Router::new()
-        .route("/announcement", post(announcementapi::create_announcement))
-        .route("/delstudent/{id}", delete(studentapi::delete_student))
-        .layer(from_fn(auth::admin_middleware));
+    // 活动记录创建路由 - 需要 activity:write
+    let activity_write_routes = Router::new()
+        .route("/activities", post(activityapi::create_activity))
+        .layer(from_fn_with_state(ACTIVITY_WRITE, permission_middleware));
+
+    // 公告发布路由 - 需要 announcement:write
+    let announcement_routes = Router::new()
+        .route(
+            "/announcement",
+            post(announcementapi::create_announcement).layer(audit(ActivityType::AddAnnouncement)),
+        )
+        .layer(from_fn_with_state(
+            ANNOUNCEMENT_WRITE,
+            permission_middleware,
+        ));
 
     // 合并所有路由
     Router::new()
-        .merge(student_routes)
-        .merge(course_routes)
-        .merge(exam_routes)
-        .merge(homework_routes)
-        .merge(activity_routes)
-        .merge(admin_routes)
+        .merge(session_routes)
+        .merge(user_manage_routes)
+        .merge(student_read_routes)
+        .merge(student_write_routes)
+        .merge(student_delete_routes)
+        .merge(course_read_routes)
+        .merge(course_write_routes)
+        .merge(course_record_read_routes)
+        .merge(course_record_write_routes)
+        .merge(exam_read_routes)
+        .merge(exam_write_routes)
+        .merge(exam_record_read_routes)
+        .merge(exam_record_write_routes)
+        .merge(homework_read_routes)
+        .merge(homework_submit_routes)
+        .merge(homework_write_routes)
+        .merge(homework_grade_routes)
+        .merge(gradebook_routes)
+        .merge(report_card_routes)
+        .merge(final_grade_read_routes)
+        .merge(final_grade_write_routes)
+        .merge(final_grade_unlock_routes)
+        .merge(file_routes)
+        .merge(attendance_read_routes)
+        .merge(attendance_write_routes)
+        .merge(activity_read_routes)
+        .merge(activity_write_routes)
+        .merge(announcement_routes)
         .merge(public_routes)
 }
@@ -0,0 +1,413 @@
+//! 题库API模块
+//!
+//! 提供题库、试卷组卷、小题得分，以及题目和知识点得分率统计相关的API端点
+
+use axum::{
+    Extension, Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+};
+use rust_decimal::Decimal;
+use sqlx::{Pool, Postgres};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+use super::access::StudentScope;
+use super::activityapi::actor_activity;
+use crate::error::{AppError, AppErrorType, FieldError};
+use crate::middleware::auth::Claims;
+use crate::middleware::validation::ValidatedJson;
+use crate::model::models::activity::{Activity, ActivityType};
+use crate::model::models::exam::Exam;
+use crate::model::models::exam_question::{
+    ExamQuestion, KnowledgePointFilter, KnowledgePointStat, QuestionScore, QuestionStat,
+    QuestionStatsFilter, RecordQuestionScoresRequest, SetExamQuestionsRequest,
+};
+use crate::model::models::exam_record::ExamRecord;
+use crate::model::models::permission::Permission;
+use crate::model::models::question::{
+    CreateQuestionRequest, Question, QuestionFilter, QuestionType, UpdateQuestionRequest,
+};
+use crate::model::query::{Page, PageParams};
+
+/// 知识点名称的最大长度
+const KNOWLEDGE_POINT_MAX_LEN: usize = 50;
+
+/// 查找题目，不存在时返回 404
+async fn find_question(pool: &Pool<Postgres>, id: Uuid) -> Result<Question, AppError> {
+    Question::find_by_id(pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("题目不存在", AppErrorType::Notfound))
+}
+
+/// 查找试卷，不存在时返回 404
+async fn find_exam(pool: &Pool<Postgres>, id: Uuid) -> Result<Exam, AppError> {
+    Exam::find_by_id(pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("试卷不存在", AppErrorType::Notfound))
+}
+
+/// 查找试卷记录，不存在时返回 404
+async fn find_exam_record(pool: &Pool<Postgres>, id: Uuid) -> Result<ExamRecord, AppError> {
+    ExamRecord::find_by_id(pool, id)
+        .await?
+        .ok_or_else(|| AppError::new_message("试卷记录不存在", AppErrorType::Notfound))
+}
+
+/// 整理选项：选择题至少两个选项，其他题型不能设置选项
+fn check_options(
+    question_type: QuestionType,
+    options: Option<Vec<String>>,
+) -> Result<Option<Vec<String>>, AppError> {
+    let options: Option<Vec<String>> = options.map(|options| {
+        options
+            .into_iter()
+            .map(|option| option.trim().to_string())
+            .filter(|option| !option.is_empty())
+            .collect()
+    });
+
+    match options {
+        Some(options) if question_type.has_options() && options.len() >= 2 => Ok(Some(options)),
+        _ if question_type.has_options() => Err(AppError::validation(vec![FieldError::new(
+            "options",
+            "选择题至少需要两个选项",
+        )])),
+        Some(options) if !options.is_empty() => Err(AppError::validation(vec![FieldError::new(
+            "options",
+            "只有选择题可以设置选项",
+        )])),
+        _ => Ok(None),
+    }
+}
+
+/// 整理知识点：去掉首尾空白和空的知识点，去掉重复的知识点
+fn normalize_knowledge_points(points: Vec<String>) -> Result<Vec<String>, AppError> {
+    let mut seen = HashSet::new();
+    let mut normalized = Vec::new();
+    for point in points {
+        let point = point.trim().to_string();
+        if point.is_empty() || !seen.insert(point.clone()) {
+            continue;
+        }
+        if point.chars().count() > KNOWLEDGE_POINT_MAX_LEN {
+            return Err(AppError::validation(vec![FieldError::new(
+                "knowledge_points",
+                "知识点不能超过50个字符",
+            )]));
+        }
+        normalized.push(point);
+    }
+
+    Ok(normalized)
+}
+
+// ===== 题库API =====
+
+/// 创建题目
+pub async fn create_question(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    ValidatedJson(mut req): ValidatedJson<CreateQuestionRequest>,
+) -> Result<Json<Question>, AppError> {
+    req.options = check_options(req.question_type, req.options.take())?;
+    req.knowledge_points = normalize_knowledge_points(std::mem::take(&mut req.knowledge_points))?;
+
+    let question = Question::create(&pool, req, claims.user_id().ok()).await?;
+    Ok(Json(question))
+}
+
+/// 查询题目，可按题型、知识点和关键词筛选
+pub async fn get_questions(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Query(filter): Query<QuestionFilter>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<Question>>, AppError> {
+    let questions = Question::query(&pool, &filter, &page).await?;
+    Ok(Json(questions))
+}
+
+/// 获取题目
+pub async fn get_question(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Question>, AppError> {
+    let question = find_question(&pool, id).await?;
+    Ok(Json(question))
+}
+
+/// 更新题目
+///
+/// 改为非选择题时清除原有选项；修改默认分值不影响已组卷的试卷
+pub async fn update_question(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(mut req): ValidatedJson<UpdateQuestionRequest>,
+) -> Result<Json<Question>, AppError> {
+    let mut question = find_question(&pool, id).await?;
+
+    let question_type = match req.question_type {
+        Some(question_type) => question_type,
+        None => question
+            .question_type
+            .parse()
+            .map_err(|e: String| AppError::new_message(&e, AppErrorType::Validation))?,
+    };
+    let existing = question.options.take();
+    let options = match req.options.take() {
+        Some(options) => Some(options),
+        None if question_type.has_options() => existing,
+        None => None,
+    };
+    req.options = check_options(question_type, options)?;
+    if let Some(points) = req.knowledge_points.take() {
+        req.knowledge_points = Some(normalize_knowledge_points(points)?);
+    }
+
+    let question = Question::update(&pool, question, req).await?;
+    Ok(Json(question))
+}
+
+/// 删除题目，已被试卷使用的题目不能删除
+pub async fn delete_question(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+) -> Result<StatusCode, AppError> {
+    let deleted = Question::delete(&pool, id).await.map_err(|e| match e {
+        sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+            AppError::new_message("题目已被试卷使用，不能删除", AppErrorType::Conflict)
+        }
+        e => e.into(),
+    })?;
+    if !deleted {
+        return Err(AppError::new_message("题目不存在", AppErrorType::Notfound));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ===== 试卷组卷API =====
+
+/// 获取试卷的题目
+///
+/// 没有 `exam:write` 权限的用户看不到参考答案
+pub async fn get_exam_questions(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<ExamQuestion>>, AppError> {
+    find_exam(&pool, id).await?;
+
+    let mut questions = ExamQuestion::find_by_exam(&*pool, id).await?;
+    if !claims.has_permission(Permission::ExamWrite) {
+        for question in &mut questions {
+            question.answer_key = None;
+        }
+    }
+    Ok(Json(questions))
+}
+
+/// 设置试卷的题目，按请求中的顺序编排题号，替换原有的题目
+///
+/// 试卷的满分更新为各题分值之和；已登记小题得分的题目不能从试卷中移除，
+/// 分值也不能低于已登记的最高得分。在同一事务中写入列出各题分值的活动记录
+pub async fn set_exam_questions(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<SetExamQuestionsRequest>,
+) -> Result<Json<Vec<ExamQuestion>>, AppError> {
+    let exam = find_exam(&pool, id).await?;
+
+    let question_ids: Vec<Uuid> = req.questions.iter().map(|item| item.question_id).collect();
+    if question_ids.iter().collect::<HashSet<_>>().len() != question_ids.len() {
+        return Err(AppError::validation(vec![FieldError::new(
+            "questions",
+            "题目不能重复",
+        )]));
+    }
+
+    let bank: HashMap<Uuid, Question> = Question::find_by_ids(&*pool, &question_ids)
+        .await?
+        .into_iter()
+        .map(|question| (question.id, question))
+        .collect();
+    let mut points = Vec::with_capacity(req.questions.len());
+    for (i, item) in req.questions.iter().enumerate() {
+        let question = bank.get(&item.question_id).ok_or_else(|| {
+            AppError::validation(vec![FieldError::new(
+                &format!("questions[{}].question_id", i),
+                "题目不存在",
+            )])
+        })?;
+        points.push(item.points.unwrap_or(question.points));
+    }
+
+    let mut tx = pool.begin().await?;
+    let max_scores = ExamQuestion::max_scores(&mut *tx, id).await?;
+    if max_scores.keys().any(|id| !question_ids.contains(id)) {
+        return Err(AppError::new_message(
+            "已登记小题得分的题目不能从试卷中移除",
+            AppErrorType::Conflict,
+        ));
+    }
+    let errors: Vec<FieldError> = question_ids
+        .iter()
+        .zip(&points)
+        .enumerate()
+        .filter_map(|(i, (question_id, points))| {
+            let max_score = max_scores.get(question_id)?;
+            (points < max_score).then(|| {
+                FieldError::new(
+                    &format!("questions[{}].points", i),
+                    &format!("分值不能低于已登记的最高得分 {}", max_score),
+                )
+            })
+        })
+        .collect();
+    if !errors.is_empty() {
+        return Err(AppError::validation(errors));
+    }
+    let questions = ExamQuestion::replace(&mut tx, id, &question_ids, &points).await?;
+
+    let items: Vec<String> = questions
+        .iter()
+        .map(|question| {
+            format!(
+                "{}. {}（{}分）",
+                question.position, question.question_id, question.points
+            )
+        })
+        .collect();
+    let description = format!(
+        "设置试卷题目：{}，共{}题，满分{}：{}",
+        exam.title,
+        questions.len(),
+        questions
+            .iter()
+            .map(|question| question.points)
+            .sum::<Decimal>(),
+        items.join("；")
+    );
+    Activity::create(
+        &mut *tx,
+        actor_activity(
+            &claims,
+            ActivityType::SetExamQuestions,
+            description,
+            Some(id),
+        )?,
+    )
+    .await?;
+    tx.commit().await?;
+
+    Ok(Json(questions))
+}
+
+// ===== 小题得分API =====
+
+/// 获取试卷记录的小题得分，按题号列出试卷的所有题目
+pub async fn get_question_scores(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<Vec<QuestionScore>>, AppError> {
+    let record = find_exam_record(&pool, id).await?;
+    StudentScope::of(&pool, &claims)
+        .await?
+        .check(record.student_id)?;
+
+    let scores = QuestionScore::find_by_record(&*pool, id, record.exam_id).await?;
+    Ok(Json(scores))
+}
+
+/// 登记试卷记录的小题得分
+///
+/// 只覆盖请求中的题目，试卷记录的分数更新为已登记的小题得分之和
+pub async fn record_question_scores(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Path(id): Path<Uuid>,
+    ValidatedJson(req): ValidatedJson<RecordQuestionScoresRequest>,
+) -> Result<Json<Vec<QuestionScore>>, AppError> {
+    let record = find_exam_record(&pool, id).await?;
+    if req.scores.is_empty() {
+        return Err(AppError::validation(vec![FieldError::new(
+            "scores",
+            "至少需要登记一道题的得分",
+        )]));
+    }
+
+    let points: HashMap<Uuid, _> = ExamQuestion::find_by_exam(&*pool, record.exam_id)
+        .await?
+        .into_iter()
+        .map(|question| (question.question_id, question.points))
+        .collect();
+    let mut seen = HashSet::new();
+    let mut errors = Vec::new();
+    for (i, item) in req.scores.iter().enumerate() {
+        match points.get(&item.question_id) {
+            None => errors.push(FieldError::new(
+                &format!("scores[{}].question_id", i),
+                "题目不在该试卷中",
+            )),
+            Some(_) if !seen.insert(item.question_id) => errors.push(FieldError::new(
+                &format!("scores[{}].question_id", i),
+                "题目不能重复",
+            )),
+            Some(&points) if item.score > points => errors.push(FieldError::new(
+                &format!("scores[{}].score", i),
+                &format!("得分不能超过该题分值{}", points.normalize()),
+            )),
+            Some(_) => {}
+        }
+    }
+    if !errors.is_empty() {
+        return Err(AppError::validation(errors));
+    }
+
+    let mut tx = pool.begin().await?;
+    QuestionScore::save(&mut tx, id, &req.scores).await?;
+    let scores = QuestionScore::find_by_record(&mut *tx, id, record.exam_id).await?;
+    tx.commit().await?;
+
+    Ok(Json(scores))
+}
+
+// ===== 得分率统计API =====
+
+/// 统计试卷各题目的得分率
+///
+/// 只统计当前用户可访问的学生，可按年级、班级和学生筛选
+pub async fn get_question_stats(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Query(filter): Query<QuestionStatsFilter>,
+) -> Result<Json<Vec<QuestionStat>>, AppError> {
+    find_exam(&pool, id).await?;
+
+    let scope = StudentScope::of(&pool, &claims).await?;
+    if let Some(student_id) = filter.student_id {
+        scope.check(student_id)?;
+    }
+
+    let stats = ExamQuestion::stats(&pool, id, &filter, scope.student_ids().as_deref()).await?;
+    Ok(Json(stats))
+}
+
+/// 按知识点统计得分率
+///
+/// 只统计当前用户可访问的学生，可按年级、班级、学生、试卷和完成日期筛选
+pub async fn get_knowledge_point_stats(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Query(filter): Query<KnowledgePointFilter>,
+) -> Result<Json<Vec<KnowledgePointStat>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    if let Some(student_id) = filter.student_id {
+        scope.check(student_id)?;
+    }
+
+    let stats = KnowledgePointStat::query(&pool, &filter, scope.student_ids().as_deref()).await?;
+    Ok(Json(stats))
+}
@@ -0,0 +1,119 @@
+//! 报告单API模块
+//!
+//! 提供生成学生 PDF 报告单的API端点
+
+use axum::{
+    Extension,
+    extract::{Path, Query, State},
+    http::header,
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use sqlx::{Pool, Postgres};
+use std::io::{Cursor, Write};
+use std::sync::Arc;
+use uuid::Uuid;
+use zip::write::SimpleFileOptions;
+
+use super::access::StudentScope;
+use super::fileapi::percent_encode;
+use crate::config;
+use crate::error::{AppError, AppErrorType};
+use crate::middleware::auth::Claims;
+use crate::model::models::report_card::{ReportAssets, ReportCard, ReportCardParams};
+
+/// 批量生成报告单的年级
+#[derive(Debug, Deserialize)]
+pub struct ReportCardGrade {
+    /// 学生年级
+    pub grade: i32,
+}
+
+/// 生成学生的 PDF 报告单
+pub async fn get_report_card(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ReportCardParams>,
+) -> Result<Response, AppError> {
+    StudentScope::of(&pool, &claims).await?.check(id)?;
+
+    let card = ReportCard::for_student(&pool, id, &params)
+        .await?
+        .ok_or_else(|| AppError::new_message("学生不存在", AppErrorType::Notfound))?;
+    let assets = ReportAssets::load(&config::get_config().report).await?;
+
+    let file_name = format!("{}.pdf", card.file_name());
+    let data =
+        tokio::task::spawn_blocking(move || card.to_pdf(&assets, &config::get_config().report))
+            .await
+            .map_err(|e| AppError::new(e, AppErrorType::Internal))??;
+
+    Ok(attachment("application/pdf", &file_name, data))
+}
+
+/// 批量生成一个年级学生的报告单，打包为 ZIP 文件
+///
+/// 只包含当前用户可访问的学生，每名学生一个 PDF 文件
+pub async fn export_report_cards(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Query(ReportCardGrade { grade }): Query<ReportCardGrade>,
+    Query(params): Query<ReportCardParams>,
+) -> Result<Response, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let cards =
+        ReportCard::for_grade(&pool, grade, scope.student_ids().as_deref(), &params).await?;
+    if cards.is_empty() {
+        return Err(AppError::new_message(
+            "该年级没有可生成报告单的学生",
+            AppErrorType::Notfound,
+        ));
+    }
+    let assets = ReportAssets::load(&config::get_config().report).await?;
+
+    let data = tokio::task::spawn_blocking(move || zip_report_cards(&cards, &assets))
+        .await
+        .map_err(|e| AppError::new(e, AppErrorType::Internal))??;
+
+    Ok(attachment(
+        "application/zip",
+        &format!("report-cards-grade-{}.zip", grade),
+        data,
+    ))
+}
+
+/// 逐份生成报告单并写入 ZIP 文件
+fn zip_report_cards(cards: &[ReportCard], assets: &ReportAssets) -> Result<Vec<u8>, AppError> {
+    let failed = |e: &dyn std::fmt::Display| {
+        AppError::new_message(&format!("生成 ZIP 文件失败: {}", e), AppErrorType::Internal)
+    };
+
+    let report_config = &config::get_config().report;
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    for card in cards {
+        let pdf = card.to_pdf(assets, report_config)?;
+        zip.start_file(
+            format!("{}.pdf", card.file_name()),
+            SimpleFileOptions::default(),
+        )
+        .map_err(|e| failed(&e))?;
+        zip.write_all(&pdf).map_err(|e| failed(&e))?;
+    }
+
+    Ok(zip.finish().map_err(|e| failed(&e))?.into_inner())
+}
+
+/// 以附件形式返回文件
+fn attachment(content_type: &str, file_name: &str, data: Vec<u8>) -> Response {
+    let disposition = format!("attachment; filename*=UTF-8''{}", percent_encode(file_name));
+
+    (
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        data,
+    )
+        .into_response()
+}
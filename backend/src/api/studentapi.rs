@@ -3,158 +3,158 @@
 //! 提供学生相关的API端点
 
 use axum::{
-    Json,
-    extract::{Path, State},
+    Extension, Json,
+    extract::{Path, Query, State},
     http::StatusCode,
 };
 use sqlx::{Pool, Postgres};
 use std::sync::Arc;
 use uuid::Uuid;
+use validator::Validate;
 
-use crate::model::models::user::{CreateUserRequest, UpdateUserRequest, User, UserWithDetails};
+use super::access::StudentScope;
+use crate::error::{AppError, AppErrorType, FieldError};
+use crate::middleware::auth::Claims;
+use crate::middleware::validation::ValidatedJson;
+use crate::model::models::permission::Permission;
+use crate::model::models::progress::{ProgressParams, StudentProgress};
+use crate::model::models::timeline::{TimelineEvent, TimelineFilter};
+use crate::model::models::user::{
+    CreateUserRequest, UpdateUserRequest, User, UserRole, UserWithDetails,
+};
+use crate::model::query::{Page, PageParams};
 
 /// 创建学生
 pub async fn create_student(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Json(mut req): Json<CreateUserRequest>,
-) -> Result<Json<User>, (StatusCode, String)> {
+    ValidatedJson(mut req): ValidatedJson<CreateUserRequest>,
+) -> Result<Json<User>, AppError> {
     // 确保角色为学生
     req.role = Some("student".to_string());
 
-    match User::create(&pool, req).await {
-        Ok(user) => Ok(Json(user)),
-        Err(e) => {
-            eprintln!("创建学生失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "创建学生失败".to_string(),
-            ))
-        }
-    }
+    let user = User::create(&*pool, req).await?;
+    Ok(Json(user))
+}
+
+/// 查找学生，用户不存在或不是学生时返回未找到
+async fn find_student(pool: &Pool<Postgres>, id: Uuid) -> Result<User, AppError> {
+    User::find_by_id(pool, id)
+        .await?
+        .filter(|user| user.role.to_lowercase() == "student")
+        .ok_or_else(|| AppError::new_message("学生不存在", AppErrorType::Notfound))
 }
 
 /// 获取学生信息
+///
+/// 学生只能查看自己，教师只能查看其授课的学生
 pub async fn get_student(
     State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
-) -> Result<Json<UserWithDetails>, (StatusCode, String)> {
-    match User::find_with_details(&pool, id).await {
-        Ok(Some(user_details)) => {
-            // 验证用户是否为学生
-            if user_details.user.role.to_lowercase() != "student" {
-                return Err((StatusCode::NOT_FOUND, "学生不存在".to_string()));
-            }
+) -> Result<Json<UserWithDetails>, AppError> {
+    StudentScope::of(&pool, &claims).await?.check(id)?;
+
+    match User::find_with_details(&pool, id).await? {
+        // 验证用户是否为学生
+        Some(user_details) if user_details.user.role.to_lowercase() == "student" => {
             Ok(Json(user_details))
         }
-        Ok(None) => Err((StatusCode::NOT_FOUND, "学生不存在".to_string())),
-        Err(e) => {
-            eprintln!("获取学生失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取学生失败".to_string(),
-            ))
-        }
+        _ => Err(AppError::new_message("学生不存在", AppErrorType::Notfound)),
     }
 }
 
+/// 获取学生的时间线
+///
+/// 上课、作业提交和评分、考试以及考勤按日期合并为一个分页的事件流
+pub async fn get_student_timeline(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Query(filter): Query<TimelineFilter>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<TimelineEvent>>, AppError> {
+    StudentScope::of(&pool, &claims).await?.check(id)?;
+    find_student(&pool, id).await?;
+
+    let events = TimelineEvent::find_by_student_id(&pool, id, &filter, &page).await?;
+    Ok(Json(events))
+}
+
+/// 获取学生的成绩趋势
+///
+/// 各次考试的分数相对同场考试平均分计算，并标记明显低于自身基线的考试
+pub async fn get_student_progress(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
+    Path(id): Path<Uuid>,
+    Query(params): Query<ProgressParams>,
+) -> Result<Json<StudentProgress>, AppError> {
+    params.validate()?;
+    StudentScope::of(&pool, &claims).await?.check(id)?;
+    find_student(&pool, id).await?;
+
+    let progress = StudentProgress::for_student(&pool, id, &params).await?;
+    Ok(Json(progress))
+}
+
 /// 更新学生信息
+///
+/// 修改角色需要 user:manage 权限
 pub async fn update_student(
     State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
     Path(id): Path<Uuid>,
-    Json(req): Json<UpdateUserRequest>,
-) -> Result<Json<User>, (StatusCode, String)> {
-    // 首先验证用户是否存在且为学生
-    match User::find_by_id(&pool, id).await {
-        Ok(Some(user)) => {
-            if user.role.to_lowercase() != "student" {
-                return Err((StatusCode::NOT_FOUND, "学生不存在".to_string()));
-            }
-
-            match User::update(&pool, id, req).await {
-                Ok(updated_user) => Ok(Json(updated_user)),
-                Err(e) => {
-                    eprintln!("更新学生失败: {}", e);
-                    Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "更新学生失败".to_string(),
-                    ))
-                }
-            }
-        }
-        Ok(None) => Err((StatusCode::NOT_FOUND, "学生不存在".to_string())),
-        Err(e) => {
-            eprintln!("查找学生失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "查找学生失败".to_string(),
-            ))
-        }
+    ValidatedJson(mut req): ValidatedJson<UpdateUserRequest>,
+) -> Result<Json<User>, AppError> {
+    if let Some(role) = req.role.take() {
+        claims.require(Permission::UserManage)?;
+        let role = role
+            .parse::<UserRole>()
+            .map_err(|e| AppError::validation(vec![FieldError::new("role", &e)]))?;
+        req.role = Some(role.as_ref().to_string());
     }
+
+    // 首先验证用户是否存在且为学生
+    find_student(&pool, id).await?;
+
+    let updated_user = User::update(&pool, id, req).await?;
+    Ok(Json(updated_user))
 }
 
 /// 删除学生
 pub async fn delete_student(
     State(pool): State<Arc<Pool<Postgres>>>,
     Path(id): Path<Uuid>,
-) -> Result<StatusCode, (StatusCode, String)> {
+) -> Result<StatusCode, AppError> {
     // 首先验证用户是否存在且为学生
-    match User::find_by_id(&pool, id).await {
-        Ok(Some(user)) => {
-            if user.role.to_lowercase() != "student" {
-                return Err((StatusCode::NOT_FOUND, "学生不存在".to_string()));
-            }
-
-            match User::delete(&pool, id).await {
-                Ok(_) => Ok(StatusCode::NO_CONTENT),
-                Err(e) => {
-                    eprintln!("删除学生失败: {}", e);
-                    Err((
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "删除学生失败".to_string(),
-                    ))
-                }
-            }
-        }
-        Ok(None) => Err((StatusCode::NOT_FOUND, "学生不存在".to_string())),
-        Err(e) => {
-            eprintln!("查找学生失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "查找学生失败".to_string(),
-            ))
-        }
-    }
+    find_student(&pool, id).await?;
+
+    User::delete(&pool, id).await?;
+    Ok(StatusCode::NO_CONTENT)
 }
 
 /// 获取特定年级的学生列表
+///
+/// 只返回当前用户可以查看的学生
 pub async fn get_students_by_grade(
     State(pool): State<Arc<Pool<Postgres>>>,
+    Extension(claims): Extension<Claims>,
     Path(grade): Path<i32>,
-) -> Result<Json<Vec<User>>, (StatusCode, String)> {
-    match User::find_students_by_grade(&pool, grade).await {
-        Ok(students) => Ok(Json(students)),
-        Err(e) => {
-            eprintln!("获取学生列表失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取学生列表失败".to_string(),
-            ))
-        }
-    }
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<User>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let students =
+        User::find_students_by_grade(&pool, grade, scope.student_ids().as_deref(), &page).await?;
+    Ok(Json(students))
 }
 
 ///获取所有学生列表
 pub async fn get_all_students(
     State(pool): State<Arc<Pool<Postgres>>>,
-) -> Result<Json<Vec<User>>, (StatusCode, String)> {
-    match User::find_all_students(&pool).await {
-        Ok(students) => Ok(Json(students)),
-        Err(e) => {
-            eprintln!("获取学生列表失败: {}", e);
-            Err((
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "获取学生列表失败".to_string(),
-            ))
-        }
-    }
+    Extension(claims): Extension<Claims>,
+    Query(page): Query<PageParams>,
+) -> Result<Json<Page<User>>, AppError> {
+    let scope = StudentScope::of(&pool, &claims).await?;
+    let students = User::find_all_students(&pool, scope.student_ids().as_deref(), &page).await?;
+    Ok(Json(students))
 }
@@ -7,23 +7,124 @@ use axum::{
     extract::{Path, State},
 };
 use bcrypt::{DEFAULT_COST, hash};
-use sqlx::{Pool, Postgres};
+use sqlx::{Pool, Postgres, postgres::PgPool};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::config::{self, RegistrationMode};
 use crate::error::{AppError, AppErrorType};
 use crate::middleware::auth;
-use crate::model::models::user::{CreateUserRequest, LoginRequest, User};
+use crate::middleware::validation::ValidatedJson;
+use crate::model::models::invitation::Invitation;
+use crate::model::models::user::{CreateUserRequest, LoginRequest, User, UserRole};
 
 /// 用户注册API
 ///
 /// 接收用户注册信息，验证数据有效性，然后创建新用户
+/// 未使用邀请时只能注册学生账号，使用邀请时按邀请中的角色创建账号
 pub async fn register_user(
     State(pool): State<Arc<Pool<Postgres>>>,
-    Json(req): Json<CreateUserRequest>,
+    ValidatedJson(req): ValidatedJson<CreateUserRequest>,
 ) -> Result<Json<User>, AppError> {
+    let mode = config::get_config().registration.mode;
+    if mode == RegistrationMode::Disabled {
+        return Err(AppError::new_message("注册已关闭", AppErrorType::Forbidden));
+    }
+
+    check_duplicate_user(&pool, &req).await?;
+
+    let mut tx = pool.begin().await?;
+
+    // 确定注册后的角色
+    let invitation = match req.invitation_token.as_deref() {
+        Some(token) => {
+            let invitation =
+                Invitation::find_valid_by_token_hash(&mut *tx, &auth::hash_opaque_token(token))
+                    .await?
+                    .ok_or_else(|| {
+                        AppError::new_message("邀请无效或已过期", AppErrorType::Forbidden)
+                    })?;
+            if let Some(email) = &invitation.email
+                && !email.eq_ignore_ascii_case(&req.email)
+            {
+                return Err(AppError::new_message(
+                    "注册邮箱与邀请不一致",
+                    AppErrorType::Forbidden,
+                ));
+            }
+            Some(invitation)
+        }
+        None => {
+            if mode == RegistrationMode::Invite {
+                return Err(AppError::new_message(
+                    "需要邀请才能注册",
+                    AppErrorType::Forbidden,
+                ));
+            }
+            None
+        }
+    };
+    let role = match &invitation {
+        Some(invitation) => invitation.role.clone(),
+        None => {
+            // 开放注册只能创建学生账号
+            let role = parse_role(req.role.as_deref())?;
+            if role != UserRole::Student {
+                return Err(AppError::new_message(
+                    "教师和管理员账号需要通过邀请注册",
+                    AppErrorType::Forbidden,
+                ));
+            }
+            role.as_ref().to_string()
+        }
+    };
+
+    let req = CreateUserRequest {
+        password: hash_password(&req.password)?,
+        role: Some(role),
+        invitation_token: None,
+        ..req
+    };
+    let user = User::create(&mut *tx, req).await?;
+
+    if let Some(invitation) = invitation
+        && !Invitation::mark_used(&mut *tx, invitation.id, user.id).await?
+    {
+        return Err(AppError::new_message(
+            "邀请无效或已过期",
+            AppErrorType::Forbidden,
+        ));
+    }
+    tx.commit().await?;
+
+    Ok(Json(user))
+}
+
+/// 管理员创建用户API
+///
+/// 可以创建任意角色的账号，需要 user:manage 权限
+pub async fn create_user(
+    State(pool): State<Arc<Pool<Postgres>>>,
+    ValidatedJson(req): ValidatedJson<CreateUserRequest>,
+) -> Result<Json<User>, AppError> {
+    check_duplicate_user(&pool, &req).await?;
+
+    let role = parse_role(req.role.as_deref())?;
+    let req = CreateUserRequest {
+        password: hash_password(&req.password)?,
+        role: Some(role.as_ref().to_string()),
+        invitation_token: None,
+        ..req
+    };
+    let user = User::create(&*pool, req).await?;
+
+    Ok(Json(user))
+}
+
+/// 验证用户名和邮箱是否已被使用
+async fn check_duplicate_user(pool: &PgPool, req: &CreateUserRequest) -> Result<(), AppError> {
     // 验证用户名是否已存在
-    if let Ok(Some(_)) = User::find_by_username(&pool, &req.username).await {
+    if let Ok(Some(_)) = User::find_by_username(pool, &req.username).await {
         return Err(AppError::new_message(
             "用户名已被使用",
             AppErrorType::Duplicate,
@@ -31,37 +132,32 @@ pub async fn register_user(
     }
 
     // 验证邮箱是否已存在
-    if let Ok(Some(_)) = User::find_by_email(&pool, &req.email).await {
+    if let Ok(Some(_)) = User::find_by_email(pool, &req.email).await {
         return Err(AppError::new_message(
             "邮箱已被注册",
             AppErrorType::Duplicate,
         ));
     }
 
-    // 对密码进行哈希处理
-    let hashed_password = match hash(&req.password, DEFAULT_COST) {
-        Ok(hashed) => hashed,
-        Err(_) => {
-            return Err(AppError::new_message(
-                "密码加密失败",
-                AppErrorType::Internal,
-            ));
-        }
-    };
-
-    // 创建包含哈希密码的请求
-    let req_with_hashed_password = CreateUserRequest {
-        password: hashed_password,
-        ..req
-    };
+    Ok(())
+}
 
-    // 创建新用户
-    match User::create(&pool, req_with_hashed_password).await {
-        Ok(user) => Ok(Json(user)),
-        Err(e) => Err(AppError::new(e, AppErrorType::Db)),
+/// 解析请求中的用户角色，未指定时为学生
+fn parse_role(role: Option<&str>) -> Result<UserRole, AppError> {
+    match role {
+        Some(role) => role
+            .parse::<UserRole>()
+            .map_err(|e| AppError::new_message(&e, AppErrorType::BadRequest)),
+        None => Ok(UserRole::default()),
     }
 }
 
+/// 对密码进行哈希处理
+pub fn hash_password(password: &str) -> Result<String, AppError> {
+    hash(password, DEFAULT_COST)
+        .map_err(|_| AppError::new_message("密码加密失败", AppErrorType::Internal))
+}
+
 /// 通过用户ID获取用户名
 ///
 /// 根据用户ID查询并返回对应的用户名
@@ -88,10 +184,13 @@ pub async fn login_user(
         Ok(Some(user)) => {
             // 生成JWT令牌
             let token = auth::generate_token(&user)?;
+            // 签发刷新令牌，开启新的登录会话
+            let refresh_token = auth::issue_refresh_token(&*pool, user.id, None).await?;
             // 返回用户信息和令牌
             let response = serde_json::json!({
                 "user": user,
-                "token": token
+                "token": token,
+                "refresh_token": refresh_token
             });
             Ok(Json(response))
         }
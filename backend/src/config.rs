@@ -24,6 +24,12 @@ pub struct Config {
     pub server: ServerConfig,
     pub database: DatabaseConfig,
     pub jwt: JwtConfig,
+    #[serde(default)]
+    pub storage: StorageConfig,
+    #[serde(default)]
+    pub registration: RegistrationConfig,
+    #[serde(default)]
+    pub report: ReportConfig,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -41,7 +47,88 @@ pub struct DatabaseConfig {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct JwtConfig {
     pub secret: String,
-    pub expiration: u64, // 过期时间（分钟）
+    pub expiration: u64, // 访问令牌过期时间（分钟）
+    #[serde(default = "default_refresh_expiration")]
+    pub refresh_expiration: u64, // 刷新令牌过期时间（天）
+}
+
+fn default_refresh_expiration() -> u64 {
+    30
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StorageConfig {
+    pub backend: String,    // 存储后端（目前支持 local）
+    pub root: String,       // 本地存储根目录
+    pub max_file_size: u64, // 单个文件大小上限（字节）
+    pub allowed_mime_types: Vec<String>,
+}
+
+impl Default for StorageConfig {
+    fn default() -> Self {
+        StorageConfig {
+            backend: "local".to_string(),
+            root: "uploads".to_string(),
+            max_file_size: 20 * 1024 * 1024, // 20MB
+            allowed_mime_types: vec![
+                "application/pdf".to_string(),
+                "application/msword".to_string(),
+                "application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+                    .to_string(),
+                "image/png".to_string(),
+                "image/jpeg".to_string(),
+                "text/plain".to_string(),
+                "application/zip".to_string(),
+            ],
+        }
+    }
+}
+
+/// 注册模式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RegistrationMode {
+    /// 开放注册，未使用邀请时只能注册学生账号
+    #[default]
+    Open,
+    /// 只能通过邀请注册
+    Invite,
+    /// 关闭注册
+    Disabled,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct RegistrationConfig {
+    pub mode: RegistrationMode,     // 注册模式：open、invite 或 disabled
+    pub invitation_expiration: u64, // 邀请默认有效期（小时）
+}
+
+impl Default for RegistrationConfig {
+    fn default() -> Self {
+        RegistrationConfig {
+            mode: RegistrationMode::Open,
+            invitation_expiration: 72,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ReportConfig {
+    pub school_name: String,           // 报告单页眉中的学校名称
+    pub header: Vec<String>,           // 学校名称下方的页眉文字，如地址、电话
+    pub font_path: String,             // TrueType 字体文件路径，需包含中文字形
+    pub template_path: Option<String>, // 报告单模板路径，未设置时使用内置模板
+}
+
+impl Default for ReportConfig {
+    fn default() -> Self {
+        ReportConfig {
+            school_name: "学生管理系统".to_string(),
+            header: vec![],
+            font_path: "fonts/report.ttf".to_string(),
+            template_path: None,
+        }
+    }
 }
 
 impl Config {
@@ -57,8 +144,10 @@ impl Config {
         let config: Config = toml::from_str(&contents)?;
         Ok(config)
     }
+}
 
-    pub fn default() -> Self {
+impl Default for Config {
+    fn default() -> Self {
         Config {
             server: ServerConfig {
                 host: "127.0.0.1".to_string(),
@@ -71,7 +160,11 @@ impl Config {
             jwt: JwtConfig {
                 secret: "default_secret_key_change_in_production".to_string(),
                 expiration: 60, // 60分钟
+                refresh_expiration: default_refresh_expiration(),
             },
+            storage: StorageConfig::default(),
+            registration: RegistrationConfig::default(),
+            report: ReportConfig::default(),
         }
     }
 }
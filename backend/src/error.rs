@@ -6,6 +6,8 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use tracing::{error, info};
+use validator::{ValidationErrors, ValidationErrorsKind};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub enum AppErrorType {
@@ -17,12 +19,37 @@ pub enum AppErrorType {
     Forbidden,
     Time,
     Internal,
+    BadRequest,
+    FileTooLarge,
+    UnsupportedFileType,
+    Validation,
+    Conflict,
+}
+
+/// 单个字段的校验错误
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FieldError {
+    /// 字段名
+    pub field: String,
+    /// 错误说明
+    pub message: String,
+}
+
+impl FieldError {
+    pub fn new(field: &str, message: &str) -> Self {
+        Self {
+            field: field.to_string(),
+            message: message.to_string(),
+        }
+    }
 }
 
 #[derive(Debug)]
 pub struct AppError {
     pub cause: Option<Box<dyn std::error::Error + Send + Sync>>,
     pub types: AppErrorType,
+    /// 字段校验错误（仅 Validation）
+    pub details: Vec<FieldError>,
 }
 
 impl AppError {
@@ -33,22 +60,43 @@ impl AppError {
         Self {
             cause: Some(Box::new(cause)),
             types,
+            details: Vec::new(),
         }
     }
 
     pub fn new_message(msg: &str, types: AppErrorType) -> Self {
         Self {
-            cause: Some(Box::new(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                msg.to_string(),
-            ))),
+            cause: Some(Box::new(std::io::Error::other(msg.to_string()))),
             types,
+            details: Vec::new(),
         }
     }
 
     pub fn notfound() -> Self {
         Self::new_message("没有找到符合条件的数据", AppErrorType::Notfound)
     }
+
+    /// 创建包含字段错误的校验错误
+    pub fn validation(details: Vec<FieldError>) -> Self {
+        Self {
+            details,
+            ..Self::new_message("请求数据校验失败", AppErrorType::Validation)
+        }
+    }
+
+    /// 对应的HTTP状态码
+    pub fn status_code(&self) -> StatusCode {
+        match self.types {
+            AppErrorType::Notfound => StatusCode::NOT_FOUND,
+            AppErrorType::Duplicate | AppErrorType::Conflict => StatusCode::CONFLICT,
+            AppErrorType::IncorrectLogin => StatusCode::UNAUTHORIZED,
+            AppErrorType::Forbidden => StatusCode::FORBIDDEN,
+            AppErrorType::BadRequest | AppErrorType::Validation => StatusCode::BAD_REQUEST,
+            AppErrorType::FileTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            AppErrorType::UnsupportedFileType => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+            _ => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
 }
 
 impl std::fmt::Display for AppError {
@@ -61,24 +109,35 @@ impl std::error::Error for AppError {}
 
 impl IntoResponse for AppError {
     fn into_response(self) -> Response {
-        let status = match self.types {
-            AppErrorType::Notfound => StatusCode::NOT_FOUND,
-            AppErrorType::Duplicate => StatusCode::CONFLICT,
-            AppErrorType::IncorrectLogin => StatusCode::UNAUTHORIZED,
-            AppErrorType::Forbidden => StatusCode::FORBIDDEN,
-            _ => StatusCode::INTERNAL_SERVER_ERROR,
-        };
+        let status = self.status_code();
 
         let msg = self
             .cause
             .as_ref()
             .map_or("有错误发生".to_string(), |e| e.to_string());
 
-        let body = json!({
+        // 错误原因记录到日志，日志所在的请求 span 带有请求ID
+        if status.is_server_error() {
+            error!(code = ?self.types, cause = ?self.cause, "请求处理失败: {}", msg);
+        } else {
+            info!(code = ?self.types, "请求被拒绝: {}", msg);
+        }
+
+        // 服务器错误不向客户端暴露内部原因
+        let msg = if status.is_server_error() {
+            "服务器内部错误，请凭响应头中的 x-request-id 联系管理员".to_string()
+        } else {
+            msg
+        };
+
+        let mut body = json!({
             "code": format!("{:?}", self.types), // 例如 "Notfound"
             "error": self.types.to_string(),    // 例如 "资源未找到"
             "message": msg
         });
+        if !self.details.is_empty() {
+            body["details"] = json!(self.details);
+        }
 
         (status, Json(body)).into_response()
     }
@@ -95,6 +154,11 @@ impl std::fmt::Display for AppErrorType {
             AppErrorType::Forbidden => "权限不足",
             AppErrorType::Time => "时间解析错误",
             AppErrorType::Internal => "内部错误",
+            AppErrorType::BadRequest => "请求参数错误",
+            AppErrorType::FileTooLarge => "文件过大",
+            AppErrorType::UnsupportedFileType => "不支持的文件类型",
+            AppErrorType::Validation => "数据校验失败",
+            AppErrorType::Conflict => "数据冲突",
         };
         write!(f, "{}", msg)
     }
@@ -109,11 +173,59 @@ impl From<sqlx::Error> for AppError {
             sqlx::Error::Database(db_err) if db_err.is_unique_violation() => {
                 AppError::new_message("数据已存在", AppErrorType::Duplicate)
             }
+            sqlx::Error::Database(db_err) if db_err.is_foreign_key_violation() => {
+                AppError::new_message("关联的数据不存在或仍被引用", AppErrorType::Conflict)
+            }
+            sqlx::Error::Database(db_err) if db_err.is_check_violation() => {
+                AppError::new_message("数据不满足约束条件", AppErrorType::Validation)
+            }
             _ => AppError::new(err, AppErrorType::Db),
         }
     }
 }
 
+impl From<ValidationErrors> for AppError {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut details = Vec::new();
+        collect_field_errors(&errors, "", &mut details);
+        details.sort_by(|a, b| a.field.cmp(&b.field));
+        AppError::validation(details)
+    }
+}
+
+/// 展开校验错误，嵌套字段使用 `records[0].reason` 形式的路径
+pub fn collect_field_errors(
+    errors: &ValidationErrors,
+    prefix: &str,
+    details: &mut Vec<FieldError>,
+) {
+    for (field, kind) in errors.errors() {
+        let path = if prefix.is_empty() {
+            field.to_string()
+        } else {
+            format!("{}.{}", prefix, field)
+        };
+
+        match kind {
+            ValidationErrorsKind::Field(errors) => {
+                for error in errors {
+                    let message = error.message.as_ref().map_or_else(
+                        || format!("校验规则 {} 未通过", error.code),
+                        |m| m.to_string(),
+                    );
+                    details.push(FieldError::new(&path, &message));
+                }
+            }
+            ValidationErrorsKind::Struct(errors) => collect_field_errors(errors, &path, details),
+            ValidationErrorsKind::List(items) => {
+                for (index, errors) in items {
+                    collect_field_errors(errors, &format!("{}[{}]", path, index), details);
+                }
+            }
+        }
+    }
+}
+
 impl From<anyhow::Error> for AppError {
     fn from(err: anyhow::Error) -> Self {
         AppError::new_message(&err.to_string(), AppErrorType::Db)
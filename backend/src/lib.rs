@@ -5,5 +5,9 @@ pub mod logger;
 pub mod middleware;
 pub mod model;
 pub mod routes;
+pub mod storage;
+
+#[cfg(test)]
+mod test_util;
 
 pub type Result<T> = std::result::Result<T, error::AppError>;
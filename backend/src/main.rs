@@ -1,4 +1,6 @@
-use backend::{config, logger, model, routes};
+use backend::model::models::permission::RolePermission;
+use backend::model::models::user::{User, UserRole};
+use backend::{config, logger, model, routes, storage};
 use std::net::SocketAddr;
 use std::path::Path;
 use std::sync::Arc;
@@ -23,8 +25,24 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // 初始化全局配置
     config::init_config(config.clone());
 
+    // 命令行子命令：
+    //   backend migrate <run|status|rollback [版本号]>
+    //   backend set-role <用户名> <角色>
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        Some("migrate") => return run_migrate_command(&args[1..]).await,
+        Some("set-role") => return run_set_role_command(&args[1..]).await,
+        _ => {}
+    }
+
+    // 初始化文件存储后端
+    storage::init_storage(&config::get_config().storage)?;
+
     // 初始化数据库连接池
     let pool = model::get_db_pool(config::get_config()).await?;
+
+    // 加载角色权限配置
+    RolePermission::load(&pool).await?;
     let pool = Arc::new(pool);
 
     // 创建应用路由
@@ -42,3 +60,76 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     Ok(())
 }
+
+/// 执行数据库迁移子命令
+async fn run_migrate_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let pool = model::connect_db_pool(config::get_config()).await?;
+
+    match args.first().map(String::as_str).unwrap_or("run") {
+        "run" => model::migrate::run_migrations(&pool).await?,
+        "status" => {
+            let version = model::migrate::current_version(&pool).await?;
+            println!(
+                "当前数据库版本: {}",
+                version.map_or("无".to_string(), |v| v.to_string())
+            );
+            for m in model::migrate::status(&pool).await? {
+                println!(
+                    "{:>16}  {:<8}  {}{}",
+                    m.version,
+                    if m.applied { "已应用" } else { "未应用" },
+                    m.description,
+                    if m.checksum_ok {
+                        ""
+                    } else {
+                        "  (校验和不一致)"
+                    }
+                );
+            }
+        }
+        "rollback" => {
+            // 未指定目标版本时只回滚最近一次迁移
+            let target = match args.get(1) {
+                Some(v) => v.parse::<i64>()?,
+                None => {
+                    let statuses = model::migrate::status(&pool).await?;
+                    let mut applied = statuses.iter().filter(|m| m.applied).map(|m| m.version);
+                    let last = applied.next_back();
+                    match last {
+                        Some(_) => applied.next_back().unwrap_or(0),
+                        None => {
+                            println!("没有可回滚的迁移");
+                            return Ok(());
+                        }
+                    }
+                }
+            };
+            model::migrate::rollback(&pool, target).await?;
+        }
+        other => {
+            return Err(format!(
+                "未知的迁移命令: {}（可用: run, status, rollback [版本号]）",
+                other
+            )
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+/// 设置用户角色子命令，用于创建首个管理员账号
+async fn run_set_role_command(args: &[String]) -> Result<(), Box<dyn std::error::Error>> {
+    let (Some(username), Some(role)) = (args.first(), args.get(1)) else {
+        return Err("用法: backend set-role <用户名> <admin|teacher|student|parent>".into());
+    };
+    let role: UserRole = role.parse()?;
+
+    let pool = model::get_db_pool(config::get_config()).await?;
+    match User::set_role_by_username(&pool, username, role).await? {
+        Some(user) => println!("已将用户 {} 的角色设置为 {}", user.username, user.role),
+        None => return Err(format!("用户不存在: {}", username).into()),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,231 @@
+//! 审计中间件模块
+//!
+//! 为资源的新增、修改和删除自动写入活动记录，记录操作用户、资源ID以及变更前后的差异
+
+use axum::body::{Body, to_bytes};
+use axum::extract::rejection::RawPathParamsRejection;
+use axum::extract::{RawPathParams, Request, State};
+use axum::middleware::Next;
+use axum::response::Response;
+use serde_json::{Map, Value, json};
+use sqlx::{Pool, Postgres};
+use std::collections::BTreeSet;
+use std::sync::Arc;
+use tracing::error;
+use uuid::Uuid;
+
+use super::auth::Claims;
+use crate::model::models::activity::{Activity, ActivityType, CreateActivityRequest};
+
+/// 读取新增接口响应体的大小上限
+const MAX_RESPONSE_BODY: usize = 1024 * 1024;
+
+/// 资源操作
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    /// 新增
+    Create,
+    /// 修改
+    Update,
+    /// 删除
+    Delete,
+}
+
+/// 审计中间件状态，声明路由对应的活动类型
+///
+/// ```ignore
+/// post(studentapi::create_student).layer(from_fn_with_state(
+///     Audit::new(&pool, ActivityType::AddStudent),
+///     audit_middleware,
+/// ))
+/// ```
+#[derive(Clone)]
+pub struct Audit {
+    pool: Arc<Pool<Postgres>>,
+    activity_type: ActivityType,
+}
+
+impl Audit {
+    /// 创建审计中间件状态
+    pub fn new(pool: &Arc<Pool<Postgres>>, activity_type: ActivityType) -> Self {
+        Self {
+            pool: pool.clone(),
+            activity_type,
+        }
+    }
+}
+
+/// 活动类型对应的数据表和操作，不对应资源变更的活动类型返回 `None`
+///
+/// 返回 `None` 的活动类型一次操作涉及多行或多张表，无法用单个资源的前后快照描述，
+/// 由处理函数在写入数据的同一事务中调用 [`Activity::create`] 记录带有说明的活动，
+/// 数据和活动记录同时提交或回滚
+fn target(activity_type: ActivityType) -> Option<(&'static str, Action)> {
+    use ActivityType::*;
+
+    let target = match activity_type {
+        AddStudent => ("users", Action::Create),
+        UpdateStudent => ("users", Action::Update),
+        DeleteStudent => ("users", Action::Delete),
+        AddCourse => ("courses", Action::Create),
+        UpdateCourse => ("courses", Action::Update),
+        DeleteCourse => ("courses", Action::Delete),
+        AddClassGroup => ("class_groups", Action::Create),
+        UpdateClassGroup => ("class_groups", Action::Update),
+        DeleteClassGroup => ("class_groups", Action::Delete),
+        EnrollStudent => ("enrollments", Action::Create),
+        DropEnrollment => ("enrollments", Action::Update),
+        AddCourseRecord => ("course_records", Action::Create),
+        UpdateCourseRecord => ("course_records", Action::Update),
+        DeleteCourseRecord => ("course_records", Action::Delete),
+        AddExam => ("exams", Action::Create),
+        UpdateExam | UploadExamFile => ("exams", Action::Update),
+        DeleteExam => ("exams", Action::Delete),
+        AddExamRecord => ("exam_records", Action::Create),
+        UpdateExamRecord => ("exam_records", Action::Update),
+        DeleteExamRecord => ("exam_records", Action::Delete),
+        AddQuestion => ("questions", Action::Create),
+        UpdateQuestion => ("questions", Action::Update),
+        DeleteQuestion => ("questions", Action::Delete),
+        SubmitHomework => ("submissions", Action::Create),
+        UpdateHomework | UploadHomeworkFile | GradeHomework | ReturnHomework => {
+            ("submissions", Action::Update)
+        }
+        DeleteHomework => ("submissions", Action::Delete),
+        AddAssignment => ("assignments", Action::Create),
+        UpdateAssignment | UploadAssignmentFile => ("assignments", Action::Update),
+        DeleteAssignment => ("assignments", Action::Delete),
+        AddGradeCategory => ("grade_categories", Action::Create),
+        UpdateGradeCategory => ("grade_categories", Action::Update),
+        DeleteGradeCategory => ("grade_categories", Action::Delete),
+        AddGradeExcusal => ("grade_excusals", Action::Create),
+        DeleteGradeExcusal => ("grade_excusals", Action::Delete),
+        AddAnnouncement => ("announcements", Action::Create),
+        ImportStudents | RecordAttendance | DeleteAttendance | SetExamQuestions
+        | AssignHomework | SetLatePolicy | DeleteLatePolicy | SetGradingScale
+        | DeleteGradingScale | OverrideFinalGrade | LockFinalGrades | UnlockFinalGrades
+        | UploadGrade | Other => return None,
+    };
+    Some(target)
+}
+
+/// 审计中间件
+///
+/// 需要放在权限中间件之内，以便读取操作用户。
+/// 只有处理成功的请求才写入活动记录，写入失败只记录日志，不影响响应
+pub async fn audit_middleware(
+    State(audit): State<Audit>,
+    params: Result<RawPathParams, RawPathParamsRejection>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some((table, action)) = target(audit.activity_type) else {
+        return next.run(req).await;
+    };
+
+    let claims = req.extensions().get::<Claims>().cloned();
+    let path_id = params.ok().and_then(|params| {
+        params
+            .iter()
+            .find(|(key, _)| *key == "id")
+            .and_then(|(_, value)| value.parse::<Uuid>().ok())
+    });
+
+    let before = match (action, path_id) {
+        (Action::Create, _) | (_, None) => None,
+        (_, Some(id)) => snapshot(&audit.pool, table, id).await,
+    };
+
+    let response = next.run(req).await;
+    let Some(claims) = claims else {
+        return response;
+    };
+    if !response.status().is_success() {
+        return response;
+    }
+
+    let (response, resource_id) = match path_id {
+        Some(id) => (response, Some(id)),
+        None => created_id(response).await,
+    };
+
+    let after = match (action, resource_id) {
+        (Action::Delete, _) | (_, None) => None,
+        (_, Some(id)) => snapshot(&audit.pool, table, id).await,
+    };
+
+    let user_id = match claims.user_id() {
+        Ok(user_id) => user_id,
+        Err(_) => return response,
+    };
+    let label = audit.activity_type.label();
+    let activity = CreateActivityRequest {
+        activity_type: audit.activity_type.as_ref().to_string(),
+        description: match resource_id {
+            Some(id) => format!("{} {}", label, id),
+            None => label.to_string(),
+        },
+        user_id,
+        user_name: claims.username,
+        user_role: claims.role,
+        resource_id,
+        changes: Some(diff(before, after)),
+    };
+
+    if let Err(e) = Activity::create(&*audit.pool, activity).await {
+        error!("写入审计记录失败: {}", e);
+    }
+
+    response
+}
+
+/// 读取资源快照，失败时记录日志并返回 `None`
+async fn snapshot(pool: &Pool<Postgres>, table: &'static str, id: Uuid) -> Option<Value> {
+    Activity::snapshot(pool, table, id)
+        .await
+        .unwrap_or_else(|e| {
+            error!("读取审计快照失败: {}", e);
+            None
+        })
+}
+
+/// 从新增接口的响应体中读取资源ID，并重新组装响应
+async fn created_id(response: Response) -> (Response, Option<Uuid>) {
+    let (parts, body) = response.into_parts();
+    let bytes = match to_bytes(body, MAX_RESPONSE_BODY).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            error!("读取响应体失败: {}", e);
+            return (Response::from_parts(parts, Body::empty()), None);
+        }
+    };
+
+    let id = serde_json::from_slice::<Value>(&bytes)
+        .ok()
+        .and_then(|value| value.get("id")?.as_str()?.parse::<Uuid>().ok());
+
+    (Response::from_parts(parts, Body::from(bytes)), id)
+}
+
+/// 计算变更前后的差异
+///
+/// 修改时只保留发生变化的字段，新增和删除时保留完整快照
+fn diff(before: Option<Value>, after: Option<Value>) -> Value {
+    let (Some(Value::Object(before)), Some(Value::Object(after))) = (&before, &after) else {
+        return json!({ "before": before, "after": after });
+    };
+
+    let keys: BTreeSet<&String> = before.keys().chain(after.keys()).collect();
+    let mut changed_before = Map::new();
+    let mut changed_after = Map::new();
+    for key in keys {
+        let old = before.get(key).cloned().unwrap_or(Value::Null);
+        let new = after.get(key).cloned().unwrap_or(Value::Null);
+        if old != new {
+            changed_before.insert(key.clone(), old);
+            changed_after.insert(key.clone(), new);
+        }
+    }
+
+    json!({ "before": changed_before, "after": changed_after })
+}
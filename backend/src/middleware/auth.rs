@@ -1,17 +1,22 @@
 //! 认证中间件模块
 //!
 //! 提供JWT认证和权限验证功能
-use axum::extract::{Json, Request};
+use axum::extract::Request;
+use axum::http::HeaderMap;
 use axum::middleware::Next;
 use axum::response::{IntoResponse, Response};
 use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation, decode, encode};
 use serde::{Deserialize, Serialize};
-use std::time::{SystemTime, UNIX_EPOCH};
+use sha2::{Digest, Sha256};
+use sqlx::PgExecutor;
 use time::{Duration, OffsetDateTime};
+use uuid::Uuid;
 
 use crate::config;
 use crate::error::{AppError, AppErrorType};
-use crate::model::models::user::User;
+use crate::model::models::permission::{Permission, RolePermission};
+use crate::model::models::refresh_token::RefreshToken;
+use crate::model::models::user::{User, UserRole};
 
 /// JWT声明结构
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -28,18 +33,53 @@ pub struct Claims {
     pub iat: u64,
 }
 
+impl Claims {
+    /// 解析令牌中的用户ID
+    pub fn user_id(&self) -> Result<Uuid, AppError> {
+        Uuid::parse_str(&self.sub)
+            .map_err(|_| AppError::new_message("无效的令牌用户", AppErrorType::Forbidden))
+    }
+
+    /// 解析令牌中的用户角色
+    pub fn user_role(&self) -> Result<UserRole, AppError> {
+        self.role
+            .parse()
+            .map_err(|_| AppError::new_message("无效的令牌角色", AppErrorType::Forbidden))
+    }
+
+    /// 判断当前用户是否拥有指定权限
+    pub fn has_permission(&self, permission: Permission) -> bool {
+        self.user_role()
+            .is_ok_and(|role| RolePermission::role_has(&role, permission))
+    }
+
+    /// 要求当前用户拥有指定权限
+    pub fn require(&self, permission: Permission) -> Result<(), AppError> {
+        if self.has_permission(permission) {
+            Ok(())
+        } else {
+            Err(AppError::new_message(
+                &format!("缺少权限: {}", permission),
+                AppErrorType::Forbidden,
+            ))
+        }
+    }
+}
+
 /// 刷新令牌请求结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RefreshTokenRequest {
-    /// 旧令牌
-    pub token: String,
+    /// 刷新令牌
+    pub refresh_token: String,
 }
 
 /// 刷新令牌响应结构
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RefreshTokenResponse {
-    /// 新令牌
+    /// 新的访问令牌
     pub token: String,
+    /// 新的刷新令牌，旧刷新令牌随即失效
+    pub refresh_token: String,
 }
 
 /// 生成JWT令牌
@@ -96,78 +136,39 @@ pub fn verify_token(token: &str) -> Result<Claims, AppError> {
     Ok(token_data.claims)
 }
 
-/// 验证JWT令牌（用于刷新，允许已过期但在刷新窗口内的令牌）
-pub fn verify_token_for_refresh(token: &str) -> Result<Claims, AppError> {
+/// 签发刷新令牌
+///
+/// 未指定令牌族时开启新的登录会话，轮换时沿用原令牌族
+pub async fn issue_refresh_token<'e, E: PgExecutor<'e>>(
+    executor: E,
+    user_id: Uuid,
+    family_id: Option<Uuid>,
+) -> Result<String, AppError> {
     let config = config::get_config();
 
-    // 创建自定义验证，忽略过期检查
-    let mut validation = Validation::default();
-    validation.validate_exp = false;
-
-    // 解码令牌，忽略过期检查
-    let token_data = decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(config.jwt.secret.as_bytes()),
-        &validation,
+    let token = generate_opaque_token();
+    let expires_at =
+        OffsetDateTime::now_utc() + Duration::days(config.jwt.refresh_expiration as i64);
+    RefreshToken::create(
+        executor,
+        user_id,
+        family_id.unwrap_or_else(Uuid::new_v4),
+        &hash_opaque_token(&token),
+        expires_at,
     )
-    .map_err(|e| match e.kind() {
-        jsonwebtoken::errors::ErrorKind::InvalidSignature => {
-            AppError::new_message("无效的令牌签名", AppErrorType::Forbidden)
-        }
-        _ => AppError::new(e, AppErrorType::Crypt),
-    })?;
-
-    let claims = token_data.claims;
+    .await?;
 
-    // 获取当前时间戳
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .expect("时间获取错误")
-        .as_secs();
-
-    // 检查令牌是否在刷新窗口内（过期后的30分钟内）
-    if claims.exp < now && now - claims.exp > 30 * 60 {
-        return Err(AppError::new_message(
-            "令牌已过期且超出刷新窗口",
-            AppErrorType::Forbidden,
-        ));
-    }
-
-    Ok(claims)
+    Ok(token)
 }
 
-/// 刷新JWT令牌
-pub fn refresh_token(old_token: &str) -> Result<String, AppError> {
-    // 验证旧令牌（允许已过期但在刷新窗口内的令牌）
-    let claims = verify_token_for_refresh(old_token)?;
-
-    let config = config::get_config();
-
-    // 获取当前时间
-    let now = OffsetDateTime::now_utc();
-    let iat = now.unix_timestamp() as u64;
-
-    // 计算新的过期时间
-    let exp = (now + Duration::minutes(config.jwt.expiration as i64)).unix_timestamp() as u64;
-
-    // 创建新的JWT声明，保留用户信息
-    let new_claims = Claims {
-        sub: claims.sub,
-        username: claims.username,
-        role: claims.role,
-        exp,
-        iat,
-    };
-
-    // 创建新的JWT令牌
-    let token = encode(
-        &Header::default(),
-        &new_claims,
-        &EncodingKey::from_secret(config.jwt.secret.as_bytes()),
-    )
-    .map_err(|e| AppError::new(e, AppErrorType::Crypt))?;
+/// 生成随机的不透明令牌（64位十六进制字符串）
+pub fn generate_opaque_token() -> String {
+    format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple())
+}
 
-    Ok(token)
+/// 计算不透明令牌的哈希，数据库中只保存哈希值
+pub fn hash_opaque_token(token: &str) -> String {
+    format!("{:x}", Sha256::digest(token.as_bytes()))
 }
 
 /// 从认证头中提取令牌
@@ -179,106 +180,29 @@ pub fn extract_token_from_header(auth_header: &str) -> Option<&str> {
     }
 }
 
-/// 认证中间件
-pub async fn auth_middleware(req: Request, next: Next) -> Result<Response, Response> {
+/// 从请求头中解析并验证令牌
+pub fn authenticate(headers: &HeaderMap) -> Result<Claims, AppError> {
     // 从请求头中获取认证信息
-    let auth_header = req
-        .headers()
+    let auth_header = headers
         .get("Authorization")
-        .and_then(|value| value.to_str().ok());
-
-    match auth_header {
-        Some(auth_header) => {
-            // 从认证头中提取令牌
-            if let Some(token) = extract_token_from_header(auth_header) {
-                // 验证令牌
-                match verify_token(token) {
-                    Ok(claims) => {
-                        // 将用户信息添加到请求扩展中
-                        let mut req = req;
-                        req.extensions_mut().insert(claims);
-
-                        // 继续处理请求
-                        Ok(next.run(req).await)
-                    }
-                    Err(e) => {
-                        // 令牌验证失败
-                        Err(e.into_response())
-                    }
-                }
-            } else {
-                // 认证头格式错误
-                Err(
-                    AppError::new_message("无效的认证头格式", AppErrorType::Forbidden)
-                        .into_response(),
-                )
-            }
-        }
-        None => {
-            // 缺少认证头
-            Err(AppError::new_message("需要认证", AppErrorType::Forbidden).into_response())
-        }
-    }
-}
+        .and_then(|value| value.to_str().ok())
+        .ok_or_else(|| AppError::new_message("需要认证", AppErrorType::Forbidden))?;
 
-/// 刷新令牌处理函数
-pub async fn refresh_token_handler(
-    Json(req): Json<RefreshTokenRequest>,
-) -> Result<Json<RefreshTokenResponse>, AppError> {
-    // 刷新令牌
-    let new_token = refresh_token(&req.token)?;
+    // 从认证头中提取令牌
+    let token = extract_token_from_header(auth_header)
+        .ok_or_else(|| AppError::new_message("无效的认证头格式", AppErrorType::Forbidden))?;
 
-    // 返回新令牌
-    Ok(Json(RefreshTokenResponse { token: new_token }))
+    // 验证令牌
+    verify_token(token)
 }
 
-/// 管理员权限中间件
-pub async fn admin_middleware(req: Request, next: Next) -> Result<Response, Response> {
-    // 先进行基本的认证
-    let auth_header = req
-        .headers()
-        .get("Authorization")
-        .and_then(|value| value.to_str().ok());
+/// 认证中间件
+pub async fn auth_middleware(mut req: Request, next: Next) -> Result<Response, Response> {
+    let claims = authenticate(req.headers()).map_err(IntoResponse::into_response)?;
 
-    match auth_header {
-        Some(auth_header) => {
-            // 从认证头中提取令牌
-            if let Some(token) = extract_token_from_header(auth_header) {
-                // 验证令牌
-                match verify_token(token) {
-                    Ok(claims) => {
-                        // 检查用户角色 - 允许admin和teacher角色访问管理员功能
-                        if claims.role == "admin" || claims.role == "teacher" {
-                            // 将用户信息添加到请求扩展中
-                            let mut req = req;
-                            req.extensions_mut().insert(claims);
+    // 将用户信息添加到请求扩展中
+    req.extensions_mut().insert(claims);
 
-                            // 继续处理请求
-                            Ok(next.run(req).await)
-                        } else {
-                            // 用户不具有管理员角色
-                            Err(
-                                AppError::new_message("需要管理员权限", AppErrorType::Forbidden)
-                                    .into_response(),
-                            )
-                        }
-                    }
-                    Err(e) => {
-                        // 令牌验证失败
-                        Err(e.into_response())
-                    }
-                }
-            } else {
-                // 认证头格式错误
-                Err(
-                    AppError::new_message("无效的认证头格式", AppErrorType::Forbidden)
-                        .into_response(),
-                )
-            }
-        }
-        None => {
-            // 缺少认证头
-            Err(AppError::new_message("需要认证", AppErrorType::Forbidden).into_response())
-        }
-    }
+    // 继续处理请求
+    Ok(next.run(req).await)
 }
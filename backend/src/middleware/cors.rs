@@ -1,9 +1,12 @@
 use tower_http::cors::{Any, CorsLayer};
 
+use super::request_id::REQUEST_ID_HEADER;
+
 /// 创建跨域中间件
 pub fn create_layer() -> CorsLayer {
     CorsLayer::new()
         .allow_methods(Any)
         .allow_headers(Any)
         .allow_origin(Any)
+        .expose_headers([REQUEST_ID_HEADER])
 }
@@ -2,9 +2,13 @@
 //!
 //! 这个模块包含所有的中间件
 //!
+pub mod audit;
 pub mod auth;
 pub mod cors;
+pub mod permission;
+pub mod request_id;
 pub mod trace_layer;
+pub mod validation;
 // use std::boxed::Box;
 // use tower::Layer;
 // use tower::ServiceBuilder;
@@ -0,0 +1,34 @@
+//! 权限中间件模块
+//!
+//! 提供按路由组声明权限的中间件
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+
+use super::auth;
+use crate::model::models::permission::Permission;
+
+/// 权限中间件
+///
+/// 验证令牌并要求用户拥有状态中列出的全部权限，通过后将用户信息添加到请求扩展中
+///
+/// ```ignore
+/// const STUDENT_READ: &[Permission] = &[Permission::StudentRead];
+/// router.layer(from_fn_with_state(STUDENT_READ, permission_middleware))
+/// ```
+pub async fn permission_middleware(
+    State(required): State<&'static [Permission]>,
+    mut req: Request,
+    next: Next,
+) -> Result<Response, Response> {
+    let claims = auth::authenticate(req.headers()).map_err(IntoResponse::into_response)?;
+
+    for permission in required {
+        claims
+            .require(*permission)
+            .map_err(IntoResponse::into_response)?;
+    }
+
+    req.extensions_mut().insert(claims);
+    Ok(next.run(req).await)
+}
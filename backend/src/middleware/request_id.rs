@@ -0,0 +1,15 @@
+use axum::http::HeaderName;
+use tower_http::request_id::{MakeRequestUuid, PropagateRequestIdLayer, SetRequestIdLayer};
+
+/// 请求ID请求头，用于关联同一请求的日志和错误响应
+pub const REQUEST_ID_HEADER: HeaderName = HeaderName::from_static("x-request-id");
+
+/// 创建请求ID中间件，客户端未提供请求ID时生成UUID
+pub fn create_set_layer() -> SetRequestIdLayer<MakeRequestUuid> {
+    SetRequestIdLayer::new(REQUEST_ID_HEADER, MakeRequestUuid)
+}
+
+/// 创建请求ID回传中间件，将请求ID写入响应头
+pub fn create_propagate_layer() -> PropagateRequestIdLayer {
+    PropagateRequestIdLayer::new(REQUEST_ID_HEADER)
+}
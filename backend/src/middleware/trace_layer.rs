@@ -1,14 +1,37 @@
+use axum::http::Request;
 use tower_http::{
     classify::{ServerErrorsAsFailures, SharedClassifier},
-    trace::{self, TraceLayer},
+    trace::{self, MakeSpan, TraceLayer},
 };
-use tracing::Level;
+use tracing::{Level, Span};
 
-pub fn create_layer() -> TraceLayer<SharedClassifier<ServerErrorsAsFailures>> {
-    let trace_layer = TraceLayer::new_for_http()
-        .make_span_with(trace::DefaultMakeSpan::new().level(Level::INFO))
-        .on_request(trace::DefaultOnRequest::new().level(Level::INFO))
-        .on_response(trace::DefaultOnResponse::new().level(Level::INFO));
+use super::request_id::REQUEST_ID_HEADER;
+
+/// 为每个请求创建带有请求ID的 span
+#[derive(Debug, Clone, Copy)]
+pub struct RequestSpan;
+
+impl<B> MakeSpan<B> for RequestSpan {
+    fn make_span(&mut self, request: &Request<B>) -> Span {
+        let request_id = request
+            .headers()
+            .get(REQUEST_ID_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .unwrap_or("-");
 
-    trace_layer
+        tracing::info_span!(
+            "request",
+            method = %request.method(),
+            uri = %request.uri(),
+            version = ?request.version(),
+            request_id = %request_id,
+        )
+    }
+}
+
+pub fn create_layer() -> TraceLayer<SharedClassifier<ServerErrorsAsFailures>, RequestSpan> {
+    TraceLayer::new_for_http()
+        .make_span_with(RequestSpan)
+        .on_request(trace::DefaultOnRequest::new().level(Level::INFO))
+        .on_response(trace::DefaultOnResponse::new().level(Level::INFO))
 }
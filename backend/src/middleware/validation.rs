@@ -0,0 +1,49 @@
+//! 请求校验模块
+//!
+//! 提供解析 JSON 请求体并执行声明式校验的提取器，
+//! 校验失败时返回 400，并在 `details` 中列出每个未通过校验的字段
+
+use axum::Json;
+use axum::extract::rejection::JsonRejection;
+use axum::extract::{FromRequest, Request};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+use crate::error::{AppError, AppErrorType};
+
+/// 经过校验的 JSON 请求体
+///
+/// ```ignore
+/// pub async fn create_course(ValidatedJson(req): ValidatedJson<CreateCourseRequest>) { ... }
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ValidatedJson<T>(pub T);
+
+impl<T, S> FromRequest<S> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    S: Send + Sync,
+{
+    type Rejection = AppError;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(json_rejection)?;
+
+        value.validate()?;
+
+        Ok(ValidatedJson(value))
+    }
+}
+
+/// 将请求体解析失败转换为 400 错误
+fn json_rejection(rejection: JsonRejection) -> AppError {
+    let msg = match rejection {
+        JsonRejection::MissingJsonContentType(_) => {
+            "请求头 Content-Type 必须为 application/json".to_string()
+        }
+        rejection => format!("请求体格式错误: {}", rejection.body_text()),
+    };
+    AppError::new_message(&msg, AppErrorType::BadRequest)
+}
@@ -0,0 +1,6 @@
+//! 日期格式模块
+//!
+//! 请求体和查询参数中的日期统一使用 `YYYY-MM-DD` 字符串格式，
+//! 通过 `#[serde(with = "date_format::date")]` 或 `date_format::date::option` 使用
+
+time::serde::format_description!(pub date, Date, "[year]-[month]-[day]");
@@ -0,0 +1,90 @@
+//! 数据库迁移模块
+//!
+//! 管理 `migrations/` 目录下按版本号排序、带校验和的迁移文件，
+//! 已应用的迁移记录在 `_sqlx_migrations` 表中
+
+use serde::Serialize;
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use sqlx::postgres::PgPool;
+use time::OffsetDateTime;
+use tracing::info;
+
+/// 编译期嵌入的迁移文件
+static MIGRATOR: Migrator = sqlx::migrate!("./migrations");
+
+/// 单个迁移的状态
+#[derive(Debug, Clone, Serialize)]
+pub struct MigrationStatus {
+    /// 迁移版本号
+    pub version: i64,
+    /// 迁移描述
+    pub description: String,
+    /// 是否已应用
+    pub applied: bool,
+    /// 应用时间
+    pub installed_on: Option<OffsetDateTime>,
+    /// 已应用迁移的校验和是否与当前文件一致
+    pub checksum_ok: bool,
+}
+
+/// 应用所有未执行的迁移
+pub async fn run_migrations(pool: &PgPool) -> Result<(), MigrateError> {
+    info!("执行数据库迁移...");
+    MIGRATOR.run(pool).await?;
+    info!(
+        "数据库迁移完成，当前版本: {:?}",
+        current_version(pool).await?
+    );
+    Ok(())
+}
+
+/// 回滚到指定版本（保留版本号小于等于 target 的迁移）
+///
+/// target 为 0 时回滚全部迁移
+pub async fn rollback(pool: &PgPool, target: i64) -> Result<(), MigrateError> {
+    info!("回滚数据库迁移至版本: {}", target);
+    MIGRATOR.undo(pool, target).await?;
+    info!("回滚完成，当前版本: {:?}", current_version(pool).await?);
+    Ok(())
+}
+
+/// 获取当前数据库结构版本（最近一次成功应用的迁移版本号）
+pub async fn current_version(pool: &PgPool) -> Result<Option<i64>, MigrateError> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied = conn.list_applied_migrations().await?;
+    Ok(applied.iter().map(|m| m.version).max())
+}
+
+/// 列出所有迁移及其应用状态
+pub async fn status(pool: &PgPool) -> Result<Vec<MigrationStatus>, MigrateError> {
+    let mut conn = pool.acquire().await?;
+    conn.ensure_migrations_table().await?;
+    let applied = conn.list_applied_migrations().await?;
+
+    // list_applied_migrations 不返回应用时间，单独查询
+    let installed: Vec<(i64, OffsetDateTime)> =
+        sqlx::query_as("SELECT version, installed_on FROM _sqlx_migrations WHERE success")
+            .fetch_all(&mut *conn)
+            .await?;
+
+    let statuses = MIGRATOR
+        .iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| {
+            let applied_migration = applied.iter().find(|a| a.version == m.version);
+            MigrationStatus {
+                version: m.version,
+                description: m.description.to_string(),
+                applied: applied_migration.is_some(),
+                installed_on: installed
+                    .iter()
+                    .find(|(version, _)| *version == m.version)
+                    .map(|(_, installed_on)| *installed_on),
+                checksum_ok: applied_migration.is_none_or(|a| a.checksum == m.checksum),
+            }
+        })
+        .collect();
+
+    Ok(statuses)
+}
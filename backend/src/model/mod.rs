@@ -2,9 +2,15 @@
 //!
 //! 这个模块包含所有数据库模型的定义和操作方法
 
+pub mod date_format;
 mod db;
 mod dbtools;
+pub mod migrate;
 pub mod models;
+pub mod pdf;
+pub mod query;
+pub mod sheet;
+pub mod validate;
 
 // 导出公共组件
-pub use db::get_db_pool;
+pub use db::{connect_db_pool, get_db_pool};
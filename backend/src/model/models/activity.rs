@@ -3,12 +3,17 @@
 //! 提供系统活动记录的数据结构和数据库操作方法
 
 use serde::{Deserialize, Serialize};
-use sqlx::{Error, postgres::PgPool};
+use serde_json::Value;
+use sqlx::{Error, PgExecutor, postgres::PgPool};
 use time::OffsetDateTime;
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::query::{Page, PageParams};
+use crate::model::validate;
 
 /// 活动类型枚举
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
 pub enum ActivityType {
     /// 添加学生
@@ -17,30 +22,236 @@ pub enum ActivityType {
     UpdateStudent,
     /// 删除学生
     DeleteStudent,
+    /// 批量导入学生
+    ImportStudents,
     /// 添加课程
     AddCourse,
     /// 更新课程
     UpdateCourse,
     /// 删除课程
     DeleteCourse,
+    /// 添加班级
+    AddClassGroup,
+    /// 更新班级
+    UpdateClassGroup,
+    /// 删除班级
+    DeleteClassGroup,
+    /// 学生选课
+    EnrollStudent,
+    /// 学生退课
+    DropEnrollment,
+    /// 添加课程记录
+    AddCourseRecord,
+    /// 更新课程记录
+    UpdateCourseRecord,
+    /// 删除课程记录
+    DeleteCourseRecord,
+    /// 添加试卷
+    AddExam,
+    /// 更新试卷
+    UpdateExam,
+    /// 删除试卷
+    DeleteExam,
+    /// 上传试卷文件
+    UploadExamFile,
+    /// 设置试卷题目
+    SetExamQuestions,
+    /// 添加试卷记录
+    AddExamRecord,
+    /// 更新试卷记录
+    UpdateExamRecord,
+    /// 删除试卷记录
+    DeleteExamRecord,
+    /// 添加题目
+    AddQuestion,
+    /// 更新题目
+    UpdateQuestion,
+    /// 删除题目
+    DeleteQuestion,
+    /// 提交作业
+    SubmitHomework,
+    /// 更新作业
+    UpdateHomework,
+    /// 删除作业
+    DeleteHomework,
+    /// 上传作业文件
+    UploadHomeworkFile,
+    /// 批改作业
+    GradeHomework,
+    /// 发还作业
+    ReturnHomework,
+    /// 添加作业任务
+    AddAssignment,
+    /// 更新作业任务
+    UpdateAssignment,
+    /// 删除作业任务
+    DeleteAssignment,
+    /// 上传作业任务附件
+    UploadAssignmentFile,
+    /// 布置作业
+    AssignHomework,
+    /// 设置迟交扣分规则
+    SetLatePolicy,
+    /// 删除迟交扣分规则
+    DeleteLatePolicy,
+    /// 设置评分等级制
+    SetGradingScale,
+    /// 删除评分等级制
+    DeleteGradingScale,
+    /// 添加成绩类别
+    AddGradeCategory,
+    /// 更新成绩类别
+    UpdateGradeCategory,
+    /// 删除成绩类别
+    DeleteGradeCategory,
+    /// 登记免做
+    AddGradeExcusal,
+    /// 撤销免做
+    DeleteGradeExcusal,
+    /// 调整总评
+    OverrideFinalGrade,
+    /// 锁定总评
+    LockFinalGrades,
+    /// 解锁总评
+    UnlockFinalGrades,
+    /// 发布公告
+    AddAnnouncement,
     /// 记录考勤
     RecordAttendance,
+    /// 删除考勤
+    DeleteAttendance,
     /// 上传成绩
     UploadGrade,
     /// 其他操作
     Other,
 }
 
+impl ActivityType {
+    /// 客户端可以通过接口记录的活动类型，其余类型只由服务端在操作成功后写入
+    pub const CLIENT_LOGGABLE: &'static [ActivityType] =
+        &[ActivityType::UploadGrade, ActivityType::Other];
+
+    /// 是否为客户端可以记录的活动类型
+    pub fn client_loggable(self) -> bool {
+        Self::CLIENT_LOGGABLE.contains(&self)
+    }
+
+    /// 活动类型的中文名称
+    pub fn label(self) -> &'static str {
+        match self {
+            ActivityType::AddStudent => "添加学生",
+            ActivityType::UpdateStudent => "更新学生",
+            ActivityType::DeleteStudent => "删除学生",
+            ActivityType::ImportStudents => "批量导入学生",
+            ActivityType::AddCourse => "添加课程",
+            ActivityType::UpdateCourse => "更新课程",
+            ActivityType::DeleteCourse => "删除课程",
+            ActivityType::AddClassGroup => "添加班级",
+            ActivityType::UpdateClassGroup => "更新班级",
+            ActivityType::DeleteClassGroup => "删除班级",
+            ActivityType::EnrollStudent => "学生选课",
+            ActivityType::DropEnrollment => "学生退课",
+            ActivityType::AddCourseRecord => "添加课程记录",
+            ActivityType::UpdateCourseRecord => "更新课程记录",
+            ActivityType::DeleteCourseRecord => "删除课程记录",
+            ActivityType::AddExam => "添加试卷",
+            ActivityType::UpdateExam => "更新试卷",
+            ActivityType::DeleteExam => "删除试卷",
+            ActivityType::UploadExamFile => "上传试卷文件",
+            ActivityType::SetExamQuestions => "设置试卷题目",
+            ActivityType::AddExamRecord => "添加试卷记录",
+            ActivityType::UpdateExamRecord => "更新试卷记录",
+            ActivityType::DeleteExamRecord => "删除试卷记录",
+            ActivityType::AddQuestion => "添加题目",
+            ActivityType::UpdateQuestion => "更新题目",
+            ActivityType::DeleteQuestion => "删除题目",
+            ActivityType::SubmitHomework => "提交作业",
+            ActivityType::UpdateHomework => "更新作业",
+            ActivityType::DeleteHomework => "删除作业",
+            ActivityType::UploadHomeworkFile => "上传作业文件",
+            ActivityType::GradeHomework => "批改作业",
+            ActivityType::ReturnHomework => "发还作业",
+            ActivityType::AddAssignment => "添加作业任务",
+            ActivityType::UpdateAssignment => "更新作业任务",
+            ActivityType::DeleteAssignment => "删除作业任务",
+            ActivityType::UploadAssignmentFile => "上传作业任务附件",
+            ActivityType::AssignHomework => "布置作业",
+            ActivityType::SetLatePolicy => "设置迟交扣分规则",
+            ActivityType::DeleteLatePolicy => "删除迟交扣分规则",
+            ActivityType::SetGradingScale => "设置评分等级制",
+            ActivityType::DeleteGradingScale => "删除评分等级制",
+            ActivityType::AddGradeCategory => "添加成绩类别",
+            ActivityType::UpdateGradeCategory => "更新成绩类别",
+            ActivityType::DeleteGradeCategory => "删除成绩类别",
+            ActivityType::AddGradeExcusal => "登记免做",
+            ActivityType::DeleteGradeExcusal => "撤销免做",
+            ActivityType::OverrideFinalGrade => "调整总评",
+            ActivityType::LockFinalGrades => "锁定总评",
+            ActivityType::UnlockFinalGrades => "解锁总评",
+            ActivityType::AddAnnouncement => "发布公告",
+            ActivityType::RecordAttendance => "记录考勤",
+            ActivityType::DeleteAttendance => "删除考勤",
+            ActivityType::UploadGrade => "上传成绩",
+            ActivityType::Other => "其他操作",
+        }
+    }
+}
+
 impl AsRef<str> for ActivityType {
     fn as_ref(&self) -> &str {
         match self {
             ActivityType::AddStudent => "add_student",
             ActivityType::UpdateStudent => "update_student",
             ActivityType::DeleteStudent => "delete_student",
+            ActivityType::ImportStudents => "import_students",
             ActivityType::AddCourse => "add_course",
             ActivityType::UpdateCourse => "update_course",
             ActivityType::DeleteCourse => "delete_course",
+            ActivityType::AddClassGroup => "add_class_group",
+            ActivityType::UpdateClassGroup => "update_class_group",
+            ActivityType::DeleteClassGroup => "delete_class_group",
+            ActivityType::EnrollStudent => "enroll_student",
+            ActivityType::DropEnrollment => "drop_enrollment",
+            ActivityType::AddCourseRecord => "add_course_record",
+            ActivityType::UpdateCourseRecord => "update_course_record",
+            ActivityType::DeleteCourseRecord => "delete_course_record",
+            ActivityType::AddExam => "add_exam",
+            ActivityType::UpdateExam => "update_exam",
+            ActivityType::DeleteExam => "delete_exam",
+            ActivityType::UploadExamFile => "upload_exam_file",
+            ActivityType::SetExamQuestions => "set_exam_questions",
+            ActivityType::AddExamRecord => "add_exam_record",
+            ActivityType::UpdateExamRecord => "update_exam_record",
+            ActivityType::DeleteExamRecord => "delete_exam_record",
+            ActivityType::AddQuestion => "add_question",
+            ActivityType::UpdateQuestion => "update_question",
+            ActivityType::DeleteQuestion => "delete_question",
+            ActivityType::SubmitHomework => "submit_homework",
+            ActivityType::UpdateHomework => "update_homework",
+            ActivityType::DeleteHomework => "delete_homework",
+            ActivityType::UploadHomeworkFile => "upload_homework_file",
+            ActivityType::GradeHomework => "grade_homework",
+            ActivityType::ReturnHomework => "return_homework",
+            ActivityType::AddAssignment => "add_assignment",
+            ActivityType::UpdateAssignment => "update_assignment",
+            ActivityType::DeleteAssignment => "delete_assignment",
+            ActivityType::UploadAssignmentFile => "upload_assignment_file",
+            ActivityType::AssignHomework => "assign_homework",
+            ActivityType::SetLatePolicy => "set_late_policy",
+            ActivityType::DeleteLatePolicy => "delete_late_policy",
+            ActivityType::SetGradingScale => "set_grading_scale",
+            ActivityType::DeleteGradingScale => "delete_grading_scale",
+            ActivityType::AddGradeCategory => "add_grade_category",
+            ActivityType::UpdateGradeCategory => "update_grade_category",
+            ActivityType::DeleteGradeCategory => "delete_grade_category",
+            ActivityType::AddGradeExcusal => "add_grade_excusal",
+            ActivityType::DeleteGradeExcusal => "delete_grade_excusal",
+            ActivityType::OverrideFinalGrade => "override_final_grade",
+            ActivityType::LockFinalGrades => "lock_final_grades",
+            ActivityType::UnlockFinalGrades => "unlock_final_grades",
+            ActivityType::AddAnnouncement => "add_announcement",
             ActivityType::RecordAttendance => "record_attendance",
+            ActivityType::DeleteAttendance => "delete_attendance",
             ActivityType::UploadGrade => "upload_grade",
             ActivityType::Other => "other",
         }
@@ -53,10 +264,55 @@ impl From<String> for ActivityType {
             "add_student" => ActivityType::AddStudent,
             "update_student" => ActivityType::UpdateStudent,
             "delete_student" => ActivityType::DeleteStudent,
+            "import_students" => ActivityType::ImportStudents,
             "add_course" => ActivityType::AddCourse,
             "update_course" => ActivityType::UpdateCourse,
             "delete_course" => ActivityType::DeleteCourse,
+            "add_class_group" => ActivityType::AddClassGroup,
+            "update_class_group" => ActivityType::UpdateClassGroup,
+            "delete_class_group" => ActivityType::DeleteClassGroup,
+            "enroll_student" => ActivityType::EnrollStudent,
+            "drop_enrollment" => ActivityType::DropEnrollment,
+            "add_course_record" => ActivityType::AddCourseRecord,
+            "update_course_record" => ActivityType::UpdateCourseRecord,
+            "delete_course_record" => ActivityType::DeleteCourseRecord,
+            "add_exam" => ActivityType::AddExam,
+            "update_exam" => ActivityType::UpdateExam,
+            "delete_exam" => ActivityType::DeleteExam,
+            "upload_exam_file" => ActivityType::UploadExamFile,
+            "set_exam_questions" => ActivityType::SetExamQuestions,
+            "add_exam_record" => ActivityType::AddExamRecord,
+            "update_exam_record" => ActivityType::UpdateExamRecord,
+            "delete_exam_record" => ActivityType::DeleteExamRecord,
+            "add_question" => ActivityType::AddQuestion,
+            "update_question" => ActivityType::UpdateQuestion,
+            "delete_question" => ActivityType::DeleteQuestion,
+            "submit_homework" => ActivityType::SubmitHomework,
+            "update_homework" => ActivityType::UpdateHomework,
+            "delete_homework" => ActivityType::DeleteHomework,
+            "upload_homework_file" => ActivityType::UploadHomeworkFile,
+            "grade_homework" => ActivityType::GradeHomework,
+            "return_homework" => ActivityType::ReturnHomework,
+            "add_assignment" => ActivityType::AddAssignment,
+            "update_assignment" => ActivityType::UpdateAssignment,
+            "delete_assignment" => ActivityType::DeleteAssignment,
+            "upload_assignment_file" => ActivityType::UploadAssignmentFile,
+            "assign_homework" => ActivityType::AssignHomework,
+            "set_late_policy" => ActivityType::SetLatePolicy,
+            "delete_late_policy" => ActivityType::DeleteLatePolicy,
+            "set_grading_scale" => ActivityType::SetGradingScale,
+            "delete_grading_scale" => ActivityType::DeleteGradingScale,
+            "add_grade_category" => ActivityType::AddGradeCategory,
+            "update_grade_category" => ActivityType::UpdateGradeCategory,
+            "delete_grade_category" => ActivityType::DeleteGradeCategory,
+            "add_grade_excusal" => ActivityType::AddGradeExcusal,
+            "delete_grade_excusal" => ActivityType::DeleteGradeExcusal,
+            "override_final_grade" => ActivityType::OverrideFinalGrade,
+            "lock_final_grades" => ActivityType::LockFinalGrades,
+            "unlock_final_grades" => ActivityType::UnlockFinalGrades,
+            "add_announcement" => ActivityType::AddAnnouncement,
             "record_attendance" => ActivityType::RecordAttendance,
+            "delete_attendance" => ActivityType::DeleteAttendance,
             "upload_grade" => ActivityType::UploadGrade,
             _ => ActivityType::Other,
         }
@@ -80,11 +336,13 @@ pub struct Activity {
     pub user_role: String,
     /// 相关资源ID（如学生ID、课程ID等）
     pub resource_id: Option<Uuid>,
+    /// 资源变更前后的差异
+    pub changes: Option<Value>,
     /// 创建时间
     pub created_at: OffsetDateTime,
 }
 
-/// 创建活动记录的请求数据结构
+/// 创建活动记录的数据结构
 #[derive(Debug, Deserialize)]
 pub struct CreateActivityRequest {
     /// 活动类型
@@ -99,19 +357,51 @@ pub struct CreateActivityRequest {
     pub user_role: String,
     /// 相关资源ID（如学生ID、课程ID等）
     pub resource_id: Option<Uuid>,
+    /// 资源变更前后的差异
+    pub changes: Option<Value>,
+}
+
+/// 记录活动的请求数据结构，操作用户取自当前登录用户
+#[derive(Debug, Deserialize, Validate)]
+pub struct LogActivityRequest {
+    /// 活动类型，只能为 [`ActivityType::CLIENT_LOGGABLE`] 中的类型
+    pub activity_type: ActivityType,
+    /// 活动描述
+    #[validate(custom(function = "validate::not_blank"))]
+    pub description: String,
+    /// 相关资源ID（如学生ID、课程ID等）
+    pub resource_id: Option<Uuid>,
 }
 
+/// 用户快照中保留的字段
+///
+/// 密码哈希、邮箱、家长信息、地址和备注等个人信息不写入活动记录
+const USER_SNAPSHOT_FIELDS: &[&str] = &[
+    "id",
+    "username",
+    "display_name",
+    "role",
+    "grade",
+    "created_at",
+    "updated_at",
+];
+
 impl Activity {
     /// 创建新活动记录
-    pub async fn create(pool: &PgPool, req: CreateActivityRequest) -> Result<Self, Error> {
+    ///
+    /// 可传入连接池或事务，以便与业务数据在同一事务中写入
+    pub async fn create<'e, E: PgExecutor<'e>>(
+        executor: E,
+        req: CreateActivityRequest,
+    ) -> Result<Self, Error> {
         let id = Uuid::new_v4();
         let now = OffsetDateTime::now_utc();
 
         let activity = sqlx::query_as!(Self,
             r#"
-            INSERT INTO activities (id, activity_type, description, user_id, user_name, user_role, resource_id, created_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
-            RETURNING id, activity_type, description, user_id, user_name, user_role, resource_id, created_at
+            INSERT INTO activities (id, activity_type, description, user_id, user_name, user_role, resource_id, changes, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            RETURNING id, activity_type, description, user_id, user_name, user_role, resource_id, changes, created_at
             "#,
             id,
             req.activity_type,
@@ -120,74 +410,111 @@ impl Activity {
             req.user_name,
             req.user_role,
             req.resource_id,
+            req.changes,
             now
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(activity)
     }
 
-    /// 获取所有活动记录，按创建时间倒序排列，并限制返回数量
-    pub async fn find_all(pool: &PgPool, limit: i64) -> Result<Vec<Self>, Error> {
-        let activities = sqlx::query_as!(Self,
-            r#"
-            SELECT id, activity_type, description, user_id, user_name, user_role, resource_id, created_at
-            FROM activities
-            ORDER BY created_at DESC
-            LIMIT $1
-            "#,
-            limit
-        )
-        .fetch_all(pool)
-        .await?;
+    /// 读取资源当前的数据快照，用于记录变更前后的差异
+    ///
+    /// `table` 只能传入代码中固定的表名。用户的快照只保留 [`USER_SNAPSHOT_FIELDS`] 中的字段
+    pub async fn snapshot(
+        pool: &PgPool,
+        table: &'static str,
+        id: Uuid,
+    ) -> Result<Option<Value>, Error> {
+        let sql = format!("SELECT to_jsonb(t) FROM {table} t WHERE id = $1");
+        let mut snapshot = sqlx::query_scalar::<_, Value>(&sql)
+            .bind(id)
+            .fetch_optional(pool)
+            .await?;
 
-        Ok(activities)
+        if let (Some(Value::Object(fields)), "users") = (&mut snapshot, table) {
+            fields.retain(|field, _| USER_SNAPSHOT_FIELDS.contains(&field.as_str()));
+        }
+        Ok(snapshot)
     }
 
-    /// 根据用户ID获取活动记录
-    pub async fn find_by_user_id(
+    /// 分页获取活动记录，按创建时间倒序排列，可按用户ID和活动类型筛选
+    pub async fn query(
         pool: &PgPool,
-        user_id: Uuid,
-        limit: i64,
-    ) -> Result<Vec<Self>, Error> {
-        let activities = sqlx::query_as!(Self,
+        user_id: Option<Uuid>,
+        activity_type: Option<&str>,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(
             r#"
-            SELECT id, activity_type, description, user_id, user_name, user_role, resource_id, created_at
+            SELECT COUNT(*) AS "count!"
             FROM activities
-            WHERE user_id = $1
-            ORDER BY created_at DESC
-            LIMIT $2
+            WHERE ($1::uuid IS NULL OR user_id = $1)
+              AND ($2::text IS NULL OR activity_type = $2)
             "#,
             user_id,
-            limit
+            activity_type
         )
-        .fetch_all(pool)
+        .fetch_one(pool)
         .await?;
 
-        Ok(activities)
-    }
-
-    /// 根据活动类型获取活动记录
-    pub async fn find_by_activity_type(
-        pool: &PgPool,
-        activity_type: &str,
-        limit: i64,
-    ) -> Result<Vec<Self>, Error> {
         let activities = sqlx::query_as!(Self,
             r#"
-            SELECT id, activity_type, description, user_id, user_name, user_role, resource_id, created_at
+            SELECT id, activity_type, description, user_id, user_name, user_role, resource_id, changes, created_at
             FROM activities
-            WHERE activity_type = $1
-            ORDER BY created_at DESC
-            LIMIT $2
+            WHERE ($1::uuid IS NULL OR user_id = $1)
+              AND ($2::text IS NULL OR activity_type = $2)
+            ORDER BY created_at DESC, id DESC
+            LIMIT $3 OFFSET $4
             "#,
+            user_id,
             activity_type,
-            limit
+            page.limit(),
+            page.offset()
         )
         .fetch_all(pool)
         .await?;
 
-        Ok(activities)
+        Ok(Page::new(activities, total, page))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TestApp;
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    #[sqlx::test]
+    async fn student_activity_omits_personal_details(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+        let admin = app.user("admin").await;
+        let teacher = app.user("teacher").await;
+        let student = app.user("student").await;
+        let course_id = app.course(&teacher).await;
+        app.enroll(course_id, &student).await;
+
+        let (status, _) = app
+            .send(
+                Method::PUT,
+                &format!("/students/{}", student.id),
+                &teacher,
+                Some(json!({ "display_name": "张三", "parent_phone": "13800000000" })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let uri = "/activities?activity_type=update_student";
+        let (status, _) = app.send(Method::GET, uri, &teacher, None).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+
+        let (status, page) = app.send(Method::GET, uri, &admin, None).await;
+        assert_eq!(status, StatusCode::OK);
+        let changes = &page["items"][0]["changes"];
+        assert_eq!(changes["after"]["display_name"], "张三");
+        assert!(changes["after"].get("parent_phone").is_none());
+        assert!(changes["before"].get("parent_phone").is_none());
     }
 }
@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 use sqlx::{Error, postgres::PgPool};
 use time::OffsetDateTime;
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::query::{Page, PageParams};
+use crate::model::validate;
 
 /// 公告结构体
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,17 +39,24 @@ pub struct Announcement {
 }
 
 /// 创建公告的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateAnnouncementRequest {
     /// 公告标题
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 200, message = "公告标题不能超过200个字符")
+    )]
     pub title: String,
     /// 公告内容
+    #[validate(custom(function = "validate::not_blank"))]
     pub content: String,
     /// 发布者ID
     pub publisher_id: Uuid,
     /// 发布者名称
+    #[validate(length(max = 100, message = "发布者名称不能超过100个字符"))]
     pub publisher_name: String,
     /// 发布者角色
+    #[validate(length(max = 20, message = "发布者角色不能超过20个字符"))]
     pub publisher_role: String,
     /// 是否重要
     pub is_important: bool,
@@ -54,11 +65,16 @@ pub struct CreateAnnouncementRequest {
 }
 
 /// 更新公告的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateAnnouncementRequest {
     /// 公告标题
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 200, message = "公告标题不能超过200个字符")
+    )]
     pub title: Option<String>,
     /// 公告内容
+    #[validate(custom(function = "validate::not_blank"))]
     pub content: Option<String>,
     /// 是否重要
     pub is_important: Option<bool>,
@@ -96,25 +112,37 @@ impl Announcement {
         Ok(announcement)
     }
 
-    /// 获取所有有效的公告，按发布时间倒序排列
-    pub async fn find_all(pool: &PgPool, limit: i64) -> Result<Vec<Self>, Error> {
+    /// 分页获取所有有效的公告，重要公告在前，按发布时间倒序排列
+    pub async fn find_all(pool: &PgPool, page: &PageParams) -> Result<Page<Self>, Error> {
         let now = OffsetDateTime::now_utc();
 
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM announcements
+            WHERE expired_at IS NULL OR expired_at > $1
+            "#,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
         let announcements = sqlx::query_as!(Self,
             r#"
             SELECT id, title, content, publisher_id, publisher_name, publisher_role, is_important, published_at, expired_at, created_at, updated_at
             FROM announcements
             WHERE expired_at IS NULL OR expired_at > $1
-            ORDER BY is_important DESC, published_at DESC
-            LIMIT $2
+            ORDER BY is_important DESC, published_at DESC, id DESC
+            LIMIT $2 OFFSET $3
             "#,
             now,
-            limit
+            page.limit(),
+            page.offset()
         )
         .fetch_all(pool)
         .await?;
 
-        Ok(announcements)
+        Ok(Page::new(announcements, total, page))
     }
 
     /// 根据ID获取公告
@@ -0,0 +1,320 @@
+//! 作业任务模型
+//!
+//! 作业任务是教师布置给一个课程的作业，包括要求、截止日期、满分和附件，
+//! 每个学生的提交记录在 [`super::submission`] 中
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::file::StoredFile;
+use crate::model::date_format;
+use crate::model::query::{Page, PageParams};
+use crate::model::validate;
+
+/// 作业任务结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Assignment {
+    /// 作业任务ID
+    pub id: Uuid,
+    /// 课程ID，由原作业迁移或通过兼容接口创建的为空
+    pub course_id: Option<Uuid>,
+    /// 作业标题
+    pub title: String,
+    /// 作业要求
+    pub instructions: Option<String>,
+    /// 截止日期
+    #[serde(with = "date_format::date::option")]
+    pub due_date: Option<Date>,
+    /// 满分
+    pub max_score: Option<Decimal>,
+    /// 布置作业的教师ID
+    pub teacher_id: Option<Uuid>,
+    /// 成绩类别ID，为空时不计入总评
+    pub category_id: Option<Uuid>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 作业任务及其附件
+#[derive(Debug, Clone, Serialize)]
+pub struct AssignmentWithAttachments {
+    /// 作业任务
+    pub assignment: Assignment,
+    /// 附件列表
+    pub attachments: Vec<StoredFile>,
+}
+
+/// 创建作业任务的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateAssignmentRequest {
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 作业标题
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 200, message = "作业标题不能超过200个字符")
+    )]
+    pub title: String,
+    /// 作业要求
+    pub instructions: Option<String>,
+    /// 截止日期
+    #[serde(default, with = "date_format::date::option")]
+    pub due_date: Option<Date>,
+    /// 满分
+    #[validate(custom(function = "validate::positive_score"))]
+    pub max_score: Option<Decimal>,
+    /// 布置作业的教师ID，默认为当前用户
+    pub teacher_id: Option<Uuid>,
+    /// 成绩类别ID，须属于同一课程
+    pub category_id: Option<Uuid>,
+}
+
+/// 更新作业任务的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateAssignmentRequest {
+    /// 作业标题
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 200, message = "作业标题不能超过200个字符")
+    )]
+    pub title: Option<String>,
+    /// 作业要求
+    pub instructions: Option<String>,
+    /// 截止日期
+    #[serde(default, with = "date_format::date::option")]
+    pub due_date: Option<Date>,
+    /// 满分
+    #[validate(custom(function = "validate::positive_score"))]
+    pub max_score: Option<Decimal>,
+    /// 布置作业的教师ID
+    pub teacher_id: Option<Uuid>,
+    /// 成绩类别ID，须属于同一课程
+    pub category_id: Option<Uuid>,
+}
+
+/// 查询作业任务的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize)]
+pub struct AssignmentFilter {
+    /// 课程ID
+    pub course_id: Option<Uuid>,
+    /// 教师ID
+    pub teacher_id: Option<Uuid>,
+}
+
+impl Assignment {
+    /// 创建作业任务
+    pub async fn create<'e, E: PgExecutor<'e>>(
+        executor: E,
+        req: CreateAssignmentRequest,
+        teacher_id: Option<Uuid>,
+    ) -> Result<Self, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let assignment = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO assignments (id, course_id, title, instructions, due_date, max_score, teacher_id, category_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING id, course_id, title, instructions, due_date, max_score, teacher_id, category_id,
+                      created_at, updated_at
+            "#,
+            id,
+            req.course_id,
+            req.title,
+            req.instructions,
+            req.due_date,
+            req.max_score,
+            teacher_id,
+            req.category_id,
+            now,
+            now
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(assignment)
+    }
+
+    /// 根据ID查找作业任务
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let assignment = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, course_id, title, instructions, due_date, max_score, teacher_id, category_id,
+                   created_at, updated_at
+            FROM assignments
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(assignment)
+    }
+
+    /// 分页查询作业任务，按截止日期倒序排列，没有截止日期的排在最后
+    pub async fn query(
+        pool: &PgPool,
+        filter: &AssignmentFilter,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM assignments
+            WHERE ($1::uuid IS NULL OR course_id = $1)
+              AND ($2::uuid IS NULL OR teacher_id = $2)
+            "#,
+            filter.course_id,
+            filter.teacher_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        let assignments = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, course_id, title, instructions, due_date, max_score, teacher_id, category_id,
+                   created_at, updated_at
+            FROM assignments
+            WHERE ($1::uuid IS NULL OR course_id = $1)
+              AND ($2::uuid IS NULL OR teacher_id = $2)
+            ORDER BY due_date DESC NULLS LAST, created_at DESC, id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            filter.course_id,
+            filter.teacher_id,
+            page.limit(),
+            page.offset()
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Page::new(assignments, total, page))
+    }
+
+    /// 更新作业任务
+    pub async fn update(
+        pool: &PgPool,
+        id: Uuid,
+        req: UpdateAssignmentRequest,
+    ) -> Result<Self, Error> {
+        let assignment = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(Error::RowNotFound)?;
+
+        let title = req.title.unwrap_or(assignment.title);
+        let instructions = req.instructions.or(assignment.instructions);
+        let due_date = req.due_date.or(assignment.due_date);
+        let max_score = req.max_score.or(assignment.max_score);
+        let teacher_id = req.teacher_id.or(assignment.teacher_id);
+        let category_id = req.category_id.or(assignment.category_id);
+        let now = OffsetDateTime::now_utc();
+
+        let assignment = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE assignments
+            SET title = $1, instructions = $2, due_date = $3, max_score = $4, teacher_id = $5,
+                category_id = $6, updated_at = $7
+            WHERE id = $8
+            RETURNING id, course_id, title, instructions, due_date, max_score, teacher_id, category_id,
+                      created_at, updated_at
+            "#,
+            title,
+            instructions,
+            due_date,
+            max_score,
+            teacher_id,
+            category_id,
+            now,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(assignment)
+    }
+
+    /// 作业任务是否共享
+    ///
+    /// 属于课程或有多份提交的作业任务为共享的作业任务；通过兼容接口创建、只有一份提交的不是
+    pub async fn is_shared(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+        let shared = sqlx::query_scalar!(
+            r#"
+            SELECT a.course_id IS NOT NULL
+                   OR (SELECT COUNT(*) FROM submissions s WHERE s.assignment_id = a.id) > 1 AS "shared!"
+            FROM assignments a
+            WHERE a.id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(shared.unwrap_or(false))
+    }
+
+    /// 删除作业任务，其提交记录一并删除
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+        let result = sqlx::query!("DELETE FROM assignments WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 获取作业任务的附件，按上传顺序排列
+    pub async fn attachments(pool: &PgPool, id: Uuid) -> Result<Vec<StoredFile>, Error> {
+        let files = sqlx::query_as!(
+            StoredFile,
+            r#"
+            SELECT f.id, f.storage_key, f.original_name, f.content_type, f.size, f.sha256,
+                   f.uploaded_by, f.created_at
+            FROM assignment_attachments aa
+            JOIN files f ON f.id = aa.file_id
+            WHERE aa.assignment_id = $1
+            ORDER BY aa.created_at, f.id
+            "#,
+            id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(files)
+    }
+
+    /// 添加附件
+    pub async fn add_attachment(pool: &PgPool, id: Uuid, file_id: Uuid) -> Result<(), Error> {
+        sqlx::query!(
+            "INSERT INTO assignment_attachments (assignment_id, file_id) VALUES ($1, $2)",
+            id,
+            file_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 移除附件，不删除文件本身
+    pub async fn remove_attachment(pool: &PgPool, id: Uuid, file_id: Uuid) -> Result<bool, Error> {
+        let result = sqlx::query!(
+            "DELETE FROM assignment_attachments WHERE assignment_id = $1 AND file_id = $2",
+            id,
+            file_id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
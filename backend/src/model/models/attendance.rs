@@ -0,0 +1,397 @@
+//! 考勤模型
+//!
+//! 提供考勤记录的数据结构和数据库操作方法
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::date_format;
+use crate::model::query::{Page, PageParams};
+
+/// 出勤状态枚举
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum AttendanceStatus {
+    /// 出勤
+    Present,
+    /// 缺勤
+    Absent,
+    /// 迟到
+    Late,
+    /// 请假
+    Excused,
+}
+
+impl AsRef<str> for AttendanceStatus {
+    fn as_ref(&self) -> &str {
+        match self {
+            AttendanceStatus::Present => "present",
+            AttendanceStatus::Absent => "absent",
+            AttendanceStatus::Late => "late",
+            AttendanceStatus::Excused => "excused",
+        }
+    }
+}
+
+/// 考勤记录结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Attendance {
+    /// 记录ID
+    pub id: Uuid,
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 上课日期（课次）
+    pub class_date: Date,
+    /// 出勤状态
+    pub status: String,
+    /// 原因
+    pub reason: Option<String>,
+    /// 记录人ID
+    pub recorded_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 记录单条考勤的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct RecordAttendanceRequest {
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 上课日期
+    #[serde(with = "date_format::date")]
+    pub class_date: Date,
+    /// 出勤状态
+    pub status: AttendanceStatus,
+    /// 原因
+    pub reason: Option<String>,
+}
+
+/// 点名记录中单个学生的考勤
+#[derive(Debug, Serialize, Deserialize, Validate)]
+pub struct RollCallEntry {
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 出勤状态
+    pub status: AttendanceStatus,
+    /// 原因
+    pub reason: Option<String>,
+}
+
+/// 整节课批量点名的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct RollCallRequest {
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 上课日期
+    #[serde(with = "date_format::date")]
+    pub class_date: Date,
+    /// 每个学生的考勤
+    #[validate(length(min = 1, message = "点名记录不能为空"), nested)]
+    pub records: Vec<RollCallEntry>,
+}
+
+/// 更新考勤的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateAttendanceRequest {
+    /// 出勤状态
+    pub status: Option<AttendanceStatus>,
+    /// 原因，填写空字符串时清除
+    pub reason: Option<String>,
+}
+
+/// 出勤率统计
+#[derive(Debug, Clone, Serialize)]
+pub struct AttendanceRate {
+    /// 考勤记录总数
+    pub total: i64,
+    /// 出勤次数
+    pub present: i64,
+    /// 缺勤次数
+    pub absent: i64,
+    /// 迟到次数
+    pub late: i64,
+    /// 请假次数
+    pub excused: i64,
+    /// 出勤率：(出勤 + 迟到) / (总数 - 请假)，没有可统计的课次时为空
+    pub rate: Option<f64>,
+}
+
+impl AttendanceRate {
+    fn from_counts(total: i64, present: i64, absent: i64, late: i64, excused: i64) -> Self {
+        let countable = total - excused;
+        let rate = if countable > 0 {
+            Some((present + late) as f64 / countable as f64)
+        } else {
+            None
+        };
+
+        Self {
+            total,
+            present,
+            absent,
+            late,
+            excused,
+            rate,
+        }
+    }
+}
+
+impl Attendance {
+    /// 记录考勤
+    ///
+    /// 同一学生同一课程同一天只保留一条记录，重复记录时覆盖原有状态
+    pub async fn upsert<'e, E: PgExecutor<'e>>(
+        executor: E,
+        student_id: Uuid,
+        course_id: Uuid,
+        class_date: Date,
+        status: AttendanceStatus,
+        reason: Option<&str>,
+        recorded_by: Option<Uuid>,
+    ) -> Result<Self, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let record = sqlx::query_as!(Self,
+            r#"
+            INSERT INTO attendance (id, student_id, course_id, class_date, status, reason, recorded_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (student_id, course_id, class_date)
+            DO UPDATE SET status = EXCLUDED.status, reason = EXCLUDED.reason,
+                          recorded_by = EXCLUDED.recorded_by, updated_at = EXCLUDED.updated_at
+            RETURNING id, student_id, course_id, class_date, status, reason, recorded_by, created_at, updated_at
+            "#,
+            id,
+            student_id,
+            course_id,
+            class_date,
+            status.as_ref(),
+            reason,
+            recorded_by,
+            now,
+            now
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// 根据ID查找考勤记录
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let record = sqlx::query_as!(Self,
+            r#"
+            SELECT id, student_id, course_id, class_date, status, reason, recorded_by, created_at, updated_at
+            FROM attendance
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// 获取某节课（课程 + 日期）的全部考勤记录
+    pub async fn find_by_session(
+        pool: &PgPool,
+        course_id: Uuid,
+        class_date: Date,
+    ) -> Result<Vec<Self>, Error> {
+        let records = sqlx::query_as!(Self,
+            r#"
+            SELECT id, student_id, course_id, class_date, status, reason, recorded_by, created_at, updated_at
+            FROM attendance
+            WHERE course_id = $1 AND class_date = $2
+            ORDER BY created_at ASC
+            "#,
+            course_id,
+            class_date
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// 分页获取学生的考勤记录，可按日期范围筛选
+    pub async fn find_by_student_id(
+        pool: &PgPool,
+        student_id: Uuid,
+        start_date: Option<Date>,
+        end_date: Option<Date>,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM attendance
+            WHERE student_id = $1
+              AND ($2::date IS NULL OR class_date >= $2)
+              AND ($3::date IS NULL OR class_date <= $3)
+            "#,
+            student_id,
+            start_date,
+            end_date
+        )
+        .fetch_one(pool)
+        .await?;
+
+        let records = sqlx::query_as!(Self,
+            r#"
+            SELECT id, student_id, course_id, class_date, status, reason, recorded_by, created_at, updated_at
+            FROM attendance
+            WHERE student_id = $1
+              AND ($2::date IS NULL OR class_date >= $2)
+              AND ($3::date IS NULL OR class_date <= $3)
+            ORDER BY class_date DESC, id DESC
+            LIMIT $4 OFFSET $5
+            "#,
+            student_id,
+            start_date,
+            end_date,
+            page.limit(),
+            page.offset()
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Page::new(records, total, page))
+    }
+
+    /// 更新考勤记录
+    pub async fn update<'e, E: PgExecutor<'e>>(
+        executor: E,
+        current: Self,
+        req: UpdateAttendanceRequest,
+        recorded_by: Option<Uuid>,
+    ) -> Result<Self, Error> {
+        let status = req
+            .status
+            .map(|s| s.as_ref().to_string())
+            .unwrap_or(current.status);
+        let reason = match req.reason {
+            Some(reason) if reason.trim().is_empty() => None,
+            Some(reason) => Some(reason),
+            None => current.reason,
+        };
+        let now = OffsetDateTime::now_utc();
+
+        let record = sqlx::query_as!(Self,
+            r#"
+            UPDATE attendance
+            SET status = $1, reason = $2, recorded_by = $3, updated_at = $4
+            WHERE id = $5
+            RETURNING id, student_id, course_id, class_date, status, reason, recorded_by, created_at, updated_at
+            "#,
+            status,
+            reason,
+            recorded_by,
+            now,
+            current.id
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// 删除考勤记录，返回被删除的记录
+    pub async fn delete<'e, E: PgExecutor<'e>>(
+        executor: E,
+        id: Uuid,
+    ) -> Result<Option<Self>, Error> {
+        let record = sqlx::query_as!(Self,
+            r#"
+            DELETE FROM attendance
+            WHERE id = $1
+            RETURNING id, student_id, course_id, class_date, status, reason, recorded_by, created_at, updated_at
+            "#,
+            id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// 统计学生的出勤率
+    pub async fn student_rate(
+        pool: &PgPool,
+        student_id: Uuid,
+        start_date: Option<Date>,
+        end_date: Option<Date>,
+    ) -> Result<AttendanceRate, Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS "total!",
+                   COUNT(*) FILTER (WHERE status = 'present') AS "present!",
+                   COUNT(*) FILTER (WHERE status = 'absent') AS "absent!",
+                   COUNT(*) FILTER (WHERE status = 'late') AS "late!",
+                   COUNT(*) FILTER (WHERE status = 'excused') AS "excused!"
+            FROM attendance
+            WHERE student_id = $1
+              AND ($2::date IS NULL OR class_date >= $2)
+              AND ($3::date IS NULL OR class_date <= $3)
+            "#,
+            student_id,
+            start_date,
+            end_date
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(AttendanceRate::from_counts(
+            row.total,
+            row.present,
+            row.absent,
+            row.late,
+            row.excused,
+        ))
+    }
+
+    /// 统计课程的出勤率
+    pub async fn course_rate(
+        pool: &PgPool,
+        course_id: Uuid,
+        start_date: Option<Date>,
+        end_date: Option<Date>,
+    ) -> Result<AttendanceRate, Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS "total!",
+                   COUNT(*) FILTER (WHERE status = 'present') AS "present!",
+                   COUNT(*) FILTER (WHERE status = 'absent') AS "absent!",
+                   COUNT(*) FILTER (WHERE status = 'late') AS "late!",
+                   COUNT(*) FILTER (WHERE status = 'excused') AS "excused!"
+            FROM attendance
+            WHERE course_id = $1
+              AND ($2::date IS NULL OR class_date >= $2)
+              AND ($3::date IS NULL OR class_date <= $3)
+            "#,
+            course_id,
+            start_date,
+            end_date
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(AttendanceRate::from_counts(
+            row.total,
+            row.present,
+            row.absent,
+            row.late,
+            row.excused,
+        ))
+    }
+}
@@ -0,0 +1,244 @@
+//! 班级模型
+//!
+//! 班级是课程在某个学期的一个教学班，由一名教师负责，可以限定人数
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::query::{Page, PageParams};
+use crate::model::validate;
+
+/// 班级结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClassGroup {
+    /// 班级ID
+    pub id: Uuid,
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 班级名称
+    pub name: String,
+    /// 学期
+    pub term: String,
+    /// 负责教师ID
+    pub teacher_id: Option<Uuid>,
+    /// 人数上限，为空时不限
+    pub capacity: Option<i32>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 创建班级的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateClassGroupRequest {
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 班级名称
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 100, message = "班级名称不能超过100个字符")
+    )]
+    pub name: String,
+    /// 学期
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 50, message = "学期不能超过50个字符")
+    )]
+    pub term: String,
+    /// 负责教师ID
+    pub teacher_id: Option<Uuid>,
+    /// 人数上限
+    #[validate(range(min = 1, message = "人数上限必须大于0"))]
+    pub capacity: Option<i32>,
+}
+
+/// 更新班级的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateClassGroupRequest {
+    /// 班级名称
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 100, message = "班级名称不能超过100个字符")
+    )]
+    pub name: Option<String>,
+    /// 学期
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 50, message = "学期不能超过50个字符")
+    )]
+    pub term: Option<String>,
+    /// 负责教师ID
+    pub teacher_id: Option<Uuid>,
+    /// 人数上限
+    #[validate(range(min = 1, message = "人数上限必须大于0"))]
+    pub capacity: Option<i32>,
+}
+
+/// 查询班级的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize)]
+pub struct ClassGroupFilter {
+    /// 课程ID
+    pub course_id: Option<Uuid>,
+    /// 学期
+    pub term: Option<String>,
+    /// 负责教师ID
+    pub teacher_id: Option<Uuid>,
+}
+
+impl ClassGroup {
+    /// 创建班级
+    pub async fn create(pool: &PgPool, req: CreateClassGroupRequest) -> Result<Self, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let group = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO class_groups (id, course_id, name, term, teacher_id, capacity, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, course_id, name, term, teacher_id, capacity, created_at, updated_at
+            "#,
+            id,
+            req.course_id,
+            req.name,
+            req.term,
+            req.teacher_id,
+            req.capacity,
+            now,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    /// 根据ID查找班级
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let group = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, course_id, name, term, teacher_id, capacity, created_at, updated_at
+            FROM class_groups
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    /// 根据ID查找班级并锁定，用于在事务中检查人数上限
+    pub async fn lock<'e, E: PgExecutor<'e>>(executor: E, id: Uuid) -> Result<Option<Self>, Error> {
+        let group = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, course_id, name, term, teacher_id, capacity, created_at, updated_at
+            FROM class_groups
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            id
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(group)
+    }
+
+    /// 分页查询班级，按学期倒序、名称排列
+    pub async fn query(
+        pool: &PgPool,
+        filter: &ClassGroupFilter,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM class_groups
+            WHERE ($1::uuid IS NULL OR course_id = $1)
+              AND ($2::text IS NULL OR term = $2)
+              AND ($3::uuid IS NULL OR teacher_id = $3)
+            "#,
+            filter.course_id,
+            filter.term,
+            filter.teacher_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        let groups = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, course_id, name, term, teacher_id, capacity, created_at, updated_at
+            FROM class_groups
+            WHERE ($1::uuid IS NULL OR course_id = $1)
+              AND ($2::text IS NULL OR term = $2)
+              AND ($3::uuid IS NULL OR teacher_id = $3)
+            ORDER BY term DESC, name ASC, id ASC
+            LIMIT $4 OFFSET $5
+            "#,
+            filter.course_id,
+            filter.term,
+            filter.teacher_id,
+            page.limit(),
+            page.offset()
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Page::new(groups, total, page))
+    }
+
+    /// 更新班级
+    pub async fn update(
+        pool: &PgPool,
+        id: Uuid,
+        req: UpdateClassGroupRequest,
+    ) -> Result<Self, Error> {
+        let group = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(Error::RowNotFound)?;
+
+        let name = req.name.unwrap_or(group.name);
+        let term = req.term.unwrap_or(group.term);
+        let teacher_id = req.teacher_id.or(group.teacher_id);
+        let capacity = req.capacity.or(group.capacity);
+        let now = OffsetDateTime::now_utc();
+
+        let group = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE class_groups
+            SET name = $1, term = $2, teacher_id = $3, capacity = $4, updated_at = $5
+            WHERE id = $6
+            RETURNING id, course_id, name, term, teacher_id, capacity, created_at, updated_at
+            "#,
+            name,
+            term,
+            teacher_id,
+            capacity,
+            now,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(group)
+    }
+
+    /// 删除班级，班级中的选课记录保留，但不再关联班级
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+        let result = sqlx::query!("DELETE FROM class_groups WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -6,6 +6,10 @@ use serde::{Deserialize, Serialize};
 use sqlx::{Error, postgres::PgPool};
 use time::OffsetDateTime;
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::query::{Page, PageParams};
+use crate::model::validate;
 
 /// 课程结构体
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,9 +29,13 @@ pub struct Course {
 }
 
 /// 创建课程的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateCourseRequest {
     /// 课程名称
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 100, message = "课程名称不能超过100个字符")
+    )]
     pub name: String,
     /// 课程描述
     pub description: Option<String>,
@@ -36,9 +44,13 @@ pub struct CreateCourseRequest {
 }
 
 /// 更新课程的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateCourseRequest {
     /// 课程名称
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 100, message = "课程名称不能超过100个字符")
+    )]
     pub name: Option<String>,
     /// 课程描述
     pub description: Option<String>,
@@ -106,38 +118,59 @@ impl Course {
         Ok(course)
     }
 
-    /// 根据关键词查找课程
-    pub async fn find_by_keyword(pool: &PgPool, keyword: &str) -> Result<Vec<Self>, Error> {
+    /// 根据关键词分页查找课程
+    pub async fn find_by_keyword(
+        pool: &PgPool,
+        keyword: &str,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM courses WHERE $1 = ANY(keywords)"#,
+            keyword
+        )
+        .fetch_one(pool)
+        .await?;
+
         let courses = sqlx::query_as!(
             Self,
             r#"
             SELECT id, name, description, keywords, created_at, updated_at
             FROM courses
             WHERE $1 = ANY(keywords)
-            ORDER BY name ASC
+            ORDER BY name ASC, id ASC
+            LIMIT $2 OFFSET $3
             "#,
-            keyword
+            keyword,
+            page.limit(),
+            page.offset()
         )
         .fetch_all(pool)
         .await?;
 
-        Ok(courses)
+        Ok(Page::new(courses, total, page))
     }
 
-    /// 获取所有课程
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<Self>, Error> {
+    /// 分页获取所有课程
+    pub async fn find_all(pool: &PgPool, page: &PageParams) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM courses"#)
+            .fetch_one(pool)
+            .await?;
+
         let courses = sqlx::query_as!(
             Self,
             r#"
             SELECT id, name, description, keywords, created_at, updated_at
             FROM courses
-            ORDER BY name ASC
-            "#
+            ORDER BY name ASC, id ASC
+            LIMIT $1 OFFSET $2
+            "#,
+            page.limit(),
+            page.offset()
         )
         .fetch_all(pool)
         .await?;
 
-        Ok(courses)
+        Ok(Page::new(courses, total, page))
     }
 
     /// 更新课程
@@ -3,12 +3,17 @@
 //! 提供课程记录的数据结构和数据库操作方法
 
 use serde::{Deserialize, Serialize};
-use sqlx::{Error, postgres::PgPool};
+use sqlx::{Error, FromRow, Postgres, QueryBuilder, postgres::PgPool};
 use time::{Date, OffsetDateTime};
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::date_format;
+use crate::model::query::{self, Page, PageParams, SortField, SortOrder};
+use crate::model::validate;
 
 /// 课程记录结构体
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct CourseRecord {
     /// 记录ID
     pub id: Uuid,
@@ -31,7 +36,7 @@ pub struct CourseRecord {
 }
 
 /// 创建课程记录的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateCourseRecordRequest {
     /// 学生ID
     pub student_id: Uuid,
@@ -41,6 +46,7 @@ pub struct CreateCourseRecordRequest {
     #[serde(deserialize_with = "deserialize_date")]
     pub class_date: Date,
     /// 上课内容
+    #[validate(custom(function = "validate::not_blank"))]
     pub content: String,
     /// 上课表现
     pub performance: Option<String>,
@@ -58,7 +64,7 @@ where
 }
 
 /// 更新课程记录的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateCourseRecordRequest {
     /// 学生ID
     pub student_id: Option<Uuid>,
@@ -67,6 +73,7 @@ pub struct UpdateCourseRecordRequest {
     /// 上课日期
     pub class_date: Option<Date>,
     /// 上课内容
+    #[validate(custom(function = "validate::not_blank"))]
     pub content: Option<String>,
     /// 上课表现
     pub performance: Option<String>,
@@ -74,6 +81,51 @@ pub struct UpdateCourseRecordRequest {
     pub teacher_id: Option<Uuid>,
 }
 
+/// 课程记录的可排序字段
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CourseRecordSort {
+    /// 上课日期
+    #[default]
+    ClassDate,
+    /// 创建时间
+    CreatedAt,
+}
+
+impl SortField for CourseRecordSort {
+    fn column(self) -> &'static str {
+        match self {
+            CourseRecordSort::ClassDate => "class_date",
+            CourseRecordSort::CreatedAt => "created_at",
+        }
+    }
+}
+
+/// 查询课程记录的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize)]
+pub struct CourseRecordFilter {
+    /// 学生ID
+    pub student_id: Option<Uuid>,
+    /// 课程ID
+    pub course_id: Option<Uuid>,
+    /// 教师ID
+    pub teacher_id: Option<Uuid>,
+    /// 上课日期起始（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    /// 上课日期截止（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+    /// 课程关键词，匹配课程名称或描述
+    pub keyword: Option<String>,
+    /// 排序字段，默认为上课日期
+    #[serde(default)]
+    pub sort_by: CourseRecordSort,
+    /// 排序方向，默认为降序
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
 impl CourseRecord {
     /// 创建新课程记录
     pub async fn create(pool: &PgPool, req: CreateCourseRecordRequest) -> Result<Self, Error> {
@@ -135,55 +187,6 @@ impl CourseRecord {
         Ok(records)
     }
 
-    /// 根据课程ID查找课程记录
-    pub async fn find_by_course_id(pool: &PgPool, course_id: Uuid) -> Result<Vec<Self>, Error> {
-        let records = sqlx::query_as!(Self,
-            r#"
-            SELECT id, student_id, course_id, class_date, content, performance, teacher_id, created_at, updated_at
-            FROM course_records
-            WHERE course_id = $1
-            ORDER BY class_date DESC
-            "#,
-            course_id
-        )
-        .fetch_all(pool)
-        .await?;
-
-        Ok(records)
-    }
-
-    /// 根据教师ID查找课程记录
-    pub async fn find_by_teacher_id(pool: &PgPool, teacher_id: Uuid) -> Result<Vec<Self>, Error> {
-        let records = sqlx::query_as!(Self,
-            r#"
-            SELECT id, student_id, course_id, class_date, content, performance, teacher_id, created_at, updated_at
-            FROM course_records
-            WHERE teacher_id = $1
-            ORDER BY class_date DESC
-            "#,
-            teacher_id
-        )
-        .fetch_all(pool)
-        .await?;
-
-        Ok(records)
-    }
-
-    /// 获取所有课程记录
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<Self>, Error> {
-        let records = sqlx::query_as!(Self,
-            r#"
-            SELECT id, student_id, course_id, class_date, content, performance, teacher_id, created_at, updated_at
-            FROM course_records
-            ORDER BY class_date DESC
-            "#
-        )
-        .fetch_all(pool)
-        .await?;
-
-        Ok(records)
-    }
-
     /// 更新课程记录
     pub async fn update(
         pool: &PgPool,
@@ -235,100 +238,69 @@ impl CourseRecord {
         Ok(result.rows_affected() > 0)
     }
 
-    /// 根据日期范围查找课程记录
-    pub async fn find_by_date_range(
+    /// 按筛选条件分页查询课程记录
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn query(
         pool: &PgPool,
-        start_date: Option<Date>,
-        end_date: Option<Date>,
-    ) -> Result<Vec<Self>, Error> {
-        let records = match (start_date, end_date) {
-            (Some(start), Some(end)) => {
-                sqlx::query_as!(
-                    Self,
-                    r#"
-                    SELECT id, student_id, course_id, class_date, content, performance, teacher_id, created_at, updated_at
-                    FROM course_records
-                    WHERE class_date >= $1 AND class_date <= $2
-                    ORDER BY class_date DESC
-                    "#,
-                    start,
-                    end
-                )
-                .fetch_all(pool)
-                .await?
-            },
-            (Some(start), None) => {
-                sqlx::query_as!(
-                    Self,
-                    r#"
-                    SELECT id, student_id, course_id, class_date, content, performance, teacher_id, created_at, updated_at
-                    FROM course_records
-                    WHERE class_date >= $1
-                    ORDER BY class_date DESC
-                    "#,
-                    start
-                )
-                .fetch_all(pool)
-                .await?
-            },
-            (None, Some(end)) => {
-                sqlx::query_as!(
-                    Self,
-                    r#"
-                    SELECT id, student_id, course_id, class_date, content, performance, teacher_id, created_at, updated_at
-                    FROM course_records
-                    WHERE class_date <= $1
-                    ORDER BY class_date DESC
-                    "#,
-                    end
-                )
-                .fetch_all(pool)
-                .await?
-            },
-            (None, None) => {
-                return Self::find_all(pool).await;
-            }
-        };
-
-        Ok(records)
-    }
-
-    /// 根据课程关键词查找课程记录
-    pub async fn find_by_course_keyword(pool: &PgPool, keyword: &str) -> Result<Vec<Self>, Error> {
-        // 先查询与关键词匹配的课程
-        let courses = sqlx::query!(
-            r#"
-            SELECT id
-            FROM courses
-            WHERE name ILIKE $1 OR description ILIKE $1
-            "#,
-            format!("%{}%", keyword)
-        )
-        .fetch_all(pool)
-        .await?;
-
-        // 如果没有找到匹配的课程，返回空列表
-        if courses.is_empty() {
-            return Ok(Vec::new());
-        }
+        filter: &CourseRecordFilter,
+        students: Option<&[Uuid]>,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM course_records WHERE TRUE");
+        Self::push_filter(&mut builder, filter, students);
+        let total: i64 = builder.build_query_scalar().fetch_one(pool).await?;
 
-        // 提取课程ID
-        let course_ids: Vec<Uuid> = courses.into_iter().map(|c| c.id).collect();
-
-        // 查询这些课程的记录
-        let records = sqlx::query_as!(
-            Self,
+        let mut builder = QueryBuilder::new(
             r#"
             SELECT id, student_id, course_id, class_date, content, performance, teacher_id, created_at, updated_at
             FROM course_records
-            WHERE course_id = ANY($1)
-            ORDER BY class_date DESC
+            WHERE TRUE
             "#,
-            &course_ids
-        )
-        .fetch_all(pool)
-        .await?;
+        );
+        Self::push_filter(&mut builder, filter, students);
+        query::push_order_by(&mut builder, filter.sort_by, filter.order);
+        query::push_page(&mut builder, page);
+        let records = builder.build_query_as::<Self>().fetch_all(pool).await?;
 
-        Ok(records)
+        Ok(Page::new(records, total, page))
+    }
+
+    /// 追加筛选条件
+    fn push_filter(
+        builder: &mut QueryBuilder<'_, Postgres>,
+        filter: &CourseRecordFilter,
+        students: Option<&[Uuid]>,
+    ) {
+        if let Some(students) = students {
+            builder
+                .push(" AND student_id = ANY(")
+                .push_bind(students.to_vec())
+                .push(")");
+        }
+        if let Some(student_id) = filter.student_id {
+            builder.push(" AND student_id = ").push_bind(student_id);
+        }
+        if let Some(course_id) = filter.course_id {
+            builder.push(" AND course_id = ").push_bind(course_id);
+        }
+        if let Some(teacher_id) = filter.teacher_id {
+            builder.push(" AND teacher_id = ").push_bind(teacher_id);
+        }
+        if let Some(start_date) = filter.start_date {
+            builder.push(" AND class_date >= ").push_bind(start_date);
+        }
+        if let Some(end_date) = filter.end_date {
+            builder.push(" AND class_date <= ").push_bind(end_date);
+        }
+        if let Some(keyword) = &filter.keyword {
+            let pattern = format!("%{}%", keyword);
+            builder
+                .push(" AND course_id IN (SELECT id FROM courses WHERE name ILIKE ")
+                .push_bind(pattern.clone())
+                .push(" OR description ILIKE ")
+                .push_bind(pattern)
+                .push(")");
+        }
     }
 }
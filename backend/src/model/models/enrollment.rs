@@ -0,0 +1,282 @@
+//! 选课模型
+//!
+//! 记录学生加入课程及其班级的日期和退课日期，提供课程名单和学生课表的查询
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::date_format;
+
+/// 选课记录结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Enrollment {
+    /// 选课ID
+    pub id: Uuid,
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 班级ID
+    pub class_group_id: Option<Uuid>,
+    /// 选课日期
+    #[serde(with = "date_format::date")]
+    pub enrolled_on: Date,
+    /// 退课日期，为空表示在读
+    #[serde(with = "date_format::date::option")]
+    pub dropped_on: Option<Date>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 选课的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateEnrollmentRequest {
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 班级ID，必须属于该课程
+    pub class_group_id: Option<Uuid>,
+    /// 选课日期，默认为当天
+    #[serde(default, with = "date_format::date::option")]
+    pub enrolled_on: Option<Date>,
+}
+
+/// 退课的请求数据结构
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct DropEnrollmentRequest {
+    /// 退课日期，默认为当天
+    #[serde(default, with = "date_format::date::option")]
+    pub dropped_on: Option<Date>,
+}
+
+/// 查询名单或课表的筛选条件
+#[derive(Debug, Default, Deserialize)]
+pub struct EnrollmentFilter {
+    /// 班级ID
+    pub class_group_id: Option<Uuid>,
+    /// 是否包含已退课的记录，默认只返回在读的
+    #[serde(default)]
+    pub include_dropped: bool,
+}
+
+/// 课程名单中的学生
+#[derive(Debug, Clone, Serialize)]
+pub struct RosterEntry {
+    /// 选课ID
+    pub enrollment_id: Uuid,
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 用户名
+    pub username: String,
+    /// 显示名称
+    pub display_name: Option<String>,
+    /// 年级
+    pub grade: Option<i32>,
+    /// 班级ID
+    pub class_group_id: Option<Uuid>,
+    /// 班级名称
+    pub class_group_name: Option<String>,
+    /// 选课日期
+    #[serde(with = "date_format::date")]
+    pub enrolled_on: Date,
+    /// 退课日期
+    #[serde(with = "date_format::date::option")]
+    pub dropped_on: Option<Date>,
+}
+
+/// 学生课表中的课程
+#[derive(Debug, Clone, Serialize)]
+pub struct EnrolledCourse {
+    /// 选课ID
+    pub enrollment_id: Uuid,
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 课程名称
+    pub course_name: String,
+    /// 班级ID
+    pub class_group_id: Option<Uuid>,
+    /// 班级名称
+    pub class_group_name: Option<String>,
+    /// 学期
+    pub term: Option<String>,
+    /// 负责教师ID
+    pub teacher_id: Option<Uuid>,
+    /// 选课日期
+    #[serde(with = "date_format::date")]
+    pub enrolled_on: Date,
+    /// 退课日期
+    #[serde(with = "date_format::date::option")]
+    pub dropped_on: Option<Date>,
+}
+
+impl Enrollment {
+    /// 创建选课记录
+    pub async fn create<'e, E: PgExecutor<'e>>(
+        executor: E,
+        req: &CreateEnrollmentRequest,
+        enrolled_on: Date,
+    ) -> Result<Self, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let enrollment = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO enrollments (id, student_id, course_id, class_group_id, enrolled_on, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, student_id, course_id, class_group_id, enrolled_on, dropped_on, created_at, updated_at
+            "#,
+            id,
+            req.student_id,
+            req.course_id,
+            req.class_group_id,
+            enrolled_on,
+            now,
+            now
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(enrollment)
+    }
+
+    /// 根据ID查找选课记录
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let enrollment = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, student_id, course_id, class_group_id, enrolled_on, dropped_on, created_at, updated_at
+            FROM enrollments
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(enrollment)
+    }
+
+    /// 统计班级的在读人数
+    pub async fn count_active<'e, E: PgExecutor<'e>>(
+        executor: E,
+        class_group_id: Uuid,
+    ) -> Result<i64, Error> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM enrollments
+            WHERE class_group_id = $1 AND dropped_on IS NULL
+            "#,
+            class_group_id
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// 获取课程中在读的学生ID
+    pub async fn active_student_ids(pool: &PgPool, course_id: Uuid) -> Result<Vec<Uuid>, Error> {
+        let ids = sqlx::query_scalar!(
+            "SELECT student_id FROM enrollments WHERE course_id = $1 AND dropped_on IS NULL",
+            course_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// 退课，已退课的记录返回未找到
+    pub async fn withdraw(pool: &PgPool, id: Uuid, dropped_on: Date) -> Result<Self, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let enrollment = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE enrollments
+            SET dropped_on = $1, updated_at = $2
+            WHERE id = $3 AND dropped_on IS NULL
+            RETURNING id, student_id, course_id, class_group_id, enrolled_on, dropped_on, created_at, updated_at
+            "#,
+            dropped_on,
+            now,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        enrollment.ok_or(Error::RowNotFound)
+    }
+
+    /// 获取课程名单，按班级和用户名排列
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn roster(
+        pool: &PgPool,
+        course_id: Uuid,
+        filter: &EnrollmentFilter,
+        students: Option<&[Uuid]>,
+    ) -> Result<Vec<RosterEntry>, Error> {
+        let entries = sqlx::query_as!(
+            RosterEntry,
+            r#"
+            SELECT e.id AS enrollment_id, e.student_id, u.username, u.display_name, u.grade,
+                   e.class_group_id, g.name AS "class_group_name?", e.enrolled_on, e.dropped_on
+            FROM enrollments e
+            JOIN users u ON u.id = e.student_id
+            LEFT JOIN class_groups g ON g.id = e.class_group_id
+            WHERE e.course_id = $1
+              AND ($2::uuid IS NULL OR e.class_group_id = $2)
+              AND ($3 OR e.dropped_on IS NULL)
+              AND ($4::uuid[] IS NULL OR e.student_id = ANY($4))
+            ORDER BY g.name NULLS LAST, u.username, e.enrolled_on
+            "#,
+            course_id,
+            filter.class_group_id,
+            filter.include_dropped,
+            students
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    /// 获取学生的课表，按选课日期倒序排列
+    pub async fn courses_of_student(
+        pool: &PgPool,
+        student_id: Uuid,
+        filter: &EnrollmentFilter,
+    ) -> Result<Vec<EnrolledCourse>, Error> {
+        let courses = sqlx::query_as!(
+            EnrolledCourse,
+            r#"
+            SELECT e.id AS enrollment_id, e.course_id, c.name AS course_name,
+                   e.class_group_id, g.name AS "class_group_name?", g.term AS "term?",
+                   g.teacher_id AS "teacher_id?", e.enrolled_on, e.dropped_on
+            FROM enrollments e
+            JOIN courses c ON c.id = e.course_id
+            LEFT JOIN class_groups g ON g.id = e.class_group_id
+            WHERE e.student_id = $1
+              AND ($2::uuid IS NULL OR e.class_group_id = $2)
+              AND ($3 OR e.dropped_on IS NULL)
+            ORDER BY e.enrolled_on DESC, c.name
+            "#,
+            student_id,
+            filter.class_group_id,
+            filter.include_dropped
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(courses)
+    }
+}
@@ -2,10 +2,15 @@
 //!
 //! 提供试卷的数据结构和数据库操作方法
 
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::{Error, postgres::PgPool};
 use time::OffsetDateTime;
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::query::{Page, PageParams};
+use crate::model::validate;
 
 /// 试卷结构体
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +25,10 @@ pub struct Exam {
     pub keywords: Option<Vec<String>>,
     /// 试卷文件路径
     pub file_path: Option<String>,
+    /// 满分，为空时按100分计
+    pub max_score: Option<Decimal>,
+    /// 成绩类别ID，为空时不计入总评
+    pub category_id: Option<Uuid>,
     /// 创建时间
     pub created_at: OffsetDateTime,
     /// 更新时间
@@ -27,29 +36,43 @@ pub struct Exam {
 }
 
 /// 创建试卷的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateExamRequest {
     /// 试卷标题
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 200, message = "试卷标题不能超过200个字符")
+    )]
     pub title: String,
     /// 试卷描述
     pub description: Option<String>,
     /// 试卷关键词
     pub keywords: Option<Vec<String>>,
-    /// 试卷文件路径
-    pub file_path: Option<String>,
+    /// 满分
+    #[validate(custom(function = "validate::positive_score"))]
+    pub max_score: Option<Decimal>,
+    /// 成绩类别ID
+    pub category_id: Option<Uuid>,
 }
 
 /// 更新试卷的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateExamRequest {
     /// 试卷标题
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 200, message = "试卷标题不能超过200个字符")
+    )]
     pub title: Option<String>,
     /// 试卷描述
     pub description: Option<String>,
     /// 试卷关键词
     pub keywords: Option<Vec<String>>,
-    /// 试卷文件路径
-    pub file_path: Option<String>,
+    /// 满分
+    #[validate(custom(function = "validate::positive_score"))]
+    pub max_score: Option<Decimal>,
+    /// 成绩类别ID
+    pub category_id: Option<Uuid>,
 }
 
 impl Exam {
@@ -61,15 +84,16 @@ impl Exam {
         let exam = sqlx::query_as!(
             Self,
             r#"
-            INSERT INTO exams (id, title, description, keywords, file_path, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7)
-            RETURNING id, title, description, keywords, file_path, created_at, updated_at
+            INSERT INTO exams (id, title, description, keywords, max_score, category_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, title, description, keywords, file_path, max_score, category_id, created_at, updated_at
             "#,
             id,
             req.title,
             req.description,
             req.keywords.as_ref().map(|k| k.as_slice()),
-            req.file_path,
+            req.max_score,
+            req.category_id,
             now,
             now
         )
@@ -84,7 +108,7 @@ impl Exam {
         let exam = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, title, description, keywords, file_path, created_at, updated_at
+            SELECT id, title, description, keywords, file_path, max_score, category_id, created_at, updated_at
             FROM exams
             WHERE id = $1
             "#,
@@ -101,7 +125,7 @@ impl Exam {
         let exam = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, title, description, keywords, file_path, created_at, updated_at
+            SELECT id, title, description, keywords, file_path, max_score, category_id, created_at, updated_at
             FROM exams
             WHERE title = $1
             "#,
@@ -113,22 +137,36 @@ impl Exam {
         Ok(exam)
     }
 
-    /// 根据关键词查找试卷
-    pub async fn find_by_keyword(pool: &PgPool, keyword: &str) -> Result<Vec<Self>, Error> {
+    /// 根据关键词分页查找试卷
+    pub async fn find_by_keyword(
+        pool: &PgPool,
+        keyword: &str,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) AS "count!" FROM exams WHERE $1 = ANY(keywords)"#,
+            keyword
+        )
+        .fetch_one(pool)
+        .await?;
+
         let exams = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, title, description, keywords, file_path, created_at, updated_at
+            SELECT id, title, description, keywords, file_path, max_score, category_id, created_at, updated_at
             FROM exams
             WHERE $1 = ANY(keywords)
-            ORDER BY title ASC
+            ORDER BY title ASC, id ASC
+            LIMIT $2 OFFSET $3
             "#,
-            keyword
+            keyword,
+            page.limit(),
+            page.offset()
         )
         .fetch_all(pool)
         .await?;
 
-        Ok(exams)
+        Ok(Page::new(exams, total, page))
     }
 
     /// 获取所有试卷
@@ -136,7 +174,7 @@ impl Exam {
         let exams = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, title, description, keywords, file_path, created_at, updated_at
+            SELECT id, title, description, keywords, file_path, max_score, category_id, created_at, updated_at
             FROM exams
             ORDER BY title ASC
             "#
@@ -155,21 +193,24 @@ impl Exam {
             let title = req.title.unwrap_or(exam.title);
             let description = req.description.or(exam.description);
             let keywords = req.keywords.or(exam.keywords);
-            let file_path = req.file_path.or(exam.file_path);
+            let max_score = req.max_score.or(exam.max_score);
+            let category_id = req.category_id.or(exam.category_id);
             let now = OffsetDateTime::now_utc();
 
             let updated_exam = sqlx::query_as!(
                 Self,
                 r#"
                 UPDATE exams
-                SET title = $1, description = $2, keywords = $3, file_path = $4, updated_at = $5
-                WHERE id = $6
-                RETURNING id, title, description, keywords, file_path, created_at, updated_at
+                SET title = $1, description = $2, keywords = $3, max_score = $4,
+                    category_id = $5, updated_at = $6
+                WHERE id = $7
+                RETURNING id, title, description, keywords, file_path, max_score, category_id, created_at, updated_at
                 "#,
                 title,
                 description,
                 keywords.as_ref().map(|k| k.as_slice()),
-                file_path,
+                max_score,
+                category_id,
                 now,
                 id
             )
@@ -182,6 +223,32 @@ impl Exam {
         }
     }
 
+    /// 更新试卷文件路径
+    pub async fn update_file_path(
+        pool: &PgPool,
+        id: Uuid,
+        file_path: Option<&str>,
+    ) -> Result<Self, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let exam = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE exams
+            SET file_path = $1, updated_at = $2
+            WHERE id = $3
+            RETURNING id, title, description, keywords, file_path, max_score, category_id, created_at, updated_at
+            "#,
+            file_path,
+            now,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(exam)
+    }
+
     /// 删除试卷
     pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
         let result = sqlx::query!("DELETE FROM exams WHERE id = $1", id)
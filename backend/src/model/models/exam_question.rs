@@ -0,0 +1,413 @@
+//! 试卷题目模型
+//!
+//! 提供试卷组卷、试卷记录的小题得分，以及按题目和知识点统计得分率的数据库操作方法
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgConnection, PgExecutor, postgres::PgPool};
+use std::collections::HashMap;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::date_format;
+use crate::model::validate;
+
+/// 试卷中的题目
+#[derive(Debug, Clone, Serialize)]
+pub struct ExamQuestion {
+    /// 试卷ID
+    pub exam_id: Uuid,
+    /// 题目ID
+    pub question_id: Uuid,
+    /// 题号，从1开始
+    pub position: i32,
+    /// 在试卷中的分值
+    pub points: Decimal,
+    /// 题型
+    pub question_type: String,
+    /// 题目内容
+    pub content: String,
+    /// 选择题的选项
+    pub options: Option<Vec<String>>,
+    /// 参考答案，没有 `exam:write` 权限的用户不返回
+    pub answer_key: Option<String>,
+    /// 知识点
+    pub knowledge_points: Vec<String>,
+}
+
+/// 组卷的题目
+#[derive(Debug, Deserialize, Validate)]
+pub struct ExamQuestionItem {
+    /// 题目ID
+    pub question_id: Uuid,
+    /// 在试卷中的分值，省略时使用题目的默认分值
+    #[validate(custom(function = "validate::positive_score"))]
+    pub points: Option<Decimal>,
+}
+
+/// 设置试卷题目的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetExamQuestionsRequest {
+    /// 题目，按题号顺序排列
+    #[validate(nested)]
+    pub questions: Vec<ExamQuestionItem>,
+}
+
+/// 试卷记录的小题得分
+#[derive(Debug, Clone, Serialize)]
+pub struct QuestionScore {
+    /// 题目ID
+    pub question_id: Uuid,
+    /// 题号
+    pub position: i32,
+    /// 在试卷中的分值
+    pub points: Decimal,
+    /// 知识点
+    pub knowledge_points: Vec<String>,
+    /// 得分，尚未登记时为空
+    pub score: Option<Decimal>,
+}
+
+/// 登记的小题得分
+#[derive(Debug, Deserialize, Validate)]
+pub struct QuestionScoreItem {
+    /// 题目ID
+    pub question_id: Uuid,
+    /// 得分
+    #[validate(custom(function = "validate::score"))]
+    pub score: Decimal,
+}
+
+/// 登记小题得分的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct RecordQuestionScoresRequest {
+    /// 小题得分
+    #[validate(nested)]
+    pub scores: Vec<QuestionScoreItem>,
+}
+
+/// 题目得分统计的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize)]
+pub struct QuestionStatsFilter {
+    /// 学生年级
+    pub grade: Option<i32>,
+    /// 班级ID，只统计班级中在读的学生
+    pub class_group_id: Option<Uuid>,
+    /// 学生ID
+    pub student_id: Option<Uuid>,
+}
+
+/// 题目得分统计
+#[derive(Debug, Clone, Serialize)]
+pub struct QuestionStat {
+    /// 题目ID
+    pub question_id: Uuid,
+    /// 题号
+    pub position: i32,
+    /// 题型
+    pub question_type: String,
+    /// 题目内容
+    pub content: String,
+    /// 在试卷中的分值
+    pub points: Decimal,
+    /// 知识点
+    pub knowledge_points: Vec<String>,
+    /// 登记了得分的记录数
+    pub count: i64,
+    /// 平均得分，保留两位小数
+    pub mean: Option<Decimal>,
+    /// 得分率，为平均得分与分值之比，保留四位小数
+    pub score_rate: Option<Decimal>,
+}
+
+/// 知识点得分统计的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize)]
+pub struct KnowledgePointFilter {
+    /// 学生年级
+    pub grade: Option<i32>,
+    /// 班级ID，只统计班级中在读的学生
+    pub class_group_id: Option<Uuid>,
+    /// 学生ID
+    pub student_id: Option<Uuid>,
+    /// 试卷ID
+    pub exam_id: Option<Uuid>,
+    /// 完成日期起始（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    /// 完成日期截止（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+}
+
+/// 知识点得分统计
+#[derive(Debug, Clone, Serialize)]
+pub struct KnowledgePointStat {
+    /// 知识点
+    pub knowledge_point: String,
+    /// 涉及的题目数
+    pub question_count: i64,
+    /// 登记的小题得分数
+    pub answer_count: i64,
+    /// 得分合计
+    pub earned: Decimal,
+    /// 分值合计
+    pub possible: Decimal,
+    /// 得分率，为得分合计与分值合计之比，保留四位小数
+    pub score_rate: Decimal,
+}
+
+impl ExamQuestion {
+    /// 获取试卷的题目，按题号排列
+    pub async fn find_by_exam<'e, E: PgExecutor<'e>>(
+        executor: E,
+        exam_id: Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let questions = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT eq.exam_id, eq.question_id, eq.position, eq.points, q.question_type, q.content,
+                   q.options, q.answer_key, q.knowledge_points
+            FROM exam_questions eq
+            JOIN questions q ON q.id = eq.question_id
+            WHERE eq.exam_id = $1
+            ORDER BY eq.position
+            "#,
+            exam_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(questions)
+    }
+
+    /// 替换试卷的题目，题号按顺序从1开始，试卷的满分更新为各题分值之和
+    ///
+    /// 需要在事务中调用，以便题目和满分一起写入
+    pub async fn replace(
+        conn: &mut PgConnection,
+        exam_id: Uuid,
+        question_ids: &[Uuid],
+        points: &[Decimal],
+    ) -> Result<Vec<Self>, Error> {
+        sqlx::query!("DELETE FROM exam_questions WHERE exam_id = $1", exam_id)
+            .execute(&mut *conn)
+            .await?;
+        sqlx::query!(
+            r#"
+            INSERT INTO exam_questions (exam_id, question_id, position, points)
+            SELECT $1, t.question_id, t.position::int, t.points
+            FROM UNNEST($2::uuid[], $3::decimal[]) WITH ORDINALITY AS t(question_id, points, position)
+            "#,
+            exam_id,
+            question_ids,
+            points
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        if !question_ids.is_empty() {
+            let now = OffsetDateTime::now_utc();
+            sqlx::query!(
+                r#"
+                UPDATE exams
+                SET max_score = (SELECT SUM(points) FROM exam_questions WHERE exam_id = $1), updated_at = $2
+                WHERE id = $1
+                "#,
+                exam_id,
+                now
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+
+        Self::find_by_exam(&mut *conn, exam_id).await
+    }
+
+    /// 获取试卷中已登记小题得分的题目及其最高得分
+    pub async fn max_scores<'e, E: PgExecutor<'e>>(
+        executor: E,
+        exam_id: Uuid,
+    ) -> Result<HashMap<Uuid, Decimal>, Error> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT s.question_id, MAX(s.score) AS "max_score!"
+            FROM exam_question_scores s
+            JOIN exam_records er ON er.id = s.exam_record_id
+            WHERE er.exam_id = $1
+            GROUP BY s.question_id
+            "#,
+            exam_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.question_id, row.max_score))
+            .collect())
+    }
+
+    /// 统计试卷各题目的得分率，按得分率从低到高排列，没有得分的题目排在最后
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn stats(
+        pool: &PgPool,
+        exam_id: Uuid,
+        filter: &QuestionStatsFilter,
+        students: Option<&[Uuid]>,
+    ) -> Result<Vec<QuestionStat>, Error> {
+        let stats = sqlx::query_as!(
+            QuestionStat,
+            r#"
+            WITH scores AS (
+                SELECT s.question_id, s.score
+                FROM exam_question_scores s
+                JOIN exam_records er ON er.id = s.exam_record_id
+                JOIN users u ON u.id = er.student_id
+                WHERE er.exam_id = $1
+                  AND ($2::int IS NULL OR u.grade = $2)
+                  AND ($3::uuid IS NULL OR er.student_id IN (
+                      SELECT student_id FROM enrollments WHERE class_group_id = $3 AND dropped_on IS NULL))
+                  AND ($4::uuid IS NULL OR er.student_id = $4)
+                  AND ($5::uuid[] IS NULL OR er.student_id = ANY($5))
+            )
+            SELECT eq.question_id, eq.position, q.question_type, q.content, eq.points, q.knowledge_points,
+                   COUNT(s.score) AS "count!",
+                   ROUND(AVG(s.score), 2) AS mean,
+                   ROUND(AVG(s.score) / eq.points, 4) AS score_rate
+            FROM exam_questions eq
+            JOIN questions q ON q.id = eq.question_id
+            LEFT JOIN scores s ON s.question_id = eq.question_id
+            WHERE eq.exam_id = $1
+            GROUP BY eq.question_id, eq.position, eq.points, q.question_type, q.content, q.knowledge_points
+            ORDER BY score_rate ASC NULLS LAST, eq.position
+            "#,
+            exam_id,
+            filter.grade,
+            filter.class_group_id,
+            filter.student_id,
+            students
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(stats)
+    }
+}
+
+impl QuestionScore {
+    /// 获取试卷记录的小题得分，按题号列出试卷的所有题目
+    pub async fn find_by_record<'e, E: PgExecutor<'e>>(
+        executor: E,
+        exam_record_id: Uuid,
+        exam_id: Uuid,
+    ) -> Result<Vec<Self>, Error> {
+        let scores = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT eq.question_id, eq.position, eq.points, q.knowledge_points, s.score AS "score?"
+            FROM exam_questions eq
+            JOIN questions q ON q.id = eq.question_id
+            LEFT JOIN exam_question_scores s ON s.question_id = eq.question_id AND s.exam_record_id = $1
+            WHERE eq.exam_id = $2
+            ORDER BY eq.position
+            "#,
+            exam_record_id,
+            exam_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(scores)
+    }
+
+    /// 登记小题得分，已登记的题目覆盖原有得分，试卷记录的分数更新为小题得分之和
+    ///
+    /// 需要在事务中调用，以便小题得分和总分一起写入
+    pub async fn save(
+        conn: &mut PgConnection,
+        exam_record_id: Uuid,
+        scores: &[QuestionScoreItem],
+    ) -> Result<(), Error> {
+        let question_ids: Vec<Uuid> = scores.iter().map(|item| item.question_id).collect();
+        let values: Vec<Decimal> = scores.iter().map(|item| item.score).collect();
+        sqlx::query!(
+            r#"
+            INSERT INTO exam_question_scores (exam_record_id, question_id, score)
+            SELECT $1, * FROM UNNEST($2::uuid[], $3::decimal[])
+            ON CONFLICT (exam_record_id, question_id) DO UPDATE SET score = EXCLUDED.score
+            "#,
+            exam_record_id,
+            &question_ids,
+            &values
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        let now = OffsetDateTime::now_utc();
+        sqlx::query!(
+            r#"
+            UPDATE exam_records
+            SET score = (SELECT SUM(score) FROM exam_question_scores WHERE exam_record_id = $1), updated_at = $2
+            WHERE id = $1
+            "#,
+            exam_record_id,
+            now
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(())
+    }
+}
+
+impl KnowledgePointStat {
+    /// 按知识点统计小题得分率，按得分率从低到高排列
+    ///
+    /// 一道题涉及多个知识点时计入每个知识点；`students` 限定学生范围，为空时不限
+    pub async fn query(
+        pool: &PgPool,
+        filter: &KnowledgePointFilter,
+        students: Option<&[Uuid]>,
+    ) -> Result<Vec<Self>, Error> {
+        let stats = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT kp AS "knowledge_point!",
+                   COUNT(DISTINCT s.question_id) AS "question_count!",
+                   COUNT(*) AS "answer_count!",
+                   SUM(s.score) AS "earned!",
+                   SUM(eq.points) AS "possible!",
+                   ROUND(SUM(s.score) / SUM(eq.points), 4) AS "score_rate!"
+            FROM exam_question_scores s
+            JOIN exam_records er ON er.id = s.exam_record_id
+            JOIN exam_questions eq ON eq.exam_id = er.exam_id AND eq.question_id = s.question_id
+            JOIN questions q ON q.id = s.question_id
+            JOIN users u ON u.id = er.student_id
+            CROSS JOIN LATERAL UNNEST(q.knowledge_points) AS kp
+            WHERE ($1::int IS NULL OR u.grade = $1)
+              AND ($2::uuid IS NULL OR er.student_id IN (
+                  SELECT student_id FROM enrollments WHERE class_group_id = $2 AND dropped_on IS NULL))
+              AND ($3::uuid IS NULL OR er.student_id = $3)
+              AND ($4::uuid IS NULL OR er.exam_id = $4)
+              AND ($5::date IS NULL OR er.completion_date >= $5)
+              AND ($6::date IS NULL OR er.completion_date <= $6)
+              AND ($7::uuid[] IS NULL OR er.student_id = ANY($7))
+            GROUP BY kp
+            ORDER BY 6 ASC, 1
+            "#,
+            filter.grade,
+            filter.class_group_id,
+            filter.student_id,
+            filter.exam_id,
+            filter.start_date,
+            filter.end_date,
+            students
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(stats)
+    }
+}
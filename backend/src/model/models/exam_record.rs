@@ -4,12 +4,18 @@
 
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::{Error, postgres::PgPool};
+use sqlx::{Error, FromRow, Postgres, QueryBuilder, postgres::PgPool};
+use std::collections::HashMap;
 use time::{Date, OffsetDateTime};
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::date_format;
+use crate::model::query::{self, Page, PageParams, SortField, SortOrder};
+use crate::model::validate;
 
 /// 试卷记录结构体
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ExamRecord {
     /// 记录ID
     pub id: Uuid,
@@ -30,13 +36,14 @@ pub struct ExamRecord {
 }
 
 /// 创建试卷记录的请求数据结构
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct CreateExamRecordRequest {
     /// 学生ID
     pub student_id: Uuid,
     /// 试卷ID
     pub exam_id: Uuid,
     /// 分数
+    #[validate(custom(function = "validate::score"))]
     pub score: Option<Decimal>,
     /// 完成日期
     pub completion_date: Date,
@@ -45,13 +52,14 @@ pub struct CreateExamRecordRequest {
 }
 
 /// 更新试卷记录的请求数据结构
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Validate)]
 pub struct UpdateExamRecordRequest {
     /// 学生ID
     pub student_id: Option<Uuid>,
     /// 试卷ID
     pub exam_id: Option<Uuid>,
     /// 分数
+    #[validate(custom(function = "validate::score"))]
     pub score: Option<Decimal>,
     /// 完成日期
     pub completion_date: Option<Date>,
@@ -59,6 +67,132 @@ pub struct UpdateExamRecordRequest {
     pub notes: Option<String>,
 }
 
+/// 默认及格分数
+pub const DEFAULT_PASS_SCORE: Decimal = Decimal::from_parts(60, 0, 0, false, 0);
+/// 默认分数段宽度
+pub const DEFAULT_BUCKET_SIZE: Decimal = Decimal::from_parts(10, 0, 0, false, 0);
+
+/// 试卷成绩统计的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct ExamStatsFilter {
+    /// 学生年级
+    pub grade: Option<i32>,
+    /// 完成日期起始（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    /// 完成日期截止（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+    /// 及格分数，默认为60
+    #[validate(custom(function = "validate::score"))]
+    pub pass_score: Option<Decimal>,
+    /// 分数段宽度，默认为10，按试卷满分划分的分数段不能超过 [`MAX_SCORE_BUCKETS`] 个
+    #[validate(custom(function = "validate::positive_score"))]
+    pub bucket_size: Option<Decimal>,
+}
+
+/// 成绩统计中分数段数量的上限
+pub const MAX_SCORE_BUCKETS: i64 = 200;
+
+/// 试卷成绩统计
+///
+/// 只统计有分数的记录，没有记录时各项统计值为空
+#[derive(Debug, Clone, Serialize)]
+pub struct ExamStats {
+    /// 试卷ID
+    pub exam_id: Uuid,
+    /// 记录数
+    pub count: i64,
+    /// 平均分
+    pub mean: Option<Decimal>,
+    /// 中位数
+    pub median: Option<Decimal>,
+    /// 总体标准差
+    pub std_dev: Option<Decimal>,
+    /// 最低分
+    pub min: Option<Decimal>,
+    /// 最高分
+    pub max: Option<Decimal>,
+    /// 百分位数
+    pub percentiles: ScorePercentiles,
+    /// 及格分数
+    pub pass_score: Decimal,
+    /// 及格人数
+    pub passed: i64,
+    /// 及格率，保留四位小数
+    pub pass_rate: Option<Decimal>,
+    /// 分数段分布，从0分开始到最高分所在的分数段
+    pub buckets: Vec<ScoreBucket>,
+}
+
+/// 成绩百分位数
+#[derive(Debug, Clone, Serialize)]
+pub struct ScorePercentiles {
+    /// 第10百分位数
+    pub p10: Option<Decimal>,
+    /// 第25百分位数
+    pub p25: Option<Decimal>,
+    /// 第75百分位数
+    pub p75: Option<Decimal>,
+    /// 第90百分位数
+    pub p90: Option<Decimal>,
+}
+
+/// 分数段
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoreBucket {
+    /// 分数下限（含）
+    pub lower: Decimal,
+    /// 分数上限（不含）
+    pub upper: Decimal,
+    /// 记录数
+    pub count: i64,
+}
+
+/// 试卷记录的可排序字段
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExamRecordSort {
+    /// 完成日期
+    #[default]
+    CompletionDate,
+    /// 分数
+    Score,
+    /// 创建时间
+    CreatedAt,
+}
+
+impl SortField for ExamRecordSort {
+    fn column(self) -> &'static str {
+        match self {
+            ExamRecordSort::CompletionDate => "completion_date",
+            ExamRecordSort::Score => "score",
+            ExamRecordSort::CreatedAt => "created_at",
+        }
+    }
+}
+
+/// 查询试卷记录的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize)]
+pub struct ExamRecordFilter {
+    /// 学生ID
+    pub student_id: Option<Uuid>,
+    /// 试卷ID
+    pub exam_id: Option<Uuid>,
+    /// 完成日期起始（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    /// 完成日期截止（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+    /// 排序字段，默认为完成日期
+    #[serde(default)]
+    pub sort_by: ExamRecordSort,
+    /// 排序方向，默认为降序
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
 impl ExamRecord {
     /// 创建新试卷记录
     pub async fn create(pool: &PgPool, req: CreateExamRecordRequest) -> Result<Self, Error> {
@@ -121,38 +255,27 @@ impl ExamRecord {
         Ok(records)
     }
 
-    /// 根据试卷ID查找试卷记录
-    pub async fn find_by_exam_id(pool: &PgPool, exam_id: Uuid) -> Result<Vec<Self>, Error> {
-        let records = sqlx::query_as!(
-            Self,
+    /// 获取各试卷的平均分，保留两位小数，没有分数的试卷不返回
+    pub async fn cohort_means(
+        pool: &PgPool,
+        exam_ids: &[Uuid],
+    ) -> Result<HashMap<Uuid, Decimal>, Error> {
+        let rows = sqlx::query!(
             r#"
-            SELECT id, student_id, exam_id, score, completion_date, notes, created_at, updated_at
+            SELECT exam_id, ROUND(AVG(score), 2) AS "mean!"
             FROM exam_records
-            WHERE exam_id = $1
-            ORDER BY completion_date DESC
+            WHERE exam_id = ANY($1) AND score IS NOT NULL
+            GROUP BY exam_id
             "#,
-            exam_id
+            exam_ids
         )
         .fetch_all(pool)
         .await?;
 
-        Ok(records)
-    }
-
-    /// 获取所有试卷记录
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<Self>, Error> {
-        let records = sqlx::query_as!(
-            Self,
-            r#"
-            SELECT id, student_id, exam_id, score, completion_date, notes, created_at, updated_at
-            FROM exam_records
-            ORDER BY completion_date DESC
-            "#
-        )
-        .fetch_all(pool)
-        .await?;
-
-        Ok(records)
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.exam_id, row.mean))
+            .collect())
     }
 
     /// 更新试卷记录
@@ -204,61 +327,225 @@ impl ExamRecord {
         Ok(result.rows_affected() > 0)
     }
 
-    /// 根据日期范围查找试卷记录
-    pub async fn find_by_date_range(
+    /// 按筛选条件分页查询试卷记录
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn query(
         pool: &PgPool,
-        start_date: Option<Date>,
-        end_date: Option<Date>,
-    ) -> Result<Vec<Self>, Error> {
-        let records = match (start_date, end_date) {
-            (Some(start), Some(end)) => {
-                sqlx::query_as!(
-                    Self,
-                    r#"
-                    SELECT id, student_id, exam_id, score, completion_date, notes, created_at, updated_at
-                    FROM exam_records
-                    WHERE completion_date >= $1 AND completion_date <= $2
-                    ORDER BY completion_date DESC
-                    "#,
-                    start,
-                    end
-                )
-                .fetch_all(pool)
-                .await?
-            },
-            (Some(start), None) => {
-                sqlx::query_as!(
-                    Self,
-                    r#"
-                    SELECT id, student_id, exam_id, score, completion_date, notes, created_at, updated_at
-                    FROM exam_records
-                    WHERE completion_date >= $1
-                    ORDER BY completion_date DESC
-                    "#,
-                    start
-                )
-                .fetch_all(pool)
-                .await?
-            },
-            (None, Some(end)) => {
-                sqlx::query_as!(
-                    Self,
-                    r#"
-                    SELECT id, student_id, exam_id, score, completion_date, notes, created_at, updated_at
-                    FROM exam_records
-                    WHERE completion_date <= $1
-                    ORDER BY completion_date DESC
-                    "#,
-                    end
-                )
-                .fetch_all(pool)
-                .await?
-            },
-            (None, None) => {
-                return Self::find_all(pool).await;
+        filter: &ExamRecordFilter,
+        students: Option<&[Uuid]>,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let mut builder = QueryBuilder::new("SELECT COUNT(*) FROM exam_records WHERE TRUE");
+        Self::push_filter(&mut builder, filter, students);
+        let total: i64 = builder.build_query_scalar().fetch_one(pool).await?;
+
+        let mut builder = QueryBuilder::new(
+            r#"
+            SELECT id, student_id, exam_id, score, completion_date, notes, created_at, updated_at
+            FROM exam_records
+            WHERE TRUE
+            "#,
+        );
+        Self::push_filter(&mut builder, filter, students);
+        query::push_order_by(&mut builder, filter.sort_by, filter.order);
+        query::push_page(&mut builder, page);
+        let records = builder.build_query_as::<Self>().fetch_all(pool).await?;
+
+        Ok(Page::new(records, total, page))
+    }
+
+    /// 统计试卷成绩
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn stats(
+        pool: &PgPool,
+        exam_id: Uuid,
+        filter: &ExamStatsFilter,
+        students: Option<&[Uuid]>,
+    ) -> Result<ExamStats, Error> {
+        let pass_score = filter.pass_score.unwrap_or(DEFAULT_PASS_SCORE);
+        let bucket_size = filter.bucket_size.unwrap_or(DEFAULT_BUCKET_SIZE);
+
+        let summary = sqlx::query!(
+            r#"
+            SELECT COUNT(*) AS "count!",
+                   ROUND(AVG(er.score), 2) AS mean,
+                   ROUND(percentile_cont(0.5) WITHIN GROUP (ORDER BY er.score)::numeric, 2) AS median,
+                   ROUND(stddev_pop(er.score), 2) AS std_dev,
+                   MIN(er.score) AS min,
+                   MAX(er.score) AS max,
+                   ROUND(percentile_cont(0.1) WITHIN GROUP (ORDER BY er.score)::numeric, 2) AS p10,
+                   ROUND(percentile_cont(0.25) WITHIN GROUP (ORDER BY er.score)::numeric, 2) AS p25,
+                   ROUND(percentile_cont(0.75) WITHIN GROUP (ORDER BY er.score)::numeric, 2) AS p75,
+                   ROUND(percentile_cont(0.9) WITHIN GROUP (ORDER BY er.score)::numeric, 2) AS p90,
+                   COUNT(*) FILTER (WHERE er.score >= $6) AS "passed!"
+            FROM exam_records er
+            JOIN users u ON u.id = er.student_id
+            WHERE er.exam_id = $1 AND er.score IS NOT NULL
+              AND ($2::int IS NULL OR u.grade = $2)
+              AND ($3::date IS NULL OR er.completion_date >= $3)
+              AND ($4::date IS NULL OR er.completion_date <= $4)
+              AND ($5::uuid[] IS NULL OR er.student_id = ANY($5))
+            "#,
+            exam_id,
+            filter.grade,
+            filter.start_date,
+            filter.end_date,
+            students,
+            pass_score
+        )
+        .fetch_one(pool)
+        .await?;
+
+        let rows = sqlx::query!(
+            r#"
+            SELECT FLOOR(er.score / $6) * $6 AS "lower!", COUNT(*) AS "count!"
+            FROM exam_records er
+            JOIN users u ON u.id = er.student_id
+            WHERE er.exam_id = $1 AND er.score IS NOT NULL
+              AND ($2::int IS NULL OR u.grade = $2)
+              AND ($3::date IS NULL OR er.completion_date >= $3)
+              AND ($4::date IS NULL OR er.completion_date <= $4)
+              AND ($5::uuid[] IS NULL OR er.student_id = ANY($5))
+            GROUP BY 1
+            ORDER BY 1
+            "#,
+            exam_id,
+            filter.grade,
+            filter.start_date,
+            filter.end_date,
+            students,
+            bucket_size
+        )
+        .fetch_all(pool)
+        .await?;
+
+        // 从0分起补齐没有记录的分数段，最多补齐 MAX_SCORE_BUCKETS 个，
+        // 之后（分数超过满分的记录）只列出有记录的分数段
+        let mut buckets = Vec::new();
+        let mut rows = rows.into_iter().peekable();
+        let mut lower = Decimal::ZERO;
+        for _ in 0..MAX_SCORE_BUCKETS {
+            if rows.peek().is_none() {
+                break;
             }
+            let count = rows
+                .next_if(|row| row.lower == lower)
+                .map_or(0, |row| row.count);
+            buckets.push(ScoreBucket {
+                lower,
+                upper: lower + bucket_size,
+                count,
+            });
+            lower += bucket_size;
+        }
+        buckets.extend(rows.map(|row| ScoreBucket {
+            lower: row.lower,
+            upper: row.lower + bucket_size,
+            count: row.count,
+        }));
+
+        Ok(ExamStats {
+            exam_id,
+            count: summary.count,
+            mean: summary.mean,
+            median: summary.median,
+            std_dev: summary.std_dev,
+            min: summary.min,
+            max: summary.max,
+            percentiles: ScorePercentiles {
+                p10: summary.p10,
+                p25: summary.p25,
+                p75: summary.p75,
+                p90: summary.p90,
+            },
+            pass_score,
+            passed: summary.passed,
+            pass_rate: (summary.count > 0).then(|| {
+                (Decimal::from(summary.passed) / Decimal::from(summary.count)).round_dp(4)
+            }),
+            buckets,
+        })
+    }
+
+    /// 追加筛选条件
+    fn push_filter(
+        builder: &mut QueryBuilder<'_, Postgres>,
+        filter: &ExamRecordFilter,
+        students: Option<&[Uuid]>,
+    ) {
+        if let Some(students) = students {
+            builder
+                .push(" AND student_id = ANY(")
+                .push_bind(students.to_vec())
+                .push(")");
+        }
+        if let Some(student_id) = filter.student_id {
+            builder.push(" AND student_id = ").push_bind(student_id);
+        }
+        if let Some(exam_id) = filter.exam_id {
+            builder.push(" AND exam_id = ").push_bind(exam_id);
+        }
+        if let Some(start_date) = filter.start_date {
+            builder
+                .push(" AND completion_date >= ")
+                .push_bind(start_date);
+        }
+        if let Some(end_date) = filter.end_date {
+            builder.push(" AND completion_date <= ").push_bind(end_date);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{TestApp, dec};
+
+    #[sqlx::test]
+    async fn stats_pads_at_most_max_buckets_for_scores_above_full_score(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+        let exam_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO exams (id, title, max_score) VALUES ($1, '期中考试', 100)")
+            .bind(exam_id)
+            .execute(&app.pool)
+            .await
+            .unwrap();
+
+        // 超过满分的分数由早于满分校验的数据或直接导入产生
+        for score in ["95", "999.99"] {
+            let student = app.user("student").await;
+            sqlx::query(
+                "INSERT INTO exam_records (id, student_id, exam_id, score, completion_date)
+                 VALUES ($1, $2, $3, $4, CURRENT_DATE)",
+            )
+            .bind(Uuid::new_v4())
+            .bind(student.id)
+            .bind(exam_id)
+            .bind(dec(score))
+            .execute(&app.pool)
+            .await
+            .unwrap();
+        }
+
+        let filter = ExamStatsFilter {
+            bucket_size: Some(dec("0.5")),
+            ..Default::default()
         };
+        let stats = ExamRecord::stats(&app.pool, exam_id, &filter, None)
+            .await
+            .unwrap();
 
-        Ok(records)
+        assert_eq!(stats.buckets.len(), MAX_SCORE_BUCKETS as usize + 1);
+        assert_eq!(stats.buckets[190].lower, dec("95"));
+        assert_eq!(stats.buckets[190].count, 1);
+        let last = stats.buckets.last().unwrap();
+        assert_eq!(last.lower, dec("999.5"));
+        assert_eq!(last.count, 1);
+        assert_eq!(
+            stats.buckets.iter().map(|bucket| bucket.count).sum::<i64>(),
+            2
+        );
     }
 }
@@ -0,0 +1,159 @@
+//! 文件模型
+//!
+//! 提供上传文件元数据的数据结构和数据库操作方法
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, postgres::PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// 文件的归属
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileOwner {
+    /// 学生提交的作业文件
+    Submission {
+        /// 学生ID
+        student_id: Uuid,
+    },
+    /// 试卷文件
+    Exam,
+    /// 作业任务的附件
+    Assignment,
+    /// 未被引用的文件
+    None,
+}
+
+/// 文件元数据结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredFile {
+    /// 文件ID
+    pub id: Uuid,
+    /// 存储后端中的文件键
+    pub storage_key: String,
+    /// 原始文件名
+    pub original_name: String,
+    /// MIME类型
+    pub content_type: String,
+    /// 文件大小（字节）
+    pub size: i64,
+    /// 文件内容SHA-256哈希
+    pub sha256: String,
+    /// 上传用户ID
+    pub uploaded_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+}
+
+/// 创建文件记录的请求数据结构
+#[derive(Debug)]
+pub struct CreateFileRequest {
+    /// 存储后端中的文件键
+    pub storage_key: String,
+    /// 原始文件名
+    pub original_name: String,
+    /// MIME类型
+    pub content_type: String,
+    /// 文件大小（字节）
+    pub size: i64,
+    /// 文件内容SHA-256哈希
+    pub sha256: String,
+    /// 上传用户ID
+    pub uploaded_by: Option<Uuid>,
+}
+
+impl StoredFile {
+    /// 创建文件记录
+    pub async fn create(pool: &PgPool, id: Uuid, req: CreateFileRequest) -> Result<Self, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let file = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO files (id, storage_key, original_name, content_type, size, sha256, uploaded_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING id, storage_key, original_name, content_type, size, sha256, uploaded_by, created_at
+            "#,
+            id,
+            req.storage_key,
+            req.original_name,
+            req.content_type,
+            req.size,
+            req.sha256,
+            req.uploaded_by,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(file)
+    }
+
+    /// 根据ID查找文件
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let file = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, storage_key, original_name, content_type, size, sha256, uploaded_by, created_at
+            FROM files
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(file)
+    }
+
+    /// 根据存储键查找文件
+    pub async fn find_by_storage_key(
+        pool: &PgPool,
+        storage_key: &str,
+    ) -> Result<Option<Self>, Error> {
+        let file = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, storage_key, original_name, content_type, size, sha256, uploaded_by, created_at
+            FROM files
+            WHERE storage_key = $1
+            "#,
+            storage_key
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(file)
+    }
+
+    /// 查找文件的归属，同一文件按提交、试卷、作业任务附件的顺序取第一个
+    pub async fn owner(&self, pool: &PgPool) -> Result<FileOwner, Error> {
+        let row = sqlx::query!(
+            r#"
+            SELECT (SELECT s.student_id FROM submissions s WHERE s.file_path = $1 LIMIT 1) AS student_id,
+                   EXISTS (SELECT 1 FROM exams e WHERE e.file_path = $1) AS "exam!",
+                   EXISTS (SELECT 1 FROM assignment_attachments a WHERE a.file_id = $2) AS "assignment!"
+            "#,
+            self.storage_key,
+            self.id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        let owner = match row.student_id {
+            Some(student_id) => FileOwner::Submission { student_id },
+            None if row.exam => FileOwner::Exam,
+            None if row.assignment => FileOwner::Assignment,
+            None => FileOwner::None,
+        };
+        Ok(owner)
+    }
+
+    /// 删除文件记录
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+        let result = sqlx::query!("DELETE FROM files WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,939 @@
+//! 总评成绩模型
+//!
+//! 按课程某学期的成绩类别和权重，由作业提交和试卷记录计算学生的总评，
+//! 并保存教师调整和锁定后的总评
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use std::collections::HashMap;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::grade_category::GradeCategory;
+use super::grading_scale::GradingScale;
+use crate::model::validate;
+
+/// 成绩项目类型枚举
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum GradeItemKind {
+    /// 作业任务
+    Assignment,
+    /// 试卷
+    Exam,
+}
+
+/// 参与总评的成绩项目
+#[derive(Debug, Clone, Serialize)]
+pub struct GradeItem {
+    /// 项目类型
+    pub kind: GradeItemKind,
+    /// 作业任务ID或试卷ID
+    pub id: Uuid,
+    /// 标题
+    pub title: String,
+    /// 分数，已布置但逾期未交的作业按0分计，尚未评分时为空
+    pub score: Option<Decimal>,
+    /// 满分，未设置时按100分计
+    pub max_score: Decimal,
+    /// 得分率（百分比），尚未评分时为空
+    pub percent: Option<Decimal>,
+    /// 是否免做
+    pub excused: bool,
+    /// 是否作为最低的项目被去掉
+    pub dropped: bool,
+}
+
+impl GradeItem {
+    /// 创建成绩项目并计算得分率
+    ///
+    /// 得分率不超过100，以免超过满分的分数使总评超出范围
+    fn new(
+        kind: GradeItemKind,
+        id: Uuid,
+        title: String,
+        score: Option<Decimal>,
+        max_score: Option<Decimal>,
+        excused: bool,
+    ) -> Self {
+        let max_score = max_score.unwrap_or(Decimal::ONE_HUNDRED);
+        let percent = score.map(|score| {
+            (score * Decimal::ONE_HUNDRED / max_score)
+                .round_dp(2)
+                .min(Decimal::ONE_HUNDRED)
+        });
+        Self {
+            kind,
+            id,
+            title,
+            score,
+            max_score,
+            percent,
+            excused,
+            dropped: false,
+        }
+    }
+}
+
+/// 成绩类别的计算结果
+#[derive(Debug, Clone, Serialize)]
+pub struct CategoryScore {
+    /// 类别ID
+    pub category_id: Uuid,
+    /// 类别名称
+    pub name: String,
+    /// 权重（百分比）
+    pub weight: Decimal,
+    /// 去掉最低的项数
+    pub drop_lowest: i32,
+    /// 类别得分率，为计入的项目得分率的平均值，没有计入的项目时为空
+    pub percent: Option<Decimal>,
+    /// 类别中的项目
+    pub items: Vec<GradeItem>,
+}
+
+impl CategoryScore {
+    /// 计算类别得分率
+    ///
+    /// 免做和尚未评分的项目不计入；去掉得分率最低的 `drop_lowest` 项，但至少保留一项
+    fn new(category: &GradeCategory, mut items: Vec<GradeItem>) -> Self {
+        let mut counted: Vec<usize> = (0..items.len())
+            .filter(|&i| !items[i].excused && items[i].percent.is_some())
+            .collect();
+        counted.sort_by_key(|&i| items[i].percent);
+
+        let drop = usize::try_from(category.drop_lowest)
+            .unwrap_or(0)
+            .min(counted.len().saturating_sub(1));
+        for &i in &counted[..drop] {
+            items[i].dropped = true;
+        }
+
+        let kept: Vec<Decimal> = counted[drop..]
+            .iter()
+            .filter_map(|&i| items[i].percent)
+            .collect();
+        let percent = (!kept.is_empty())
+            .then(|| (kept.iter().sum::<Decimal>() / Decimal::from(kept.len())).round_dp(2));
+
+        Self {
+            category_id: category.id,
+            name: category.name.clone(),
+            weight: category.weight,
+            drop_lowest: category.drop_lowest,
+            percent,
+            items,
+        }
+    }
+}
+
+/// 按权重计算总评：有得分率的类别按权重加权平均，权重之和不必为100
+fn weighted_score(categories: &[CategoryScore]) -> Option<Decimal> {
+    let (total, weights) = categories
+        .iter()
+        .filter_map(|category| {
+            category
+                .percent
+                .map(|percent| (percent * category.weight, category.weight))
+        })
+        .fold(
+            (Decimal::ZERO, Decimal::ZERO),
+            |(total, weights), (score, weight)| (total + score, weights + weight),
+        );
+
+    (!weights.is_zero()).then(|| (total / weights).round_dp(2))
+}
+
+/// 学生的总评计算结果
+#[derive(Debug, Clone, Serialize)]
+pub struct FinalGradePreview {
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 用户名
+    pub username: String,
+    /// 显示名称
+    pub display_name: Option<String>,
+    /// 各类别的计算结果
+    pub categories: Vec<CategoryScore>,
+    /// 按当前成绩计算的总评，没有计入的项目时为空
+    pub computed_score: Option<Decimal>,
+    /// 最终总评：已锁定时为锁定的总评，否则调整后的总评优先
+    pub score: Option<Decimal>,
+    /// 按评分等级制显示的等级
+    pub grade: Option<String>,
+    /// 已保存的总评
+    pub saved: Option<FinalGrade>,
+}
+
+/// 总评成绩结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinalGrade {
+    /// 总评ID
+    pub id: Uuid,
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 学期
+    pub term: String,
+    /// 按权重计算的总评
+    pub computed_score: Option<Decimal>,
+    /// 调整后的总评
+    pub override_score: Option<Decimal>,
+    /// 调整原因
+    pub override_reason: Option<String>,
+    /// 最终总评，调整后的总评优先
+    pub score: Option<Decimal>,
+    /// 按评分等级制显示的等级
+    pub grade: Option<String>,
+    /// 锁定时间，为空表示未锁定
+    pub locked_at: Option<OffsetDateTime>,
+    /// 锁定总评的用户ID
+    pub locked_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 查询总评的参数
+#[derive(Debug, Deserialize)]
+pub struct FinalGradeParams {
+    /// 学期
+    pub term: String,
+    /// 学生ID，为空时为课程中所有在读的学生
+    pub student_id: Option<Uuid>,
+}
+
+/// 调整总评的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct OverrideFinalGradeRequest {
+    /// 学期
+    #[validate(custom(function = "validate::not_blank"))]
+    pub term: String,
+    /// 调整后的总评，为空时取消调整
+    #[validate(custom(function = "validate::percent"))]
+    pub score: Option<Decimal>,
+    /// 调整原因，调整总评时必填
+    pub reason: Option<String>,
+}
+
+/// 锁定或解锁总评的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct LockFinalGradesRequest {
+    /// 学期
+    #[validate(custom(function = "validate::not_blank"))]
+    pub term: String,
+    /// 学生ID，为空时为课程中所有在读的学生
+    pub student_ids: Option<Vec<Uuid>>,
+}
+
+impl FinalGradePreview {
+    /// 计算课程某学期学生的总评，按年级和用户名排列
+    ///
+    /// 作业取提交的分数（迟交扣分后），已布置但截至 `today` 逾期未交的按0分计；
+    /// 试卷取最近完成的一次记录。已锁定的总评保持锁定时的分数和等级
+    pub async fn compute(
+        pool: &PgPool,
+        course_id: Uuid,
+        term: &str,
+        student_ids: &[Uuid],
+        today: Date,
+    ) -> Result<Vec<Self>, Error> {
+        let categories = GradeCategory::find_by_course(pool, course_id, Some(term)).await?;
+        let category_ids: Vec<Uuid> = categories.iter().map(|category| category.id).collect();
+
+        let students = sqlx::query!(
+            r#"
+            SELECT id, username, display_name
+            FROM users
+            WHERE id = ANY($1)
+            ORDER BY grade NULLS LAST, username
+            "#,
+            student_ids
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut items: HashMap<(Uuid, Uuid), Vec<GradeItem>> = HashMap::new();
+        let assignments = sqlx::query!(
+            r#"
+            SELECT a.category_id AS "category_id!", a.id, a.title, a.max_score, s.student_id,
+                   CASE WHEN s.score IS NULL AND s.status = 'assigned' AND a.due_date < $3 THEN 0
+                        ELSE s.score END AS score,
+                   (x.id IS NOT NULL) AS "excused!"
+            FROM assignments a
+            JOIN submissions s ON s.assignment_id = a.id
+            LEFT JOIN grade_excusals x ON x.assignment_id = a.id AND x.student_id = s.student_id
+            WHERE a.category_id = ANY($1) AND s.student_id = ANY($2)
+            ORDER BY a.due_date NULLS LAST, a.created_at, a.id
+            "#,
+            &category_ids,
+            student_ids,
+            today
+        )
+        .fetch_all(pool)
+        .await?;
+        for row in assignments {
+            items
+                .entry((row.student_id, row.category_id))
+                .or_default()
+                .push(GradeItem::new(
+                    GradeItemKind::Assignment,
+                    row.id,
+                    row.title,
+                    row.score,
+                    row.max_score,
+                    row.excused,
+                ));
+        }
+
+        let exams = sqlx::query!(
+            r#"
+            SELECT DISTINCT ON (er.student_id, e.id)
+                   e.category_id AS "category_id!", e.id, e.title, e.max_score, er.student_id, er.score,
+                   (x.id IS NOT NULL) AS "excused!"
+            FROM exams e
+            JOIN exam_records er ON er.exam_id = e.id
+            LEFT JOIN grade_excusals x ON x.exam_id = e.id AND x.student_id = er.student_id
+            WHERE e.category_id = ANY($1) AND er.student_id = ANY($2)
+            ORDER BY er.student_id, e.id, er.completion_date DESC, er.created_at DESC
+            "#,
+            &category_ids,
+            student_ids
+        )
+        .fetch_all(pool)
+        .await?;
+        for row in exams {
+            items
+                .entry((row.student_id, row.category_id))
+                .or_default()
+                .push(GradeItem::new(
+                    GradeItemKind::Exam,
+                    row.id,
+                    row.title,
+                    row.score,
+                    row.max_score,
+                    row.excused,
+                ));
+        }
+
+        let mut saved: HashMap<Uuid, FinalGrade> =
+            FinalGrade::find_by_course(pool, course_id, term, Some(student_ids), false)
+                .await?
+                .into_iter()
+                .map(|grade| (grade.student_id, grade))
+                .collect();
+        let scale = GradingScale::find_by_course(pool, course_id).await?;
+
+        let previews = students
+            .into_iter()
+            .map(|student| {
+                let categories: Vec<CategoryScore> = categories
+                    .iter()
+                    .map(|category| {
+                        let items = items.remove(&(student.id, category.id)).unwrap_or_default();
+                        CategoryScore::new(category, items)
+                    })
+                    .collect();
+                let computed_score = weighted_score(&categories);
+                let saved = saved.remove(&student.id);
+
+                let (score, grade) = match &saved {
+                    Some(saved) if saved.locked_at.is_some() => (saved.score, saved.grade.clone()),
+                    _ => {
+                        let score = saved
+                            .as_ref()
+                            .and_then(|saved| saved.override_score)
+                            .or(computed_score);
+                        let grade = score
+                            .map(|score| GradingScale::convert(scale.as_ref(), score, None).value);
+                        (score, grade)
+                    }
+                };
+
+                Self {
+                    student_id: student.id,
+                    username: student.username,
+                    display_name: student.display_name,
+                    categories,
+                    computed_score,
+                    score,
+                    grade,
+                    saved,
+                }
+            })
+            .collect();
+
+        Ok(previews)
+    }
+}
+
+impl FinalGrade {
+    /// 获取课程某学期的总评，按年级和用户名排列
+    ///
+    /// `students` 限定学生范围，为空时不限；`locked_only` 为真时只返回已锁定的总评
+    pub async fn find_by_course(
+        pool: &PgPool,
+        course_id: Uuid,
+        term: &str,
+        students: Option<&[Uuid]>,
+        locked_only: bool,
+    ) -> Result<Vec<Self>, Error> {
+        let grades = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT f.id, f.student_id, f.course_id, f.term, f.computed_score, f.override_score,
+                   f.override_reason, f.score, f.grade, f.locked_at, f.locked_by, f.created_at, f.updated_at
+            FROM final_grades f
+            JOIN users u ON u.id = f.student_id
+            WHERE f.course_id = $1 AND f.term = $2
+              AND ($3::uuid[] IS NULL OR f.student_id = ANY($3))
+              AND (NOT $4 OR f.locked_at IS NOT NULL)
+            ORDER BY u.grade NULLS LAST, u.username
+            "#,
+            course_id,
+            term,
+            students,
+            locked_only
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(grades)
+    }
+
+    /// 获取学生的总评，按学期倒序排列
+    ///
+    /// `locked_only` 为真时只返回已锁定的总评
+    pub async fn find_by_student(
+        pool: &PgPool,
+        student_id: Uuid,
+        locked_only: bool,
+    ) -> Result<Vec<Self>, Error> {
+        let grades = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, student_id, course_id, term, computed_score, override_score,
+                   override_reason, score, grade, locked_at, locked_by, created_at, updated_at
+            FROM final_grades
+            WHERE student_id = $1 AND (NOT $2 OR locked_at IS NOT NULL)
+            ORDER BY term DESC, course_id
+            "#,
+            student_id,
+            locked_only
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(grades)
+    }
+
+    /// 保存调整后的总评，`override_score` 为空时取消调整
+    ///
+    /// 总评已锁定时不修改，返回空
+    #[allow(clippy::too_many_arguments)]
+    pub async fn save_override<'e, E: PgExecutor<'e>>(
+        executor: E,
+        student_id: Uuid,
+        course_id: Uuid,
+        term: &str,
+        computed_score: Option<Decimal>,
+        override_score: Option<Decimal>,
+        override_reason: Option<&str>,
+        grade: Option<&str>,
+    ) -> Result<Option<Self>, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let final_grade = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO final_grades (id, student_id, course_id, term, computed_score, override_score,
+                                      override_reason, grade, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            ON CONFLICT (student_id, course_id, term) DO UPDATE
+            SET computed_score = EXCLUDED.computed_score, override_score = EXCLUDED.override_score,
+                override_reason = EXCLUDED.override_reason, grade = EXCLUDED.grade,
+                updated_at = EXCLUDED.updated_at
+            WHERE final_grades.locked_at IS NULL
+            RETURNING id, student_id, course_id, term, computed_score, override_score,
+                      override_reason, score, grade, locked_at, locked_by, created_at, updated_at
+            "#,
+            id,
+            student_id,
+            course_id,
+            term,
+            computed_score,
+            override_score,
+            override_reason,
+            grade,
+            now
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(final_grade)
+    }
+
+    /// 锁定总评，同时保存计算的总评和等级，已调整的总评保留调整
+    ///
+    /// 总评已锁定时不修改，返回空
+    pub async fn lock<'e, E: PgExecutor<'e>>(
+        executor: E,
+        student_id: Uuid,
+        course_id: Uuid,
+        term: &str,
+        computed_score: Option<Decimal>,
+        grade: Option<&str>,
+        locked_by: Uuid,
+    ) -> Result<Option<Self>, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let final_grade = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO final_grades (id, student_id, course_id, term, computed_score, grade,
+                                      locked_at, locked_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $7, $7)
+            ON CONFLICT (student_id, course_id, term) DO UPDATE
+            SET computed_score = EXCLUDED.computed_score, grade = EXCLUDED.grade,
+                locked_at = EXCLUDED.locked_at, locked_by = EXCLUDED.locked_by,
+                updated_at = EXCLUDED.updated_at
+            WHERE final_grades.locked_at IS NULL
+            RETURNING id, student_id, course_id, term, computed_score, override_score,
+                      override_reason, score, grade, locked_at, locked_by, created_at, updated_at
+            "#,
+            id,
+            student_id,
+            course_id,
+            term,
+            computed_score,
+            grade,
+            now,
+            locked_by
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(final_grade)
+    }
+
+    /// 解锁课程某学期的总评，`students` 为空时解锁全部，返回解锁的总评
+    pub async fn unlock<'e, E: PgExecutor<'e>>(
+        executor: E,
+        course_id: Uuid,
+        term: &str,
+        students: Option<&[Uuid]>,
+    ) -> Result<Vec<Self>, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let grades = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE final_grades
+            SET locked_at = NULL, locked_by = NULL, updated_at = $1
+            WHERE course_id = $2 AND term = $3 AND locked_at IS NOT NULL
+              AND ($4::uuid[] IS NULL OR student_id = ANY($4))
+            RETURNING id, student_id, course_id, term, computed_score, override_score,
+                      override_reason, score, grade, locked_at, locked_by, created_at, updated_at
+            "#,
+            now,
+            course_id,
+            term,
+            students
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(grades)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{TestApp, TestUser, dec, json_dec};
+    use axum::http::{Method, StatusCode};
+    use serde_json::{Value, json};
+    use time::macros::date;
+
+    fn category(weight: &str, drop_lowest: i32) -> GradeCategory {
+        GradeCategory {
+            id: Uuid::new_v4(),
+            course_id: Uuid::nil(),
+            term: "2025春".to_string(),
+            name: "作业".to_string(),
+            weight: dec(weight),
+            drop_lowest,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn item(score: Option<&str>, excused: bool) -> GradeItem {
+        GradeItem::new(
+            GradeItemKind::Assignment,
+            Uuid::new_v4(),
+            "作业".to_string(),
+            score.map(dec),
+            None,
+            excused,
+        )
+    }
+
+    fn scored(percent: Option<&str>, weight: &str) -> CategoryScore {
+        CategoryScore {
+            category_id: Uuid::new_v4(),
+            name: "类别".to_string(),
+            weight: dec(weight),
+            drop_lowest: 0,
+            percent: percent.map(dec),
+            items: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn grade_item_defaults_max_score_to_100() {
+        let item = item(Some("85"), false);
+        assert_eq!(item.max_score, Decimal::ONE_HUNDRED);
+        assert_eq!(item.percent, Some(dec("85")));
+    }
+
+    #[test]
+    fn grade_item_percent_uses_max_score() {
+        let item = GradeItem::new(
+            GradeItemKind::Exam,
+            Uuid::nil(),
+            "测验".to_string(),
+            Some(dec("20")),
+            Some(dec("30")),
+            false,
+        );
+        assert_eq!(item.percent, Some(dec("66.67")));
+    }
+
+    #[test]
+    fn grade_item_percent_is_capped_at_100() {
+        let item = GradeItem::new(
+            GradeItemKind::Exam,
+            Uuid::nil(),
+            "测验".to_string(),
+            Some(dec("120")),
+            Some(dec("100")),
+            false,
+        );
+        assert_eq!(item.percent, Some(Decimal::ONE_HUNDRED));
+    }
+
+    #[test]
+    fn grade_item_without_score_has_no_percent() {
+        assert_eq!(item(None, false).percent, None);
+    }
+
+    #[test]
+    fn category_averages_counted_items() {
+        let score = CategoryScore::new(
+            &category("40", 0),
+            vec![item(Some("80"), false), item(Some("90"), false)],
+        );
+        assert_eq!(score.percent, Some(dec("85")));
+        assert!(score.items.iter().all(|item| !item.dropped));
+    }
+
+    #[test]
+    fn category_skips_excused_and_ungraded_items() {
+        let score = CategoryScore::new(
+            &category("40", 0),
+            vec![
+                item(Some("60"), false),
+                item(Some("0"), true),
+                item(None, false),
+            ],
+        );
+        assert_eq!(score.percent, Some(dec("60")));
+    }
+
+    #[test]
+    fn category_drops_lowest_items() {
+        let score = CategoryScore::new(
+            &category("40", 2),
+            vec![
+                item(Some("50"), false),
+                item(Some("90"), false),
+                item(Some("70"), false),
+                item(Some("100"), false),
+            ],
+        );
+        assert_eq!(score.percent, Some(dec("95")));
+        let dropped: Vec<bool> = score.items.iter().map(|item| item.dropped).collect();
+        assert_eq!(dropped, [true, false, true, false]);
+    }
+
+    #[test]
+    fn category_keeps_at_least_one_item() {
+        let score = CategoryScore::new(
+            &category("40", 5),
+            vec![item(Some("70"), false), item(Some("40"), false)],
+        );
+        assert_eq!(score.percent, Some(dec("70")));
+        assert!(score.items[1].dropped);
+        assert!(!score.items[0].dropped);
+    }
+
+    #[test]
+    fn category_never_drops_excused_items() {
+        let score = CategoryScore::new(
+            &category("40", 1),
+            vec![item(Some("0"), true), item(Some("80"), false)],
+        );
+        assert_eq!(score.percent, Some(dec("80")));
+        assert!(score.items.iter().all(|item| !item.dropped));
+    }
+
+    #[test]
+    fn category_without_counted_items_has_no_percent() {
+        let score = CategoryScore::new(&category("40", 1), vec![item(None, false)]);
+        assert_eq!(score.percent, None);
+        assert_eq!(
+            CategoryScore::new(&category("40", 0), Vec::new()).percent,
+            None
+        );
+    }
+
+    #[test]
+    fn category_rounds_mean_to_two_decimals() {
+        let score = CategoryScore::new(
+            &category("40", 0),
+            vec![
+                item(Some("100"), false),
+                item(Some("100"), false),
+                item(Some("0"), false),
+            ],
+        );
+        assert_eq!(score.percent, Some(dec("66.67")));
+    }
+
+    #[test]
+    fn weighted_score_normalizes_weights() {
+        let categories = [scored(Some("80"), "30"), scored(Some("90"), "20")];
+        assert_eq!(weighted_score(&categories), Some(dec("84")));
+    }
+
+    #[test]
+    fn weighted_score_skips_categories_without_percent() {
+        let categories = [scored(Some("75"), "40"), scored(None, "60")];
+        assert_eq!(weighted_score(&categories), Some(dec("75")));
+    }
+
+    #[test]
+    fn weighted_score_is_none_without_scores_or_weights() {
+        assert_eq!(weighted_score(&[]), None);
+        assert_eq!(weighted_score(&[scored(None, "100")]), None);
+        assert_eq!(weighted_score(&[scored(Some("80"), "0")]), None);
+    }
+
+    #[test]
+    fn weighted_score_rounds_to_two_decimals() {
+        let categories = [
+            scored(Some("100"), "1"),
+            scored(Some("100"), "1"),
+            scored(Some("0"), "1"),
+        ];
+        assert_eq!(weighted_score(&categories), Some(dec("66.67")));
+    }
+
+    const TERM: &str = "2024-2025-2";
+
+    /// 布置一项满分50分的作业并按给定分数评分，返回提交ID
+    async fn graded_submission(
+        app: &TestApp,
+        teacher: &TestUser,
+        student: &TestUser,
+        course_id: Uuid,
+        score: u32,
+    ) -> String {
+        let (_, category) = app
+            .send(
+                Method::POST,
+                "/grade-categories",
+                teacher,
+                Some(
+                    json!({ "course_id": course_id, "term": TERM, "name": "作业", "weight": 100 }),
+                ),
+            )
+            .await;
+        let (_, assignment) = app
+            .send(
+                Method::POST,
+                "/assignments",
+                teacher,
+                Some(json!({
+                    "course_id": course_id,
+                    "category_id": category["id"],
+                    "title": "期中作业",
+                    "max_score": 50,
+                })),
+            )
+            .await;
+        let (_, submission) = app
+            .send(
+                Method::POST,
+                "/submissions",
+                teacher,
+                Some(json!({ "assignment_id": assignment["id"], "student_id": student.id })),
+            )
+            .await;
+        let id = submission["id"].as_str().unwrap().to_string();
+        let (status, _) = app
+            .send(
+                Method::PUT,
+                &format!("/submissions/{id}/grade"),
+                teacher,
+                Some(json!({ "score": score })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        id
+    }
+
+    async fn saved_final_grades(app: &TestApp, user: &TestUser, course_id: Uuid) -> Vec<Value> {
+        let (status, grades) = app
+            .send(
+                Method::GET,
+                &format!("/courses/{course_id}/final-grades?term={TERM}"),
+                user,
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        grades.as_array().unwrap().clone()
+    }
+
+    #[sqlx::test]
+    async fn locked_final_grade_is_kept_until_unlocked(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+        let admin = app.user("admin").await;
+        let teacher = app.user("teacher").await;
+        let student = app.user("student").await;
+        let course_id = app.course(&teacher).await;
+        app.enroll(course_id, &student).await;
+        let submission_id = graded_submission(&app, &teacher, &student, course_id, 40).await;
+
+        let (status, locked) = app
+            .send(
+                Method::POST,
+                &format!("/courses/{course_id}/final-grades/lock"),
+                &teacher,
+                Some(json!({ "term": TERM })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(locked.as_array().unwrap().len(), 1);
+
+        // 锁定后改分不影响已保存的总评
+        app.send(
+            Method::PUT,
+            &format!("/submissions/{submission_id}/grade"),
+            &teacher,
+            Some(json!({ "score": 50 })),
+        )
+        .await;
+        let grades = saved_final_grades(&app, &teacher, course_id).await;
+        assert_eq!(grades.len(), 1);
+        assert_eq!(json_dec(&grades[0]["computed_score"]), dec("80"));
+        assert_eq!(json_dec(&grades[0]["score"]), dec("80"));
+        assert!(!grades[0]["locked_at"].is_null());
+
+        let override_uri = format!("/courses/{course_id}/final-grades/{}/override", student.id);
+        let body = json!({ "term": TERM, "score": 85, "reason": "补考成绩" });
+        let (status, _) = app
+            .send(Method::PUT, &override_uri, &teacher, Some(body.clone()))
+            .await;
+        assert_eq!(status, StatusCode::CONFLICT);
+
+        let unlock_uri = format!("/courses/{course_id}/final-grades/unlock");
+        let (status, _) = app
+            .send(
+                Method::POST,
+                &unlock_uri,
+                &teacher,
+                Some(json!({ "term": TERM })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
+        let (status, _) = app
+            .send(
+                Method::POST,
+                &unlock_uri,
+                &admin,
+                Some(json!({ "term": TERM })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, _) = app
+            .send(Method::PUT, &override_uri, &teacher, Some(body))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        let grades = saved_final_grades(&app, &teacher, course_id).await;
+        assert_eq!(json_dec(&grades[0]["override_score"]), dec("85"));
+        assert_eq!(json_dec(&grades[0]["score"]), dec("85"));
+        assert_eq!(grades[0]["override_reason"], "补考成绩");
+        assert!(grades[0]["locked_at"].is_null());
+    }
+
+    #[sqlx::test]
+    async fn exam_record_score_cannot_exceed_exam_max_score(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+        let teacher = app.user("teacher").await;
+        let student = app.user("student").await;
+        let course_id = app.course(&teacher).await;
+        app.enroll(course_id, &student).await;
+
+        let (_, exam) = app
+            .send(
+                Method::POST,
+                "/exams",
+                &teacher,
+                Some(json!({ "title": "单元测验", "max_score": 50 })),
+            )
+            .await;
+        let mut record = json!({
+            "student_id": student.id,
+            "exam_id": exam["id"],
+            "score": 60,
+            "completion_date": date!(2025 - 04 - 10),
+        });
+        let (status, body) = app
+            .send(
+                Method::POST,
+                "/exam-records",
+                &teacher,
+                Some(record.clone()),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["details"][0]["field"], "score");
+
+        record["score"] = json!(45);
+        let (status, created) = app
+            .send(Method::POST, "/exam-records", &teacher, Some(record))
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let id = created["id"].as_str().unwrap();
+        let (status, _) = app
+            .send(
+                Method::PUT,
+                &format!("/exam-records/{id}"),
+                &teacher,
+                Some(json!({ "score": 50.5 })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+    }
+}
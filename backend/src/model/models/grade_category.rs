@@ -0,0 +1,196 @@
+//! 成绩类别模型
+//!
+//! 成绩类别是课程在某个学期参与总评的一类成绩，如作业、测验、考试，
+//! 作业任务和试卷通过 `category_id` 归入类别，类别按权重计入总评
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, postgres::PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::validate;
+
+/// 成绩类别结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeCategory {
+    /// 类别ID
+    pub id: Uuid,
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 学期
+    pub term: String,
+    /// 类别名称
+    pub name: String,
+    /// 权重（百分比）
+    pub weight: Decimal,
+    /// 去掉最低的项数
+    pub drop_lowest: i32,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 创建成绩类别的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateGradeCategoryRequest {
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 学期
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 50, message = "学期不能超过50个字符")
+    )]
+    pub term: String,
+    /// 类别名称
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 50, message = "类别名称不能超过50个字符")
+    )]
+    pub name: String,
+    /// 权重（百分比）
+    #[validate(custom(function = "validate::positive_percent"))]
+    pub weight: Decimal,
+    /// 去掉最低的项数
+    #[serde(default)]
+    #[validate(range(min = 0, max = 100, message = "去掉的项数必须在0到100之间"))]
+    pub drop_lowest: i32,
+}
+
+/// 更新成绩类别的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateGradeCategoryRequest {
+    /// 类别名称
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 50, message = "类别名称不能超过50个字符")
+    )]
+    pub name: Option<String>,
+    /// 权重（百分比）
+    #[validate(custom(function = "validate::positive_percent"))]
+    pub weight: Option<Decimal>,
+    /// 去掉最低的项数
+    #[validate(range(min = 0, max = 100, message = "去掉的项数必须在0到100之间"))]
+    pub drop_lowest: Option<i32>,
+}
+
+/// 查询成绩类别的条件
+#[derive(Debug, Default, Deserialize)]
+pub struct GradeCategoryFilter {
+    /// 学期
+    pub term: Option<String>,
+}
+
+impl GradeCategory {
+    /// 创建成绩类别
+    pub async fn create(pool: &PgPool, req: CreateGradeCategoryRequest) -> Result<Self, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let category = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO grade_categories (id, course_id, term, name, weight, drop_lowest, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+            RETURNING id, course_id, term, name, weight, drop_lowest, created_at, updated_at
+            "#,
+            id,
+            req.course_id,
+            req.term,
+            req.name,
+            req.weight,
+            req.drop_lowest,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(category)
+    }
+
+    /// 根据ID查找成绩类别
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let category = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, course_id, term, name, weight, drop_lowest, created_at, updated_at
+            FROM grade_categories
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(category)
+    }
+
+    /// 获取课程的成绩类别，按学期倒序、名称排列，`term` 为空时不限学期
+    pub async fn find_by_course(
+        pool: &PgPool,
+        course_id: Uuid,
+        term: Option<&str>,
+    ) -> Result<Vec<Self>, Error> {
+        let categories = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, course_id, term, name, weight, drop_lowest, created_at, updated_at
+            FROM grade_categories
+            WHERE course_id = $1 AND ($2::text IS NULL OR term = $2)
+            ORDER BY term DESC, name ASC, id ASC
+            "#,
+            course_id,
+            term
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(categories)
+    }
+
+    /// 更新成绩类别
+    pub async fn update(
+        pool: &PgPool,
+        id: Uuid,
+        req: UpdateGradeCategoryRequest,
+    ) -> Result<Self, Error> {
+        let category = Self::find_by_id(pool, id)
+            .await?
+            .ok_or(Error::RowNotFound)?;
+
+        let name = req.name.unwrap_or(category.name);
+        let weight = req.weight.unwrap_or(category.weight);
+        let drop_lowest = req.drop_lowest.unwrap_or(category.drop_lowest);
+        let now = OffsetDateTime::now_utc();
+
+        let category = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE grade_categories
+            SET name = $1, weight = $2, drop_lowest = $3, updated_at = $4
+            WHERE id = $5
+            RETURNING id, course_id, term, name, weight, drop_lowest, created_at, updated_at
+            "#,
+            name,
+            weight,
+            drop_lowest,
+            now,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(category)
+    }
+
+    /// 删除成绩类别，其中的作业任务和试卷不再归入类别
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+        let result = sqlx::query!("DELETE FROM grade_categories WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,120 @@
+//! 免做模型
+//!
+//! 记录学生免做的作业任务或试卷，免做的项目不计入总评
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, postgres::PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::validate;
+
+/// 免做记录结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GradeExcusal {
+    /// 免做记录ID
+    pub id: Uuid,
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 作业任务ID，与试卷ID二选一
+    pub assignment_id: Option<Uuid>,
+    /// 试卷ID，与作业任务ID二选一
+    pub exam_id: Option<Uuid>,
+    /// 免做原因
+    pub reason: String,
+    /// 登记免做的用户ID
+    pub excused_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+}
+
+/// 登记免做的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateGradeExcusalRequest {
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 作业任务ID
+    pub assignment_id: Option<Uuid>,
+    /// 试卷ID
+    pub exam_id: Option<Uuid>,
+    /// 免做原因
+    #[validate(custom(function = "validate::not_blank"))]
+    pub reason: String,
+}
+
+impl GradeExcusal {
+    /// 登记免做
+    pub async fn create(
+        pool: &PgPool,
+        req: CreateGradeExcusalRequest,
+        excused_by: Uuid,
+    ) -> Result<Self, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let excusal = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO grade_excusals (id, student_id, assignment_id, exam_id, reason, excused_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, student_id, assignment_id, exam_id, reason, excused_by, created_at
+            "#,
+            id,
+            req.student_id,
+            req.assignment_id,
+            req.exam_id,
+            req.reason,
+            excused_by,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(excusal)
+    }
+
+    /// 获取学生的免做记录，按登记时间倒序排列
+    pub async fn find_by_student(pool: &PgPool, student_id: Uuid) -> Result<Vec<Self>, Error> {
+        let excusals = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, student_id, assignment_id, exam_id, reason, excused_by, created_at
+            FROM grade_excusals
+            WHERE student_id = $1
+            ORDER BY created_at DESC, id
+            "#,
+            student_id
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(excusals)
+    }
+
+    /// 根据ID查找免做记录
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let excusal = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, student_id, assignment_id, exam_id, reason, excused_by, created_at
+            FROM grade_excusals
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(excusal)
+    }
+
+    /// 撤销免做
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+        let result = sqlx::query!("DELETE FROM grade_excusals WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,235 @@
+//! 成绩册模型
+//!
+//! 以学生为行、试卷和作业为列汇总成绩，供导出为表格文件
+
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use sqlx::{Error, postgres::PgPool};
+use std::collections::HashMap;
+use time::Date;
+use uuid::Uuid;
+
+use crate::model::date_format;
+use crate::model::sheet::{Cell, SheetFormat, Table};
+
+/// 成绩册固定列的表头
+const STUDENT_HEADERS: [&str; 3] = ["用户名", "姓名", "年级"];
+
+/// 导出成绩册的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize)]
+pub struct GradebookFilter {
+    /// 学生年级
+    pub grade: Option<i32>,
+    /// 课程ID，只包含有该课程上课或考勤记录的学生
+    pub course_id: Option<Uuid>,
+    /// 考试完成日期和作业提交日期起始（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    /// 考试完成日期和作业提交日期截止（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+    /// 导出格式，默认为 CSV
+    #[serde(default)]
+    pub format: SheetFormat,
+}
+
+/// 成绩册中的学生
+#[derive(Debug, Clone)]
+struct GradebookStudent {
+    id: Uuid,
+    username: String,
+    display_name: Option<String>,
+    grade: Option<i32>,
+}
+
+/// 成绩册的一列
+#[derive(Debug, Clone)]
+enum GradebookColumn {
+    /// 试卷
+    Exam { id: Uuid, title: String },
+    /// 作业，按标题归为一列
+    Homework { title: String },
+}
+
+impl GradebookColumn {
+    /// 列的表头
+    fn header(&self) -> String {
+        match self {
+            GradebookColumn::Exam { title, .. } => format!("[试卷] {}", title),
+            GradebookColumn::Homework { title } => format!("[作业] {}", title),
+        }
+    }
+}
+
+/// 成绩册
+///
+/// 同一学生同一试卷有多条记录时取最近完成的一次，同一标题的作业取最近提交的一次
+#[derive(Debug, Clone, Default)]
+pub struct Gradebook {
+    /// 学生，按年级和用户名排列
+    students: Vec<GradebookStudent>,
+    /// 列，试卷在前、作业在后，各自按最早的日期排列
+    columns: Vec<GradebookColumn>,
+    /// 试卷分数，键为（学生ID，试卷ID）
+    exam_scores: HashMap<(Uuid, Uuid), Decimal>,
+    /// 作业评分，键为（学生ID，作业标题）
+    homework_grades: HashMap<(Uuid, String), String>,
+}
+
+impl Gradebook {
+    /// 汇总成绩册
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn build(
+        pool: &PgPool,
+        filter: &GradebookFilter,
+        students: Option<&[Uuid]>,
+    ) -> Result<Self, Error> {
+        let students = sqlx::query_as!(
+            GradebookStudent,
+            r#"
+            SELECT id, username, display_name, grade
+            FROM users u
+            WHERE role = 'student'
+              AND ($1::int IS NULL OR grade = $1)
+              AND ($2::uuid IS NULL
+                   OR EXISTS (SELECT 1 FROM course_records cr WHERE cr.student_id = u.id AND cr.course_id = $2)
+                   OR EXISTS (SELECT 1 FROM attendance a WHERE a.student_id = u.id AND a.course_id = $2))
+              AND ($3::uuid[] IS NULL OR id = ANY($3))
+            ORDER BY grade NULLS LAST, username
+            "#,
+            filter.grade,
+            filter.course_id,
+            students
+        )
+        .fetch_all(pool)
+        .await?;
+        let student_ids: Vec<Uuid> = students.iter().map(|student| student.id).collect();
+
+        let exams = sqlx::query!(
+            r#"
+            SELECT DISTINCT ON (er.student_id, er.exam_id)
+                   er.student_id, er.exam_id, e.title, er.score, er.completion_date
+            FROM exam_records er
+            JOIN exams e ON e.id = er.exam_id
+            WHERE er.student_id = ANY($1)
+              AND ($2::date IS NULL OR er.completion_date >= $2)
+              AND ($3::date IS NULL OR er.completion_date <= $3)
+            ORDER BY er.student_id, er.exam_id, er.completion_date DESC, er.created_at DESC
+            "#,
+            &student_ids,
+            filter.start_date,
+            filter.end_date
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let homeworks = sqlx::query!(
+            r#"
+            SELECT DISTINCT ON (s.student_id, a.title)
+                   s.student_id, a.title, s.grade, s.submission_date AS "submission_date!"
+            FROM submissions s
+            JOIN assignments a ON a.id = s.assignment_id
+            WHERE s.student_id = ANY($1)
+              AND s.submission_date IS NOT NULL
+              AND ($2::date IS NULL OR s.submission_date >= $2)
+              AND ($3::date IS NULL OR s.submission_date <= $3)
+            ORDER BY s.student_id, a.title, s.submission_date DESC, s.created_at DESC
+            "#,
+            &student_ids,
+            filter.start_date,
+            filter.end_date
+        )
+        .fetch_all(pool)
+        .await?;
+
+        // 各列按最早的日期排列
+        let mut exam_columns: HashMap<Uuid, (Date, String)> = HashMap::new();
+        let mut exam_scores = HashMap::new();
+        for exam in exams {
+            let column = exam_columns
+                .entry(exam.exam_id)
+                .or_insert((exam.completion_date, exam.title));
+            column.0 = column.0.min(exam.completion_date);
+            if let Some(score) = exam.score {
+                exam_scores.insert((exam.student_id, exam.exam_id), score);
+            }
+        }
+
+        let mut homework_columns: HashMap<String, Date> = HashMap::new();
+        let mut homework_grades = HashMap::new();
+        for homework in homeworks {
+            let date = homework_columns
+                .entry(homework.title.clone())
+                .or_insert(homework.submission_date);
+            *date = (*date).min(homework.submission_date);
+            if let Some(grade) = homework.grade {
+                homework_grades.insert((homework.student_id, homework.title), grade);
+            }
+        }
+
+        let mut exam_columns: Vec<_> = exam_columns.into_iter().collect();
+        exam_columns.sort_by(|(a_id, (a_date, a_title)), (b_id, (b_date, b_title))| {
+            (a_date, a_title, a_id).cmp(&(b_date, b_title, b_id))
+        });
+        let mut homework_columns: Vec<_> = homework_columns.into_iter().collect();
+        homework_columns.sort_by(|(a_title, a_date), (b_title, b_date)| {
+            (a_date, a_title).cmp(&(b_date, b_title))
+        });
+
+        let columns = exam_columns
+            .into_iter()
+            .map(|(id, (_, title))| GradebookColumn::Exam { id, title })
+            .chain(
+                homework_columns
+                    .into_iter()
+                    .map(|(title, _)| GradebookColumn::Homework { title }),
+            )
+            .collect();
+
+        Ok(Self {
+            students,
+            columns,
+            exam_scores,
+            homework_grades,
+        })
+    }
+
+    /// 转换为导出表格，没有成绩的单元格为空
+    pub fn to_table(&self) -> Table {
+        let headers = STUDENT_HEADERS
+            .iter()
+            .map(|header| header.to_string())
+            .chain(self.columns.iter().map(GradebookColumn::header))
+            .collect();
+
+        let rows = self
+            .students
+            .iter()
+            .map(|student| {
+                let mut row = vec![
+                    Cell::Text(student.username.clone()),
+                    student.display_name.clone().map_or(Cell::Empty, Cell::Text),
+                    student
+                        .grade
+                        .map_or(Cell::Empty, |grade| Cell::Number(Decimal::from(grade))),
+                ];
+                row.extend(self.columns.iter().map(|column| {
+                    match column {
+                        GradebookColumn::Exam { id, .. } => self
+                            .exam_scores
+                            .get(&(student.id, *id))
+                            .map_or(Cell::Empty, |score| Cell::Number(*score)),
+                        GradebookColumn::Homework { title } => self
+                            .homework_grades
+                            .get(&(student.id, title.clone()))
+                            .map_or(Cell::Empty, |grade| Cell::Text(grade.clone())),
+                    }
+                }));
+                row
+            })
+            .collect();
+
+        Table { headers, rows }
+    }
+}
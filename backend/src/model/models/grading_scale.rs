@@ -0,0 +1,509 @@
+//! 评分等级制模型
+//!
+//! 每个课程可以设置一种评分等级制，评分时按得分率把数字分数换算为显示的等级。
+//! 没有设置的课程按百分制显示
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgConnection, PgExecutor, postgres::PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::validate;
+
+/// 评分等级制类型枚举
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScaleKind {
+    /// 百分制，显示得分率
+    #[default]
+    Percentage,
+    /// A-F 字母等级
+    Letter,
+    /// 优/良/中/差
+    Tier,
+    /// 通过/不通过
+    PassFail,
+}
+
+impl AsRef<str> for ScaleKind {
+    fn as_ref(&self) -> &str {
+        match self {
+            ScaleKind::Percentage => "percentage",
+            ScaleKind::Letter => "letter",
+            ScaleKind::Tier => "tier",
+            ScaleKind::PassFail => "pass_fail",
+        }
+    }
+}
+
+impl ScaleKind {
+    /// 未指定等级时使用的默认等级，百分制没有等级
+    pub fn default_levels(self) -> Vec<GradeLevel> {
+        let levels: &[(&str, i64)] = match self {
+            ScaleKind::Percentage => &[],
+            ScaleKind::Letter => &[("A", 90), ("B", 80), ("C", 70), ("D", 60), ("F", 0)],
+            ScaleKind::Tier => &[("优", 90), ("良", 75), ("中", 60), ("差", 0)],
+            ScaleKind::PassFail => &[("通过", 60), ("不通过", 0)],
+        };
+        levels
+            .iter()
+            .map(|&(label, min_percent)| GradeLevel {
+                label: label.to_string(),
+                min_percent: Decimal::from(min_percent),
+            })
+            .collect()
+    }
+}
+
+/// 等级及其最低得分率
+#[derive(Debug, Clone, Serialize, Deserialize, Validate)]
+pub struct GradeLevel {
+    /// 等级名称
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 10, message = "等级名称不能超过10个字符")
+    )]
+    pub label: String,
+    /// 最低得分率（百分比）
+    #[validate(custom(function = "validate::percent"))]
+    pub min_percent: Decimal,
+}
+
+/// 评分等级制结构体
+#[derive(Debug, Clone, Serialize)]
+pub struct GradingScale {
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 等级制类型
+    pub kind: String,
+    /// 等级，按最低得分率从高到低排列，百分制为空
+    pub levels: Vec<GradeLevel>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 设置评分等级制的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetGradingScaleRequest {
+    /// 等级制类型
+    pub kind: ScaleKind,
+    /// 等级，省略时使用该类型的默认等级
+    #[validate(nested)]
+    pub levels: Option<Vec<GradeLevel>>,
+}
+
+/// 分数换算的查询参数
+#[derive(Debug, Deserialize, Validate)]
+pub struct ConvertScoreParams {
+    /// 分数
+    #[validate(custom(function = "validate::score"))]
+    pub score: Decimal,
+    /// 满分，默认为100
+    #[validate(custom(function = "validate::positive_score"))]
+    pub max_score: Option<Decimal>,
+}
+
+/// 分数换算结果
+#[derive(Debug, Clone, Serialize)]
+pub struct ScaleValue {
+    /// 等级制类型
+    pub kind: String,
+    /// 得分率（百分比），保留两位小数
+    pub percent: Decimal,
+    /// 按评分等级制显示的等级
+    pub value: String,
+}
+
+impl GradingScale {
+    /// 设置课程的评分等级制，已有时覆盖原有的类型和等级
+    ///
+    /// 需要在事务中调用，以便等级制和等级一起写入
+    pub async fn upsert(
+        conn: &mut PgConnection,
+        course_id: Uuid,
+        kind: ScaleKind,
+        levels: &[GradeLevel],
+    ) -> Result<Self, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let scale = sqlx::query!(
+            r#"
+            INSERT INTO grading_scales (course_id, kind, created_at, updated_at)
+            VALUES ($1, $2, $3, $3)
+            ON CONFLICT (course_id) DO UPDATE
+            SET kind = EXCLUDED.kind, updated_at = EXCLUDED.updated_at
+            RETURNING course_id, kind, created_at, updated_at
+            "#,
+            course_id,
+            kind.as_ref(),
+            now
+        )
+        .fetch_one(&mut *conn)
+        .await?;
+
+        sqlx::query!("DELETE FROM grade_levels WHERE course_id = $1", course_id)
+            .execute(&mut *conn)
+            .await?;
+        let labels: Vec<String> = levels.iter().map(|level| level.label.clone()).collect();
+        let min_percents: Vec<Decimal> = levels.iter().map(|level| level.min_percent).collect();
+        sqlx::query!(
+            r#"
+            INSERT INTO grade_levels (course_id, label, min_percent)
+            SELECT $1, * FROM UNNEST($2::varchar[], $3::decimal[])
+            "#,
+            course_id,
+            &labels,
+            &min_percents
+        )
+        .execute(&mut *conn)
+        .await?;
+
+        Ok(Self {
+            levels: Self::levels(&mut *conn, scale.course_id).await?,
+            course_id: scale.course_id,
+            kind: scale.kind,
+            created_at: scale.created_at,
+            updated_at: scale.updated_at,
+        })
+    }
+
+    /// 获取课程的评分等级制
+    pub async fn find_by_course(pool: &PgPool, course_id: Uuid) -> Result<Option<Self>, Error> {
+        let scale = sqlx::query!(
+            r#"
+            SELECT course_id, kind, created_at, updated_at
+            FROM grading_scales
+            WHERE course_id = $1
+            "#,
+            course_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        let Some(scale) = scale else {
+            return Ok(None);
+        };
+        Ok(Some(Self {
+            levels: Self::levels(pool, scale.course_id).await?,
+            course_id: scale.course_id,
+            kind: scale.kind,
+            created_at: scale.created_at,
+            updated_at: scale.updated_at,
+        }))
+    }
+
+    /// 获取作业任务所属课程的评分等级制
+    pub async fn find_by_assignment(
+        pool: &PgPool,
+        assignment_id: Uuid,
+    ) -> Result<Option<Self>, Error> {
+        let course_id = sqlx::query_scalar!(
+            r#"
+            SELECT g.course_id
+            FROM grading_scales g
+            JOIN assignments a ON a.course_id = g.course_id
+            WHERE a.id = $1
+            "#,
+            assignment_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        match course_id {
+            Some(course_id) => Self::find_by_course(pool, course_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// 获取课程的等级，按最低得分率从高到低排列
+    async fn levels<'e, E: PgExecutor<'e>>(
+        executor: E,
+        course_id: Uuid,
+    ) -> Result<Vec<GradeLevel>, Error> {
+        let levels = sqlx::query_as!(
+            GradeLevel,
+            r#"
+            SELECT label, min_percent
+            FROM grade_levels
+            WHERE course_id = $1
+            ORDER BY min_percent DESC
+            "#,
+            course_id
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(levels)
+    }
+
+    /// 删除课程的评分等级制
+    pub async fn delete<'e, E: PgExecutor<'e>>(
+        executor: E,
+        course_id: Uuid,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!("DELETE FROM grading_scales WHERE course_id = $1", course_id)
+            .execute(executor)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 按评分等级制换算分数，没有等级制时按百分制，没有满分时按100分计
+    pub fn convert(scale: Option<&Self>, score: Decimal, max_score: Option<Decimal>) -> ScaleValue {
+        let max_score = max_score.unwrap_or(Decimal::ONE_HUNDRED);
+        let percent = (score * Decimal::ONE_HUNDRED / max_score).round_dp(2);
+
+        let level = scale.and_then(|scale| {
+            scale
+                .levels
+                .iter()
+                .find(|level| percent >= level.min_percent)
+                .or(scale.levels.last())
+        });
+        let value = match level {
+            Some(level) => level.label.clone(),
+            None => format!("{}%", percent.normalize()),
+        };
+        let kind = scale.map_or(ScaleKind::Percentage.as_ref(), |scale| scale.kind.as_str());
+        ScaleValue {
+            kind: kind.to_string(),
+            percent,
+            value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{TestApp, dec, json_dec};
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+
+    fn scale(kind: ScaleKind, levels: Vec<GradeLevel>) -> GradingScale {
+        GradingScale {
+            course_id: Uuid::nil(),
+            kind: kind.as_ref().to_string(),
+            levels,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    fn convert(scale: Option<&GradingScale>, score: &str, max_score: Option<&str>) -> ScaleValue {
+        GradingScale::convert(scale, dec(score), max_score.map(dec))
+    }
+
+    #[test]
+    fn convert_without_scale_shows_percentage() {
+        let value = convert(None, "87.5", None);
+        assert_eq!(value.kind, "percentage");
+        assert_eq!(value.percent, dec("87.5"));
+        assert_eq!(value.value, "87.5%");
+    }
+
+    #[test]
+    fn convert_uses_max_score() {
+        let value = convert(None, "120", Some("150"));
+        assert_eq!(value.percent, dec("80"));
+        assert_eq!(value.value, "80%");
+    }
+
+    #[test]
+    fn convert_rounds_percent_to_two_decimals() {
+        let value = convert(None, "2", Some("3"));
+        assert_eq!(value.percent, dec("66.67"));
+        assert_eq!(value.value, "66.67%");
+    }
+
+    #[test]
+    fn convert_drops_trailing_zeros_in_percentage() {
+        let value = convert(None, "100.00", None);
+        assert_eq!(value.value, "100%");
+    }
+
+    #[test]
+    fn convert_letter_boundaries() {
+        let letter = scale(ScaleKind::Letter, ScaleKind::Letter.default_levels());
+        let grade = |score| convert(Some(&letter), score, None).value;
+        assert_eq!(grade("100"), "A");
+        assert_eq!(grade("90"), "A");
+        assert_eq!(grade("89.99"), "B");
+        assert_eq!(grade("80"), "B");
+        assert_eq!(grade("60"), "D");
+        assert_eq!(grade("59.99"), "F");
+        assert_eq!(grade("0"), "F");
+    }
+
+    #[test]
+    fn convert_pass_fail_boundary() {
+        let pass_fail = scale(ScaleKind::PassFail, ScaleKind::PassFail.default_levels());
+        let value = convert(Some(&pass_fail), "30", Some("50"));
+        assert_eq!(value.kind, "pass_fail");
+        assert_eq!(value.percent, dec("60"));
+        assert_eq!(value.value, "通过");
+        assert_eq!(
+            convert(Some(&pass_fail), "29.99", Some("50")).value,
+            "不通过"
+        );
+    }
+
+    #[test]
+    fn convert_falls_back_to_lowest_level() {
+        let levels = vec![
+            GradeLevel {
+                label: "优".to_string(),
+                min_percent: dec("90"),
+            },
+            GradeLevel {
+                label: "合格".to_string(),
+                min_percent: dec("60"),
+            },
+        ];
+        let tier = scale(ScaleKind::Tier, levels);
+        assert_eq!(convert(Some(&tier), "10", None).value, "合格");
+    }
+
+    #[test]
+    fn convert_percentage_scale_without_levels() {
+        let percentage = scale(ScaleKind::Percentage, Vec::new());
+        let value = convert(Some(&percentage), "45", Some("50"));
+        assert_eq!(value.kind, "percentage");
+        assert_eq!(value.value, "90%");
+    }
+
+    #[sqlx::test]
+    async fn grading_converts_score_with_course_scale(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+        let teacher = app.user("teacher").await;
+        let student = app.user("student").await;
+        let course_id = app.course(&teacher).await;
+        app.enroll(course_id, &student).await;
+
+        let (status, _) = app
+            .send(
+                Method::PUT,
+                &format!("/courses/{course_id}/grading-scale"),
+                &teacher,
+                Some(json!({ "kind": "tier" })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (status, converted) = app
+            .send(
+                Method::GET,
+                &format!("/courses/{course_id}/grading-scale/convert?score=36&max_score=40"),
+                &teacher,
+                None,
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(converted["kind"], "tier");
+        assert_eq!(json_dec(&converted["percent"]), dec("90"));
+        assert_eq!(converted["value"], "优");
+
+        let (_, assignment) = app
+            .send(
+                Method::POST,
+                "/assignments",
+                &teacher,
+                Some(json!({ "course_id": course_id, "title": "单元测验", "max_score": 40 })),
+            )
+            .await;
+        let (_, submission) = app
+            .send(
+                Method::POST,
+                "/submissions",
+                &teacher,
+                Some(json!({ "assignment_id": assignment["id"], "student_id": student.id })),
+            )
+            .await;
+
+        // 按作业任务的满分40分换算，30分的得分率为75%
+        let id = submission["id"].as_str().unwrap();
+        let (status, graded) = app
+            .send(
+                Method::PUT,
+                &format!("/submissions/{id}/grade"),
+                &teacher,
+                Some(json!({ "score": 30 })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(graded["grade"], "良");
+
+        let (_, saved) = app
+            .send(Method::GET, &format!("/submissions/{id}"), &teacher, None)
+            .await;
+        assert_eq!(saved["grade"], "良");
+        assert_eq!(json_dec(&saved["score"]), dec("30"));
+    }
+
+    #[sqlx::test]
+    async fn grading_rejects_grade_together_with_score(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+        let teacher = app.user("teacher").await;
+        let student = app.user("student").await;
+        let course_id = app.course(&teacher).await;
+        app.enroll(course_id, &student).await;
+
+        let (_, assignment) = app
+            .send(
+                Method::POST,
+                "/assignments",
+                &teacher,
+                Some(json!({ "course_id": course_id, "title": "单元测验" })),
+            )
+            .await;
+        let (_, submission) = app
+            .send(
+                Method::POST,
+                "/submissions",
+                &teacher,
+                Some(json!({ "assignment_id": assignment["id"], "student_id": student.id })),
+            )
+            .await;
+        let id = submission["id"].as_str().unwrap();
+
+        for uri in [
+            format!("/submissions/{id}/grade"),
+            format!("/homework/{id}/grade"),
+        ] {
+            let (status, body) = app
+                .send(
+                    Method::PUT,
+                    &uri,
+                    &teacher,
+                    Some(json!({ "grade": "A", "score": 70 })),
+                )
+                .await;
+            assert_eq!(status, StatusCode::BAD_REQUEST);
+            assert_eq!(body["details"][0]["field"], "grade");
+        }
+
+        // 只填写评分时按原样保存，不按已有的分数重新换算
+        app.send(
+            Method::PUT,
+            &format!("/submissions/{id}/grade"),
+            &teacher,
+            Some(json!({ "score": 70 })),
+        )
+        .await;
+        let (status, graded) = app
+            .send(
+                Method::PUT,
+                &format!("/homework/{id}/grade"),
+                &teacher,
+                Some(json!({ "grade": "优秀" })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(graded["grade"], "优秀");
+        assert_eq!(json_dec(&graded["score"]), dec("70"));
+    }
+}
@@ -1,17 +1,37 @@
 //! 作业模型
 //!
-//! 提供作业的数据结构和数据库操作方法
+//! 兼容原有的作业接口：一份作业对应一个已提交的 [`Submission`] 及其所属作业任务的标题和要求，
+//! 作业ID即提交ID。尚未提交的作业不在其中
 
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::{Error, postgres::PgPool};
+use sqlx::{Error, FromRow, Postgres, QueryBuilder, postgres::PgPool};
 use time::{Date, OffsetDateTime};
 use uuid::Uuid;
+use validator::Validate;
+
+use super::submission::{self, Submission, SubmissionStatus};
+use crate::model::date_format;
+use crate::model::query::{self, Page, PageParams, SortField, SortOrder};
+use crate::model::validate;
+
+/// 已提交作业的查询，列与 [`Homework`] 对应，用作 FROM 子句
+const HOMEWORK: &str = r#"
+    (SELECT s.id, s.assignment_id, s.student_id, a.title, a.instructions AS description, s.file_path,
+            s.submission_date, a.due_date, s.status, s.days_late, s.grade, s.raw_score, s.late_penalty,
+            s.score, s.feedback, a.teacher_id, s.created_at, s.updated_at
+     FROM submissions s
+     JOIN assignments a ON a.id = s.assignment_id
+     WHERE s.submission_date IS NOT NULL) AS homework
+"#;
 
 /// 作业结构体
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Homework {
-    /// 作业ID
+    /// 作业ID，即提交ID
     pub id: Uuid,
+    /// 作业任务ID
+    pub assignment_id: Uuid,
     /// 学生ID
     pub student_id: Uuid,
     /// 作业标题
@@ -22,8 +42,20 @@ pub struct Homework {
     pub file_path: Option<String>,
     /// 提交日期
     pub submission_date: Date,
-    /// 评分
+    /// 截止日期
+    pub due_date: Option<Date>,
+    /// 提交状态
+    pub status: String,
+    /// 迟交天数，按时提交为0
+    pub days_late: i32,
+    /// 评分，有分数时为按评分等级制换算的等级
     pub grade: Option<String>,
+    /// 扣分前的分数
+    pub raw_score: Option<Decimal>,
+    /// 迟交扣除的百分比
+    pub late_penalty: Option<Decimal>,
+    /// 扣分后的分数
+    pub score: Option<Decimal>,
     /// 反馈
     pub feedback: Option<String>,
     /// 教师ID
@@ -35,19 +67,25 @@ pub struct Homework {
 }
 
 /// 创建作业的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateHomeworkRequest {
     /// 学生ID
     pub student_id: Uuid,
     /// 作业标题
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 200, message = "作业标题不能超过200个字符")
+    )]
     pub title: String,
     /// 作业描述
     pub description: Option<String>,
-    /// 作业文件路径
-    pub file_path: Option<String>,
     /// 提交日期
     pub submission_date: Date,
+    /// 截止日期
+    #[serde(default)]
+    pub due_date: Option<Date>,
     /// 评分
+    #[validate(length(max = 10, message = "评分不能超过10个字符"))]
     pub grade: Option<String>,
     /// 反馈
     pub feedback: Option<String>,
@@ -56,61 +94,158 @@ pub struct CreateHomeworkRequest {
 }
 
 /// 更新作业的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateHomeworkRequest {
-    /// 作业标题
+    /// 作业标题，属于作业任务，作业任务不共享时才能在此修改
+    #[validate(
+        custom(function = "validate::not_blank"),
+        length(max = 200, message = "作业标题不能超过200个字符")
+    )]
     pub title: Option<String>,
-    /// 作业描述
+    /// 作业描述，属于作业任务，作业任务不共享时才能在此修改
     pub description: Option<String>,
-    /// 作业文件路径
-    pub file_path: Option<String>,
     /// 提交日期
     pub submission_date: Option<Date>,
+    /// 截止日期，属于作业任务，作业任务不共享时才能在此修改
+    #[serde(default)]
+    pub due_date: Option<Date>,
     /// 评分
+    #[validate(length(max = 10, message = "评分不能超过10个字符"))]
     pub grade: Option<String>,
+    /// 分数，按课程的迟交扣分规则扣分后记录，评分按课程的评分等级制换算
+    #[validate(custom(function = "validate::score"))]
+    pub score: Option<Decimal>,
     /// 反馈
     pub feedback: Option<String>,
+    /// 教师ID，属于作业任务，作业任务不共享时才能在此修改
+    pub teacher_id: Option<Uuid>,
+}
+
+impl UpdateHomeworkRequest {
+    /// 请求中填写的作业任务字段，这些字段由同一作业任务的所有提交共享
+    pub fn assignment_fields(&self) -> Vec<&'static str> {
+        [
+            ("title", self.title.is_some()),
+            ("description", self.description.is_some()),
+            ("due_date", self.due_date.is_some()),
+            ("teacher_id", self.teacher_id.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, present)| present.then_some(field))
+        .collect()
+    }
+}
+
+/// 作业的可排序字段
+#[derive(Debug, Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HomeworkSort {
+    /// 提交日期
+    #[default]
+    SubmissionDate,
+    /// 作业标题
+    Title,
+    /// 评分
+    Grade,
+    /// 创建时间
+    CreatedAt,
+}
+
+impl SortField for HomeworkSort {
+    fn column(self) -> &'static str {
+        match self {
+            HomeworkSort::SubmissionDate => "submission_date",
+            HomeworkSort::Title => "title",
+            HomeworkSort::Grade => "grade",
+            HomeworkSort::CreatedAt => "created_at",
+        }
+    }
+}
+
+/// 查询作业的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize)]
+pub struct HomeworkFilter {
+    /// 学生ID
+    pub student_id: Option<Uuid>,
     /// 教师ID
     pub teacher_id: Option<Uuid>,
+    /// 提交日期起始（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    /// 提交日期截止（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+    /// 标题关键词
+    pub title: Option<String>,
+    /// 排序字段，默认为提交日期
+    #[serde(default)]
+    pub sort_by: HomeworkSort,
+    /// 排序方向，默认为降序
+    #[serde(default)]
+    pub order: SortOrder,
 }
 
 impl Homework {
-    /// 创建新作业
+    /// 创建新作业，即创建一个没有课程的作业任务及该学生已提交的作业
     pub async fn create(pool: &PgPool, req: CreateHomeworkRequest) -> Result<Self, Error> {
         let id = Uuid::new_v4();
         let now = OffsetDateTime::now_utc();
+        let days_late = submission::days_late(req.submission_date, req.due_date);
+        let status = if req.grade.is_some() {
+            SubmissionStatus::Graded
+        } else if days_late > 0 {
+            SubmissionStatus::Late
+        } else {
+            SubmissionStatus::Submitted
+        };
 
-        let homework = sqlx::query_as!(Self,
+        let mut tx = pool.begin().await?;
+        sqlx::query!(
             r#"
-            INSERT INTO homework (id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            RETURNING id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
+            INSERT INTO assignments (id, title, instructions, due_date, teacher_id, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $6)
             "#,
             id,
-            req.student_id,
             req.title,
             req.description,
-            req.file_path,
+            req.due_date,
+            req.teacher_id,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO submissions (id, assignment_id, student_id, status, submission_date, days_late, grade, feedback, created_at, updated_at)
+            VALUES ($1, $1, $2, $3, $4, $5, $6, $7, $8, $8)
+            "#,
+            id,
+            req.student_id,
+            status.as_ref(),
             req.submission_date,
+            days_late,
             req.grade,
             req.feedback,
-            req.teacher_id,
-            now,
             now
         )
-        .fetch_one(pool)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
 
-        Ok(homework)
+        Self::find_by_id(pool, id).await?.ok_or(Error::RowNotFound)
     }
 
     /// 根据ID查找作业
     pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
         let homework = sqlx::query_as!(Self,
             r#"
-            SELECT id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
-            FROM homework
-            WHERE id = $1
+            SELECT s.id, s.assignment_id, s.student_id, a.title, a.instructions AS description, s.file_path,
+                   s.submission_date AS "submission_date!", a.due_date, s.status, s.days_late, s.grade,
+                   s.raw_score, s.late_penalty, s.score, s.feedback, a.teacher_id, s.created_at, s.updated_at
+            FROM submissions s
+            JOIN assignments a ON a.id = s.assignment_id
+            WHERE s.id = $1 AND s.submission_date IS NOT NULL
             "#,
             id
         )
@@ -124,10 +259,13 @@ impl Homework {
     pub async fn find_by_student_id(pool: &PgPool, student_id: Uuid) -> Result<Vec<Self>, Error> {
         let homeworks = sqlx::query_as!(Self,
             r#"
-            SELECT id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
-            FROM homework
-            WHERE student_id = $1
-            ORDER BY submission_date DESC
+            SELECT s.id, s.assignment_id, s.student_id, a.title, a.instructions AS description, s.file_path,
+                   s.submission_date AS "submission_date!", a.due_date, s.status, s.days_late, s.grade,
+                   s.raw_score, s.late_penalty, s.score, s.feedback, a.teacher_id, s.created_at, s.updated_at
+            FROM submissions s
+            JOIN assignments a ON a.id = s.assignment_id
+            WHERE s.student_id = $1 AND s.submission_date IS NOT NULL
+            ORDER BY s.submission_date DESC
             "#,
             student_id
         )
@@ -137,39 +275,12 @@ impl Homework {
         Ok(homeworks)
     }
 
-    /// 根据教师ID查找作业
-    pub async fn find_by_teacher_id(pool: &PgPool, teacher_id: Uuid) -> Result<Vec<Self>, Error> {
-        let homeworks = sqlx::query_as!(Self,
-            r#"
-            SELECT id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
-            FROM homework
-            WHERE teacher_id = $1
-            ORDER BY submission_date DESC
-            "#,
-            teacher_id
-        )
-        .fetch_all(pool)
-        .await?;
-
-        Ok(homeworks)
-    }
-
-    /// 获取所有作业
-    pub async fn find_all(pool: &PgPool) -> Result<Vec<Self>, Error> {
-        let homeworks = sqlx::query_as!(Self,
-            r#"
-            SELECT id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
-            FROM homework
-            ORDER BY submission_date DESC
-            "#
-        )
-        .fetch_all(pool)
-        .await?;
-
-        Ok(homeworks)
-    }
-
     /// 更新作业
+    ///
+    /// 标题、描述、截止日期和教师写入所属的作业任务，作业任务共享时由调用方拒绝；其余字段写入提交，
+    /// 填写评分或分数时状态变为已评分。提交日期或截止日期变化时重新计算迟交天数。
+    /// 有分数时按 [`Submission::score_record`] 重新扣分，没有填写评分时取换算的等级；
+    /// 评分和分数不能同时填写，由调用方拒绝
     pub async fn update(
         pool: &PgPool,
         id: Uuid,
@@ -180,121 +291,255 @@ impl Homework {
         if let Some(homework) = homework {
             let title = req.title.unwrap_or(homework.title);
             let description = req.description.or(homework.description);
-            let file_path = req.file_path.or(homework.file_path);
+            let teacher_id = req.teacher_id.or(homework.teacher_id);
             let submission_date = req.submission_date.unwrap_or(homework.submission_date);
-            let grade = req.grade.or(homework.grade);
+            let due_date = req.due_date.or(homework.due_date);
+            let days_late = submission::days_late(submission_date, due_date);
+            let status = if req.grade.is_some() || req.score.is_some() {
+                SubmissionStatus::Graded.as_ref()
+            } else if homework.status == SubmissionStatus::Graded.as_ref()
+                || homework.status == SubmissionStatus::Returned.as_ref()
+            {
+                homework.status.as_str()
+            } else if days_late > 0 {
+                SubmissionStatus::Late.as_ref()
+            } else {
+                SubmissionStatus::Submitted.as_ref()
+            };
+            let record = match req.score.or(homework.raw_score) {
+                Some(raw_score) => Some(
+                    Submission::score_record(pool, homework.assignment_id, days_late, raw_score)
+                        .await?,
+                ),
+                None => None,
+            };
+            let grade = match (req.grade, &record) {
+                (Some(grade), _) => Some(grade),
+                (None, Some(record)) => Some(record.grade.clone()),
+                (None, None) => homework.grade,
+            };
             let feedback = req.feedback.or(homework.feedback);
-            let teacher_id = req.teacher_id.or(homework.teacher_id);
             let now = OffsetDateTime::now_utc();
 
-            let updated_homework = sqlx::query_as!(Self,
+            let mut tx = pool.begin().await?;
+            sqlx::query!(
                 r#"
-                UPDATE homework
-                SET title = $1, description = $2, file_path = $3, submission_date = $4, grade = $5, feedback = $6, teacher_id = $7, updated_at = $8
-                WHERE id = $9
-                RETURNING id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
+                UPDATE assignments
+                SET title = $1, instructions = $2, due_date = $3, teacher_id = $4, updated_at = $5
+                WHERE id = $6
                 "#,
                 title,
                 description,
-                file_path,
+                due_date,
+                teacher_id,
+                now,
+                homework.assignment_id
+            )
+            .execute(&mut *tx)
+            .await?;
+
+            sqlx::query!(
+                r#"
+                UPDATE submissions
+                SET submission_date = $1, days_late = $2, status = $3, grade = $4,
+                    raw_score = $5, late_penalty = $6, score = $7, feedback = $8, updated_at = $9
+                WHERE id = $10
+                "#,
                 submission_date,
+                days_late,
+                status,
                 grade,
+                record.as_ref().map(|record| record.raw_score),
+                record.as_ref().map(|record| record.penalty.percent),
+                record.as_ref().map(|record| record.penalty.score),
                 feedback,
-                teacher_id,
                 now,
                 id
             )
-            .fetch_one(pool)
+            .execute(&mut *tx)
             .await?;
+            tx.commit().await?;
 
-            Ok(updated_homework)
+            Self::find_by_id(pool, id).await?.ok_or(Error::RowNotFound)
         } else {
             Err(Error::RowNotFound)
         }
     }
 
+    /// 更新作业文件路径
+    pub async fn update_file_path(
+        pool: &PgPool,
+        id: Uuid,
+        file_path: Option<&str>,
+    ) -> Result<Self, Error> {
+        Submission::update_file_path(pool, id, file_path).await?;
+        Self::find_by_id(pool, id).await?.ok_or(Error::RowNotFound)
+    }
+
     /// 删除作业
+    ///
+    /// 没有课程的作业任务在删除最后一个提交时一并删除
     pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
-        let result = sqlx::query!("DELETE FROM homework WHERE id = $1", id)
-            .execute(pool)
-            .await?;
-
-        Ok(result.rows_affected() > 0)
-    }
+        let mut tx = pool.begin().await?;
+        let assignment_id = sqlx::query_scalar!(
+            "DELETE FROM submissions WHERE id = $1 AND submission_date IS NOT NULL RETURNING assignment_id",
+            id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
 
-    /// 根据标题查找作业
-    pub async fn find_by_title(pool: &PgPool, title: &str) -> Result<Vec<Self>, Error> {
-        let homeworks = sqlx::query_as!(
-            Self,
+        let Some(assignment_id) = assignment_id else {
+            return Ok(false);
+        };
+        sqlx::query!(
             r#"
-            SELECT id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
-            FROM homework
-            WHERE title ILIKE $1
-            ORDER BY submission_date DESC
+            DELETE FROM assignments a
+            WHERE a.id = $1 AND a.course_id IS NULL
+              AND NOT EXISTS (SELECT 1 FROM submissions s WHERE s.assignment_id = a.id)
             "#,
-            format!("%{}%", title)
+            assignment_id
         )
-        .fetch_all(pool)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
 
-        Ok(homeworks)
+        Ok(true)
     }
 
-    /// 根据日期范围查找作业
-    pub async fn find_by_date_range(
+    /// 按筛选条件分页查询作业
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn query(
         pool: &PgPool,
-        start_date: Option<Date>,
-        end_date: Option<Date>,
-    ) -> Result<Vec<Self>, Error> {
-        let homeworks = match (start_date, end_date) {
-            (Some(start), Some(end)) => {
-                sqlx::query_as!(
-                    Self,
-                    r#"
-                    SELECT id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
-                    FROM homework
-                    WHERE submission_date >= $1 AND submission_date <= $2
-                    ORDER BY submission_date DESC
-                    "#,
-                    start,
-                    end
-                )
-                .fetch_all(pool)
-                .await?
-            },
-            (Some(start), None) => {
-                sqlx::query_as!(
-                    Self,
-                    r#"
-                    SELECT id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
-                    FROM homework
-                    WHERE submission_date >= $1
-                    ORDER BY submission_date DESC
-                    "#,
-                    start
-                )
-                .fetch_all(pool)
-                .await?
-            },
-            (None, Some(end)) => {
-                sqlx::query_as!(
-                    Self,
-                    r#"
-                    SELECT id, student_id, title, description, file_path, submission_date, grade, feedback, teacher_id, created_at, updated_at
-                    FROM homework
-                    WHERE submission_date <= $1
-                    ORDER BY submission_date DESC
-                    "#,
-                    end
-                )
-                .fetch_all(pool)
-                .await?
-            },
-            (None, None) => {
-                return Self::find_all(pool).await;
-            }
-        };
+        filter: &HomeworkFilter,
+        students: Option<&[Uuid]>,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let mut builder = QueryBuilder::new(format!("SELECT COUNT(*) FROM {HOMEWORK} WHERE TRUE"));
+        Self::push_filter(&mut builder, filter, students);
+        let total: i64 = builder.build_query_scalar().fetch_one(pool).await?;
 
-        Ok(homeworks)
+        let mut builder = QueryBuilder::new(format!(
+            r#"
+            SELECT id, assignment_id, student_id, title, description, file_path, submission_date, due_date,
+                   status, days_late, grade, raw_score, late_penalty, score, feedback, teacher_id,
+                   created_at, updated_at
+            FROM {HOMEWORK}
+            WHERE TRUE
+            "#
+        ));
+        Self::push_filter(&mut builder, filter, students);
+        query::push_order_by(&mut builder, filter.sort_by, filter.order);
+        query::push_page(&mut builder, page);
+        let homeworks = builder.build_query_as::<Self>().fetch_all(pool).await?;
+
+        Ok(Page::new(homeworks, total, page))
+    }
+
+    /// 追加筛选条件
+    fn push_filter(
+        builder: &mut QueryBuilder<'_, Postgres>,
+        filter: &HomeworkFilter,
+        students: Option<&[Uuid]>,
+    ) {
+        if let Some(students) = students {
+            builder
+                .push(" AND student_id = ANY(")
+                .push_bind(students.to_vec())
+                .push(")");
+        }
+        if let Some(student_id) = filter.student_id {
+            builder.push(" AND student_id = ").push_bind(student_id);
+        }
+        if let Some(teacher_id) = filter.teacher_id {
+            builder.push(" AND teacher_id = ").push_bind(teacher_id);
+        }
+        if let Some(start_date) = filter.start_date {
+            builder
+                .push(" AND submission_date >= ")
+                .push_bind(start_date);
+        }
+        if let Some(end_date) = filter.end_date {
+            builder.push(" AND submission_date <= ").push_bind(end_date);
+        }
+        if let Some(title) = &filter.title {
+            builder
+                .push(" AND title ILIKE ")
+                .push_bind(format!("%{}%", title));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::TestApp;
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+    use sqlx::PgPool;
+    use time::macros::date;
+
+    #[sqlx::test]
+    async fn update_writes_assignment_fields_only_when_not_shared(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+        let teacher = app.user("teacher").await;
+        let student = app.user("student").await;
+        let course_id = app.course(&teacher).await;
+        app.enroll(course_id, &student).await;
+
+        let (_, homework) = app
+            .send(
+                Method::POST,
+                "/homework",
+                &teacher,
+                Some(json!({
+                    "student_id": student.id,
+                    "title": "第一章练习",
+                    "submission_date": date!(2025 - 04 - 12),
+                    "due_date": date!(2025 - 04 - 10),
+                })),
+            )
+            .await;
+        let id = homework["id"].as_str().unwrap();
+        let (status, updated) = app
+            .send(
+                Method::PUT,
+                &format!("/homework/{id}"),
+                &teacher,
+                Some(json!({ "title": "第一章习题", "due_date": date!(2025 - 04 - 15) })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(updated["title"], "第一章习题");
+        assert_eq!(updated["days_late"], 0);
+        assert_eq!(updated["status"], "submitted");
+
+        // 课程的作业任务由多份提交共享
+        let (_, assignment) = app
+            .send(
+                Method::POST,
+                "/assignments",
+                &teacher,
+                Some(json!({ "course_id": course_id, "title": "第二章练习" })),
+            )
+            .await;
+        let (_, submission) = app
+            .send(
+                Method::POST,
+                "/submissions",
+                &teacher,
+                Some(json!({ "assignment_id": assignment["id"], "student_id": student.id })),
+            )
+            .await;
+        let id = submission["id"].as_str().unwrap();
+        let (status, body) = app
+            .send(
+                Method::PUT,
+                &format!("/homework/{id}"),
+                &teacher,
+                Some(json!({ "title": "第二章习题" })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        assert_eq!(body["details"][0]["field"], "title");
     }
 }
@@ -0,0 +1,173 @@
+//! 注册邀请模型
+//!
+//! 提供注册邀请的数据结构和数据库操作方法，邀请令牌只保存哈希
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::user::UserRole;
+use crate::model::query::{Page, PageParams};
+
+/// 注册邀请结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Invitation {
+    /// 邀请ID
+    pub id: Uuid,
+    /// 邀请令牌哈希
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    /// 注册后的角色
+    pub role: String,
+    /// 限定注册邮箱
+    pub email: Option<String>,
+    /// 创建人ID
+    pub created_by: Option<Uuid>,
+    /// 过期时间
+    pub expires_at: OffsetDateTime,
+    /// 使用者ID
+    pub used_by: Option<Uuid>,
+    /// 使用时间
+    pub used_at: Option<OffsetDateTime>,
+    /// 撤销时间
+    pub revoked_at: Option<OffsetDateTime>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+}
+
+/// 创建邀请的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateInvitationRequest {
+    /// 注册后的角色
+    pub role: UserRole,
+    /// 限定注册邮箱
+    #[validate(email(message = "电子邮件格式不正确"))]
+    pub email: Option<String>,
+    /// 有效期（小时），默认使用配置中的值
+    #[validate(range(min = 1, max = 8760, message = "邀请有效期必须在1到8760小时之间"))]
+    pub expires_in_hours: Option<u64>,
+}
+
+impl Invitation {
+    /// 创建邀请
+    pub async fn create(
+        pool: &PgPool,
+        token_hash: &str,
+        role: UserRole,
+        email: Option<&str>,
+        created_by: Option<Uuid>,
+        expires_at: OffsetDateTime,
+    ) -> Result<Self, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let invitation = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO invitations (id, token_hash, role, email, created_by, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id, token_hash, role, email, created_by, expires_at, used_by, used_at, revoked_at, created_at
+            "#,
+            id,
+            token_hash,
+            role.as_ref(),
+            email,
+            created_by,
+            expires_at,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(invitation)
+    }
+
+    /// 分页获取所有邀请
+    pub async fn find_all(pool: &PgPool, page: &PageParams) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(r#"SELECT COUNT(*) AS "count!" FROM invitations"#)
+            .fetch_one(pool)
+            .await?;
+
+        let invitations = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, token_hash, role, email, created_by, expires_at, used_by, used_at, revoked_at, created_at
+            FROM invitations
+            ORDER BY created_at DESC, id DESC
+            LIMIT $1 OFFSET $2
+            "#,
+            page.limit(),
+            page.offset()
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Page::new(invitations, total, page))
+    }
+
+    /// 根据令牌哈希查找未使用、未撤销且未过期的邀请
+    pub async fn find_valid_by_token_hash<'e, E: PgExecutor<'e>>(
+        executor: E,
+        token_hash: &str,
+    ) -> Result<Option<Self>, Error> {
+        let invitation = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, token_hash, role, email, created_by, expires_at, used_by, used_at, revoked_at, created_at
+            FROM invitations
+            WHERE token_hash = $1 AND used_at IS NULL AND revoked_at IS NULL AND expires_at > NOW()
+            FOR UPDATE
+            "#,
+            token_hash
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(invitation)
+    }
+
+    /// 将邀请标记为已使用
+    pub async fn mark_used<'e, E: PgExecutor<'e>>(
+        executor: E,
+        id: Uuid,
+        used_by: Uuid,
+    ) -> Result<bool, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE invitations
+            SET used_by = $1, used_at = $2
+            WHERE id = $3 AND used_at IS NULL AND revoked_at IS NULL
+            "#,
+            used_by,
+            now,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 撤销尚未使用的邀请
+    pub async fn revoke(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE invitations
+            SET revoked_at = $1
+            WHERE id = $2 AND used_at IS NULL AND revoked_at IS NULL
+            "#,
+            now,
+            id
+        )
+        .execute(pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
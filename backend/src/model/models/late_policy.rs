@@ -0,0 +1,330 @@
+//! 迟交扣分规则模型
+//!
+//! 每个课程可以设置一条迟交扣分规则，评分时按提交记录的迟交天数从原始分数中扣分
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::validate;
+
+/// 迟交扣分规则结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LatePolicy {
+    /// 课程ID
+    pub course_id: Uuid,
+    /// 每迟交一天扣除的百分比
+    pub percent_per_day: Decimal,
+    /// 扣分上限（百分比）
+    pub max_percent: Decimal,
+    /// 宽限天数，宽限期内迟交不扣分
+    pub grace_days: i32,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 设置迟交扣分规则的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct SetLatePolicyRequest {
+    /// 每迟交一天扣除的百分比
+    #[validate(custom(function = "validate::percent"))]
+    pub percent_per_day: Decimal,
+    /// 扣分上限（百分比）
+    #[validate(custom(function = "validate::percent"))]
+    pub max_percent: Decimal,
+    /// 宽限天数
+    #[serde(default)]
+    #[validate(range(min = 0, max = 365, message = "宽限天数必须在0到365之间"))]
+    pub grace_days: i32,
+}
+
+/// 扣分结果
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Penalty {
+    /// 扣除的百分比
+    pub percent: Decimal,
+    /// 扣分后的分数，保留两位小数
+    pub score: Decimal,
+}
+
+impl LatePolicy {
+    /// 设置课程的迟交扣分规则，已有规则时覆盖
+    pub async fn upsert<'e, E: PgExecutor<'e>>(
+        executor: E,
+        course_id: Uuid,
+        req: &SetLatePolicyRequest,
+    ) -> Result<Self, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let policy = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO late_policies (course_id, percent_per_day, max_percent, grace_days, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $5)
+            ON CONFLICT (course_id) DO UPDATE
+            SET percent_per_day = EXCLUDED.percent_per_day, max_percent = EXCLUDED.max_percent,
+                grace_days = EXCLUDED.grace_days, updated_at = EXCLUDED.updated_at
+            RETURNING course_id, percent_per_day, max_percent, grace_days, created_at, updated_at
+            "#,
+            course_id,
+            req.percent_per_day,
+            req.max_percent,
+            req.grace_days,
+            now
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// 获取课程的迟交扣分规则
+    pub async fn find_by_course(pool: &PgPool, course_id: Uuid) -> Result<Option<Self>, Error> {
+        let policy = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT course_id, percent_per_day, max_percent, grace_days, created_at, updated_at
+            FROM late_policies
+            WHERE course_id = $1
+            "#,
+            course_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// 获取作业任务所属课程的迟交扣分规则
+    pub async fn find_by_assignment(
+        pool: &PgPool,
+        assignment_id: Uuid,
+    ) -> Result<Option<Self>, Error> {
+        let policy = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT p.course_id, p.percent_per_day, p.max_percent, p.grace_days, p.created_at, p.updated_at
+            FROM late_policies p
+            JOIN assignments a ON a.course_id = p.course_id
+            WHERE a.id = $1
+            "#,
+            assignment_id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// 删除课程的迟交扣分规则
+    pub async fn delete<'e, E: PgExecutor<'e>>(
+        executor: E,
+        course_id: Uuid,
+    ) -> Result<bool, Error> {
+        let result = sqlx::query!("DELETE FROM late_policies WHERE course_id = $1", course_id)
+            .execute(executor)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    /// 迟交天数对应的扣分百分比：超出宽限期的天数乘以每天的百分比，不超过上限
+    pub fn penalty_percent(&self, days_late: i32) -> Decimal {
+        let days = (days_late - self.grace_days).max(0);
+        (self.percent_per_day * Decimal::from(days)).min(self.max_percent)
+    }
+
+    /// 按规则从原始分数中扣分，没有规则时不扣分
+    pub fn apply(policy: Option<&Self>, raw_score: Decimal, days_late: i32) -> Penalty {
+        let percent = policy.map_or(Decimal::ZERO, |policy| policy.penalty_percent(days_late));
+        let score =
+            (raw_score * (Decimal::ONE_HUNDRED - percent) / Decimal::ONE_HUNDRED).round_dp(2);
+        Penalty { percent, score }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{TestApp, dec, json_dec};
+    use axum::http::{Method, StatusCode};
+    use serde_json::json;
+    use time::macros::date;
+
+    fn policy(percent_per_day: &str, max_percent: &str, grace_days: i32) -> LatePolicy {
+        LatePolicy {
+            course_id: Uuid::nil(),
+            percent_per_day: dec(percent_per_day),
+            max_percent: dec(max_percent),
+            grace_days,
+            created_at: OffsetDateTime::UNIX_EPOCH,
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        }
+    }
+
+    #[test]
+    fn penalty_percent_is_zero_on_time_and_within_grace() {
+        let policy = policy("10", "50", 2);
+        assert_eq!(policy.penalty_percent(0), Decimal::ZERO);
+        assert_eq!(policy.penalty_percent(1), Decimal::ZERO);
+        assert_eq!(policy.penalty_percent(2), Decimal::ZERO);
+    }
+
+    #[test]
+    fn penalty_percent_counts_days_after_grace() {
+        let policy = policy("10", "50", 2);
+        assert_eq!(policy.penalty_percent(3), dec("10"));
+        assert_eq!(policy.penalty_percent(5), dec("30"));
+    }
+
+    #[test]
+    fn penalty_percent_is_capped_at_max() {
+        let policy = policy("10", "50", 0);
+        assert_eq!(policy.penalty_percent(5), dec("50"));
+        assert_eq!(policy.penalty_percent(6), dec("50"));
+        assert_eq!(policy.penalty_percent(365), dec("50"));
+    }
+
+    #[test]
+    fn penalty_percent_ignores_negative_days() {
+        let policy = policy("10", "50", 0);
+        assert_eq!(policy.penalty_percent(-3), Decimal::ZERO);
+    }
+
+    #[test]
+    fn apply_without_policy_keeps_score() {
+        let penalty = LatePolicy::apply(None, dec("87.5"), 10);
+        assert_eq!(penalty.percent, Decimal::ZERO);
+        assert_eq!(penalty.score, dec("87.5"));
+    }
+
+    #[test]
+    fn apply_deducts_percent_of_raw_score() {
+        let policy = policy("10", "100", 0);
+        let penalty = LatePolicy::apply(Some(&policy), dec("80"), 2);
+        assert_eq!(penalty.percent, dec("20"));
+        assert_eq!(penalty.score, dec("64"));
+    }
+
+    #[test]
+    fn apply_rounds_score_to_two_decimals() {
+        let policy = policy("33.33", "100", 0);
+        let penalty = LatePolicy::apply(Some(&policy), dec("10"), 1);
+        assert_eq!(penalty.score, dec("6.67"));
+    }
+
+    #[test]
+    fn apply_full_penalty_gives_zero() {
+        let policy = policy("50", "100", 0);
+        let penalty = LatePolicy::apply(Some(&policy), dec("95"), 3);
+        assert_eq!(penalty.percent, dec("100"));
+        assert_eq!(penalty.score, Decimal::ZERO);
+    }
+
+    #[sqlx::test]
+    async fn grading_late_submission_applies_course_policy(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+        let teacher = app.user("teacher").await;
+        let student = app.user("student").await;
+        let course_id = app.course(&teacher).await;
+        app.enroll(course_id, &student).await;
+
+        let (status, _) = app
+            .send(
+                Method::PUT,
+                &format!("/courses/{course_id}/late-policy"),
+                &teacher,
+                Some(json!({ "percent_per_day": 10, "max_percent": 50, "grace_days": 1 })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        let (_, assignment) = app
+            .send(
+                Method::POST,
+                "/assignments",
+                &teacher,
+                Some(json!({ "course_id": course_id, "title": "第一章练习", "due_date": "2025-04-10" })),
+            )
+            .await;
+        let (status, submission) = app
+            .send(
+                Method::POST,
+                "/submissions",
+                &teacher,
+                Some(json!({
+                    "assignment_id": assignment["id"],
+                    "student_id": student.id,
+                    "submission_date": "2025-04-13",
+                })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(submission["status"], "late");
+        assert_eq!(submission["days_late"], 3);
+
+        let id = submission["id"].as_str().unwrap();
+        let (status, _) = app
+            .send(
+                Method::PUT,
+                &format!("/submissions/{id}/grade"),
+                &teacher,
+                Some(json!({ "score": 80 })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+
+        // 迟交3天、宽限1天，扣除20%
+        let (_, graded) = app
+            .send(Method::GET, &format!("/submissions/{id}"), &teacher, None)
+            .await;
+        assert_eq!(json_dec(&graded["raw_score"]), dec("80"));
+        assert_eq!(json_dec(&graded["late_penalty"]), dec("20"));
+        assert_eq!(json_dec(&graded["score"]), dec("64"));
+    }
+
+    #[sqlx::test]
+    async fn grading_legacy_homework_records_days_late_without_penalty(pool: PgPool) {
+        let app = TestApp::new(pool).await;
+        let teacher = app.user("teacher").await;
+        let student = app.user("student").await;
+        let course_id = app.course(&teacher).await;
+        app.enroll(course_id, &student).await;
+
+        let (status, homework) = app
+            .send(
+                Method::POST,
+                "/homework",
+                &teacher,
+                Some(json!({
+                    "student_id": student.id,
+                    "title": "第一章练习",
+                    "submission_date": date!(2025 - 04 - 12),
+                    "due_date": date!(2025 - 04 - 10),
+                })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(homework["days_late"], 2);
+
+        // 兼容接口创建的作业任务不属于课程，没有扣分规则
+        let id = homework["id"].as_str().unwrap();
+        let (status, graded) = app
+            .send(
+                Method::PUT,
+                &format!("/homework/{id}/grade"),
+                &teacher,
+                Some(json!({ "score": 90 })),
+            )
+            .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json_dec(&graded["raw_score"]), dec("90"));
+        assert_eq!(json_dec(&graded["late_penalty"]), Decimal::ZERO);
+        assert_eq!(json_dec(&graded["score"]), dec("90"));
+    }
+}
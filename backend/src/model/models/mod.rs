@@ -3,10 +3,30 @@
 //! 包含所有与数据库表对应的结构体定义
 pub mod activity;
 pub mod announcement;
+pub mod assignment;
+pub mod attendance;
+pub mod class_group;
 pub mod course;
 pub mod course_record;
+pub mod enrollment;
 pub mod exam;
+pub mod exam_question;
 pub mod exam_record;
+pub mod file;
+pub mod final_grade;
+pub mod grade_category;
+pub mod grade_excusal;
+pub mod gradebook;
+pub mod grading_scale;
 pub mod homework;
+pub mod invitation;
+pub mod late_policy;
+pub mod permission;
+pub mod progress;
+pub mod question;
+pub mod refresh_token;
+pub mod report_card;
 // student模块已被整合到user模块中
+pub mod submission;
+pub mod timeline;
 pub mod user;
@@ -0,0 +1,242 @@
+//! 权限模型
+//!
+//! 定义系统权限，并从 role_permissions 表加载角色与权限的对应关系
+
+use once_cell::sync::OnceCell;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, postgres::PgPool};
+use std::collections::{HashMap, HashSet};
+use std::str::FromStr;
+use std::sync::RwLock;
+use tracing::{info, warn};
+
+use super::user::UserRole;
+
+// 角色权限缓存，启动时从数据库加载
+static ROLE_PERMISSIONS: OnceCell<RwLock<HashMap<UserRole, HashSet<Permission>>>> = OnceCell::new();
+
+/// 权限枚举
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum Permission {
+    /// 管理用户账号
+    #[serde(rename = "user:manage")]
+    UserManage,
+    /// 查看学生信息
+    #[serde(rename = "student:read")]
+    StudentRead,
+    /// 创建和修改学生信息
+    #[serde(rename = "student:write")]
+    StudentWrite,
+    /// 删除学生
+    #[serde(rename = "student:delete")]
+    StudentDelete,
+    /// 查看课程
+    #[serde(rename = "course:read")]
+    CourseRead,
+    /// 创建、修改和删除课程
+    #[serde(rename = "course:write")]
+    CourseWrite,
+    /// 查看课程记录
+    #[serde(rename = "course_record:read")]
+    CourseRecordRead,
+    /// 创建、修改和删除课程记录
+    #[serde(rename = "course_record:write")]
+    CourseRecordWrite,
+    /// 查看试卷
+    #[serde(rename = "exam:read")]
+    ExamRead,
+    /// 创建、修改和删除试卷
+    #[serde(rename = "exam:write")]
+    ExamWrite,
+    /// 查看试卷记录
+    #[serde(rename = "exam_record:read")]
+    ExamRecordRead,
+    /// 创建、修改和删除试卷记录
+    #[serde(rename = "exam_record:write")]
+    ExamRecordWrite,
+    /// 查看作业
+    #[serde(rename = "homework:read")]
+    HomeworkRead,
+    /// 提交作业
+    #[serde(rename = "homework:submit")]
+    HomeworkSubmit,
+    /// 修改和删除作业
+    #[serde(rename = "homework:write")]
+    HomeworkWrite,
+    /// 评分作业
+    #[serde(rename = "homework:grade")]
+    HomeworkGrade,
+    /// 查看考勤
+    #[serde(rename = "attendance:read")]
+    AttendanceRead,
+    /// 记录考勤
+    #[serde(rename = "attendance:write")]
+    AttendanceWrite,
+    /// 查看活动记录
+    #[serde(rename = "activity:read")]
+    ActivityRead,
+    /// 创建活动记录
+    #[serde(rename = "activity:write")]
+    ActivityWrite,
+    /// 发布公告
+    #[serde(rename = "announcement:write")]
+    AnnouncementWrite,
+    /// 下载文件
+    #[serde(rename = "file:read")]
+    FileRead,
+    /// 查看总评成绩
+    #[serde(rename = "final_grade:read")]
+    FinalGradeRead,
+    /// 设置成绩类别和免做，计算、调整和锁定总评成绩
+    #[serde(rename = "final_grade:write")]
+    FinalGradeWrite,
+    /// 解锁总评成绩
+    #[serde(rename = "final_grade:unlock")]
+    FinalGradeUnlock,
+}
+
+impl Permission {
+    /// 全部权限
+    pub const ALL: [Permission; 25] = [
+        Permission::UserManage,
+        Permission::StudentRead,
+        Permission::StudentWrite,
+        Permission::StudentDelete,
+        Permission::CourseRead,
+        Permission::CourseWrite,
+        Permission::CourseRecordRead,
+        Permission::CourseRecordWrite,
+        Permission::ExamRead,
+        Permission::ExamWrite,
+        Permission::ExamRecordRead,
+        Permission::ExamRecordWrite,
+        Permission::HomeworkRead,
+        Permission::HomeworkSubmit,
+        Permission::HomeworkWrite,
+        Permission::HomeworkGrade,
+        Permission::AttendanceRead,
+        Permission::AttendanceWrite,
+        Permission::ActivityRead,
+        Permission::ActivityWrite,
+        Permission::AnnouncementWrite,
+        Permission::FileRead,
+        Permission::FinalGradeRead,
+        Permission::FinalGradeWrite,
+        Permission::FinalGradeUnlock,
+    ];
+}
+
+impl AsRef<str> for Permission {
+    fn as_ref(&self) -> &str {
+        match self {
+            Permission::UserManage => "user:manage",
+            Permission::StudentRead => "student:read",
+            Permission::StudentWrite => "student:write",
+            Permission::StudentDelete => "student:delete",
+            Permission::CourseRead => "course:read",
+            Permission::CourseWrite => "course:write",
+            Permission::CourseRecordRead => "course_record:read",
+            Permission::CourseRecordWrite => "course_record:write",
+            Permission::ExamRead => "exam:read",
+            Permission::ExamWrite => "exam:write",
+            Permission::ExamRecordRead => "exam_record:read",
+            Permission::ExamRecordWrite => "exam_record:write",
+            Permission::HomeworkRead => "homework:read",
+            Permission::HomeworkSubmit => "homework:submit",
+            Permission::HomeworkWrite => "homework:write",
+            Permission::HomeworkGrade => "homework:grade",
+            Permission::AttendanceRead => "attendance:read",
+            Permission::AttendanceWrite => "attendance:write",
+            Permission::ActivityRead => "activity:read",
+            Permission::ActivityWrite => "activity:write",
+            Permission::AnnouncementWrite => "announcement:write",
+            Permission::FileRead => "file:read",
+            Permission::FinalGradeRead => "final_grade:read",
+            Permission::FinalGradeWrite => "final_grade:write",
+            Permission::FinalGradeUnlock => "final_grade:unlock",
+        }
+    }
+}
+
+impl FromStr for Permission {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Permission::ALL
+            .iter()
+            .find(|p| p.as_ref() == s)
+            .copied()
+            .ok_or_else(|| format!("未知权限: {}", s))
+    }
+}
+
+impl std::fmt::Display for Permission {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
+/// 角色权限记录
+#[derive(Debug, Clone, Serialize)]
+pub struct RolePermission {
+    /// 角色
+    pub role: String,
+    /// 权限代码
+    pub permission_code: String,
+}
+
+impl RolePermission {
+    /// 获取所有角色权限
+    pub async fn find_all(pool: &PgPool) -> Result<Vec<Self>, Error> {
+        let rows = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT role, permission_code
+            FROM role_permissions
+            ORDER BY role ASC, permission_code ASC
+            "#
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(rows)
+    }
+
+    /// 从数据库加载角色权限到缓存，重复调用时刷新缓存
+    pub async fn load(pool: &PgPool) -> Result<(), Error> {
+        let mut map: HashMap<UserRole, HashSet<Permission>> = HashMap::new();
+
+        for row in Self::find_all(pool).await? {
+            let role = match row.role.parse::<UserRole>() {
+                Ok(role) => role,
+                Err(_) => {
+                    warn!("忽略未知角色的权限配置: {}", row.role);
+                    continue;
+                }
+            };
+            match row.permission_code.parse::<Permission>() {
+                Ok(permission) => {
+                    map.entry(role).or_default().insert(permission);
+                }
+                Err(e) => warn!("忽略权限配置: {}", e),
+            }
+        }
+
+        info!("已加载 {} 个角色的权限配置", map.len());
+
+        let cache = ROLE_PERMISSIONS.get_or_init(|| RwLock::new(HashMap::new()));
+        *cache.write().expect("权限缓存锁已损坏") = map;
+        Ok(())
+    }
+
+    /// 判断角色是否拥有指定权限
+    pub fn role_has(role: &UserRole, permission: Permission) -> bool {
+        ROLE_PERMISSIONS
+            .get()
+            .expect("角色权限未加载")
+            .read()
+            .expect("权限缓存锁已损坏")
+            .get(role)
+            .is_some_and(|permissions| permissions.contains(&permission))
+    }
+}
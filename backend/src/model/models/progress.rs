@@ -0,0 +1,345 @@
+//! 学生成绩趋势模型
+//!
+//! 根据学生的试卷记录计算成绩趋势。每次考试的分数减去同场考试的平均分得到相对分，
+//! 在相对分上计算移动平均、线性趋势斜率，并标记明显低于自身基线的考试
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, postgres::PgPool};
+use std::collections::HashMap;
+use time::Date;
+use uuid::Uuid;
+use validator::Validate;
+
+use super::exam_record::ExamRecord;
+use crate::model::date_format;
+use crate::model::validate;
+
+/// 默认移动平均窗口（考试次数）
+pub const DEFAULT_WINDOW: u32 = 3;
+/// 默认下降提醒阈值（分）
+pub const DEFAULT_DROP_THRESHOLD: Decimal = Decimal::from_parts(10, 0, 0, false, 0);
+
+/// 成绩趋势的计算参数
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct ProgressParams {
+    /// 移动平均窗口，默认为3次考试
+    #[validate(range(min = 1, max = 20, message = "移动平均窗口必须在1到20之间"))]
+    pub window: Option<u32>,
+    /// 下降提醒阈值，相对分低于基线超过该值时提醒，默认为10分
+    #[validate(custom(function = "validate::positive_score"))]
+    pub drop_threshold: Option<Decimal>,
+}
+
+/// 成绩趋势中的一次考试
+#[derive(Debug, Clone, Serialize)]
+pub struct ProgressPoint {
+    /// 试卷记录ID
+    pub exam_record_id: Uuid,
+    /// 试卷ID
+    pub exam_id: Uuid,
+    /// 完成日期
+    #[serde(with = "date_format::date")]
+    pub completion_date: Date,
+    /// 分数
+    pub score: Decimal,
+    /// 同场考试的平均分
+    pub cohort_mean: Decimal,
+    /// 相对分，即分数减去同场考试的平均分
+    pub relative_score: Decimal,
+    /// 相对分的移动平均，考试次数不足窗口时为空
+    pub moving_average: Option<Decimal>,
+    /// 基线，即此前各次考试相对分的平均值，第一次考试时为空
+    pub baseline: Option<Decimal>,
+    /// 相对分是否低于基线超过阈值
+    pub below_baseline: bool,
+}
+
+/// 学生成绩趋势
+#[derive(Debug, Clone, Serialize)]
+pub struct StudentProgress {
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 移动平均窗口
+    pub window: u32,
+    /// 下降提醒阈值
+    pub drop_threshold: Decimal,
+    /// 相对分的线性趋势斜率，即平均每次考试变化的分数，少于两次考试时为空
+    pub slope: Option<Decimal>,
+    /// 低于基线的考试次数
+    pub alerts: usize,
+    /// 按完成日期排列的各次考试
+    pub points: Vec<ProgressPoint>,
+}
+
+impl StudentProgress {
+    /// 计算学生的成绩趋势，没有分数的试卷记录不参与计算
+    pub async fn for_student(
+        pool: &PgPool,
+        student_id: Uuid,
+        params: &ProgressParams,
+    ) -> Result<Self, Error> {
+        let mut records: Vec<ExamRecord> = ExamRecord::find_by_student_id(pool, student_id)
+            .await?
+            .into_iter()
+            .filter(|record| record.score.is_some())
+            .collect();
+        records.sort_by_key(|record| (record.completion_date, record.created_at));
+
+        let exam_ids: Vec<Uuid> = records.iter().map(|record| record.exam_id).collect();
+        let cohort_means = ExamRecord::cohort_means(pool, &exam_ids).await?;
+
+        Ok(Self::compute(student_id, &records, &cohort_means, params))
+    }
+
+    /// 根据按时间排列的试卷记录和各试卷平均分计算趋势
+    fn compute(
+        student_id: Uuid,
+        records: &[ExamRecord],
+        cohort_means: &HashMap<Uuid, Decimal>,
+        params: &ProgressParams,
+    ) -> Self {
+        let window = params.window.unwrap_or(DEFAULT_WINDOW);
+        let drop_threshold = params.drop_threshold.unwrap_or(DEFAULT_DROP_THRESHOLD);
+
+        let mut relatives = Vec::with_capacity(records.len());
+        let mut points = Vec::with_capacity(records.len());
+        for record in records {
+            let (Some(score), Some(&cohort_mean)) =
+                (record.score, cohort_means.get(&record.exam_id))
+            else {
+                continue;
+            };
+
+            let relative_score = score - cohort_mean;
+            let baseline = mean(&relatives);
+            relatives.push(relative_score);
+
+            let moving_average = relatives
+                .len()
+                .checked_sub(window as usize)
+                .and_then(|start| mean(&relatives[start..]));
+
+            points.push(ProgressPoint {
+                exam_record_id: record.id,
+                exam_id: record.exam_id,
+                completion_date: record.completion_date,
+                score,
+                cohort_mean,
+                relative_score,
+                moving_average,
+                baseline,
+                below_baseline: baseline
+                    .is_some_and(|baseline| relative_score < baseline - drop_threshold),
+            });
+        }
+
+        Self {
+            student_id,
+            window,
+            drop_threshold,
+            slope: slope(&relatives),
+            alerts: points.iter().filter(|point| point.below_baseline).count(),
+            points,
+        }
+    }
+}
+
+/// 平均值，保留两位小数
+fn mean(values: &[Decimal]) -> Option<Decimal> {
+    if values.is_empty() {
+        return None;
+    }
+    let sum: Decimal = values.iter().sum();
+    Some((sum / Decimal::from(values.len())).round_dp(2))
+}
+
+/// 以考试序号为自变量的最小二乘斜率，保留两位小数
+fn slope(values: &[Decimal]) -> Option<Decimal> {
+    if values.len() < 2 {
+        return None;
+    }
+
+    let n = Decimal::from(values.len());
+    let x_mean = (n - Decimal::ONE) / Decimal::TWO;
+    let y_mean = values.iter().sum::<Decimal>() / n;
+
+    let mut numerator = Decimal::ZERO;
+    let mut denominator = Decimal::ZERO;
+    for (i, y) in values.iter().enumerate() {
+        let dx = Decimal::from(i) - x_mean;
+        numerator += dx * (y - y_mean);
+        denominator += dx * dx;
+    }
+
+    Some((numerator / denominator).round_dp(2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{dec, decs};
+    use time::{Month, OffsetDateTime};
+
+    /// 按顺序生成试卷记录，每条记录对应一场新的考试，同时返回各场考试的平均分
+    fn records(
+        scores: &[(Option<&str>, Option<&str>)],
+    ) -> (Vec<ExamRecord>, HashMap<Uuid, Decimal>) {
+        let mut cohort_means = HashMap::new();
+        let records = scores
+            .iter()
+            .enumerate()
+            .map(|(i, &(score, cohort_mean))| {
+                let exam_id = Uuid::new_v4();
+                if let Some(cohort_mean) = cohort_mean {
+                    cohort_means.insert(exam_id, dec(cohort_mean));
+                }
+                ExamRecord {
+                    id: Uuid::new_v4(),
+                    student_id: Uuid::nil(),
+                    exam_id,
+                    score: score.map(dec),
+                    completion_date: Date::from_calendar_date(2025, Month::March, i as u8 + 1)
+                        .unwrap(),
+                    notes: None,
+                    created_at: OffsetDateTime::UNIX_EPOCH,
+                    updated_at: OffsetDateTime::UNIX_EPOCH,
+                }
+            })
+            .collect();
+        (records, cohort_means)
+    }
+
+    fn compute(
+        scores: &[(Option<&str>, Option<&str>)],
+        params: &ProgressParams,
+    ) -> StudentProgress {
+        let (records, cohort_means) = records(scores);
+        StudentProgress::compute(Uuid::nil(), &records, &cohort_means, params)
+    }
+
+    #[test]
+    fn slope_needs_two_values() {
+        assert_eq!(slope(&[]), None);
+        assert_eq!(slope(&decs(&["5"])), None);
+    }
+
+    #[test]
+    fn slope_of_constant_values_is_zero() {
+        assert_eq!(slope(&decs(&["3", "3", "3"])), Some(Decimal::ZERO));
+    }
+
+    #[test]
+    fn slope_of_linear_values() {
+        assert_eq!(slope(&decs(&["0", "10", "20", "30"])), Some(dec("10")));
+        assert_eq!(slope(&decs(&["5", "-1"])), Some(dec("-6")));
+    }
+
+    #[test]
+    fn slope_is_rounded_to_two_decimals() {
+        assert_eq!(slope(&decs(&["0", "0", "1"])), Some(dec("0.5")));
+        assert_eq!(slope(&decs(&["0", "1", "0", "0"])), Some(dec("-0.1")));
+        assert_eq!(
+            slope(&decs(&["1", "0", "0", "2", "0", "0"])),
+            Some(dec("-0.09"))
+        );
+    }
+
+    #[test]
+    fn compute_uses_defaults() {
+        let progress = compute(&[], &ProgressParams::default());
+        assert_eq!(progress.window, DEFAULT_WINDOW);
+        assert_eq!(progress.drop_threshold, DEFAULT_DROP_THRESHOLD);
+        assert_eq!(progress.slope, None);
+        assert_eq!(progress.alerts, 0);
+        assert!(progress.points.is_empty());
+    }
+
+    #[test]
+    fn compute_skips_records_without_score_or_cohort_mean() {
+        let progress = compute(
+            &[
+                (Some("80"), Some("70")),
+                (None, Some("70")),
+                (Some("90"), None),
+                (Some("60"), Some("65")),
+            ],
+            &ProgressParams::default(),
+        );
+        let relatives: Vec<Decimal> = progress
+            .points
+            .iter()
+            .map(|point| point.relative_score)
+            .collect();
+        assert_eq!(relatives, decs(&["10", "-5"]));
+        assert_eq!(progress.slope, Some(dec("-15")));
+    }
+
+    #[test]
+    fn compute_baseline_and_moving_average() {
+        let progress = compute(
+            &[
+                (Some("80"), Some("70")),
+                (Some("70"), Some("70")),
+                (Some("75"), Some("70")),
+                (Some("90"), Some("70")),
+            ],
+            &ProgressParams {
+                window: Some(2),
+                drop_threshold: None,
+            },
+        );
+        let baselines: Vec<Option<Decimal>> =
+            progress.points.iter().map(|point| point.baseline).collect();
+        assert_eq!(
+            baselines,
+            [None, Some(dec("10")), Some(dec("5")), Some(dec("5"))]
+        );
+        let averages: Vec<Option<Decimal>> = progress
+            .points
+            .iter()
+            .map(|point| point.moving_average)
+            .collect();
+        assert_eq!(
+            averages,
+            [None, Some(dec("5")), Some(dec("2.5")), Some(dec("12.5"))]
+        );
+    }
+
+    #[test]
+    fn compute_flags_drops_beyond_threshold() {
+        let progress = compute(
+            &[
+                (Some("90"), Some("70")),
+                (Some("80"), Some("70")),
+                (Some("60"), Some("70")),
+                (Some("55"), Some("70")),
+            ],
+            &ProgressParams {
+                window: None,
+                drop_threshold: Some(dec("10")),
+            },
+        );
+        // 相对分依次为 20、10、-10、-15，基线为 20、15、6.67；相对分恰好低于基线10分时不提醒
+        let flags: Vec<bool> = progress
+            .points
+            .iter()
+            .map(|point| point.below_baseline)
+            .collect();
+        assert_eq!(flags, [false, false, true, true]);
+        assert_eq!(progress.alerts, 2);
+    }
+
+    #[test]
+    fn compute_does_not_flag_drop_equal_to_threshold() {
+        let progress = compute(
+            &[(Some("80"), Some("70")), (Some("70"), Some("70"))],
+            &ProgressParams {
+                window: None,
+                drop_threshold: Some(dec("10")),
+            },
+        );
+        assert!(!progress.points[1].below_baseline);
+        assert_eq!(progress.alerts, 0);
+    }
+}
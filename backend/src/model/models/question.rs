@@ -0,0 +1,315 @@
+//! 题库模型
+//!
+//! 题目保存在可复用的题库中，试卷从题库中选题组成
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use std::str::FromStr;
+use time::OffsetDateTime;
+use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::query::{Page, PageParams};
+use crate::model::validate;
+
+/// 题型枚举
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum QuestionType {
+    /// 单选题
+    SingleChoice,
+    /// 多选题
+    MultipleChoice,
+    /// 判断题
+    TrueFalse,
+    /// 填空题
+    FillBlank,
+    /// 简答题
+    ShortAnswer,
+}
+
+impl AsRef<str> for QuestionType {
+    fn as_ref(&self) -> &str {
+        match self {
+            QuestionType::SingleChoice => "single_choice",
+            QuestionType::MultipleChoice => "multiple_choice",
+            QuestionType::TrueFalse => "true_false",
+            QuestionType::FillBlank => "fill_blank",
+            QuestionType::ShortAnswer => "short_answer",
+        }
+    }
+}
+
+impl FromStr for QuestionType {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "single_choice" => Ok(QuestionType::SingleChoice),
+            "multiple_choice" => Ok(QuestionType::MultipleChoice),
+            "true_false" => Ok(QuestionType::TrueFalse),
+            "fill_blank" => Ok(QuestionType::FillBlank),
+            "short_answer" => Ok(QuestionType::ShortAnswer),
+            _ => Err(format!("未知的题型: {}", s)),
+        }
+    }
+}
+
+impl QuestionType {
+    /// 是否为需要选项的选择题
+    pub fn has_options(self) -> bool {
+        matches!(
+            self,
+            QuestionType::SingleChoice | QuestionType::MultipleChoice
+        )
+    }
+}
+
+/// 题目结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Question {
+    /// 题目ID
+    pub id: Uuid,
+    /// 题型
+    pub question_type: String,
+    /// 题目内容
+    pub content: String,
+    /// 选择题的选项
+    pub options: Option<Vec<String>>,
+    /// 参考答案
+    pub answer_key: Option<String>,
+    /// 默认分值
+    pub points: Decimal,
+    /// 知识点
+    pub knowledge_points: Vec<String>,
+    /// 创建题目的用户ID
+    pub created_by: Option<Uuid>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 创建题目的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct CreateQuestionRequest {
+    /// 题型
+    pub question_type: QuestionType,
+    /// 题目内容
+    #[validate(custom(function = "validate::not_blank"))]
+    pub content: String,
+    /// 选择题的选项，单选题和多选题至少两个
+    pub options: Option<Vec<String>>,
+    /// 参考答案
+    pub answer_key: Option<String>,
+    /// 默认分值
+    #[validate(custom(function = "validate::positive_score"))]
+    pub points: Decimal,
+    /// 知识点
+    #[serde(default)]
+    pub knowledge_points: Vec<String>,
+}
+
+/// 更新题目的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct UpdateQuestionRequest {
+    /// 题型
+    pub question_type: Option<QuestionType>,
+    /// 题目内容
+    #[validate(custom(function = "validate::not_blank"))]
+    pub content: Option<String>,
+    /// 选择题的选项
+    pub options: Option<Vec<String>>,
+    /// 参考答案
+    pub answer_key: Option<String>,
+    /// 默认分值，不影响已组卷的试卷中的分值
+    #[validate(custom(function = "validate::positive_score"))]
+    pub points: Option<Decimal>,
+    /// 知识点
+    pub knowledge_points: Option<Vec<String>>,
+}
+
+/// 查询题目的筛选条件，所有条件以 AND 组合
+#[derive(Debug, Default, Deserialize)]
+pub struct QuestionFilter {
+    /// 题型
+    pub question_type: Option<QuestionType>,
+    /// 知识点
+    pub knowledge_point: Option<String>,
+    /// 题目内容关键词
+    pub keyword: Option<String>,
+}
+
+impl Question {
+    /// 创建题目，选项和知识点由调用方整理
+    pub async fn create(
+        pool: &PgPool,
+        req: CreateQuestionRequest,
+        created_by: Option<Uuid>,
+    ) -> Result<Self, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let question = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO questions (id, question_type, content, options, answer_key, points, knowledge_points,
+                                   created_by, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $9)
+            RETURNING id, question_type, content, options, answer_key, points, knowledge_points,
+                      created_by, created_at, updated_at
+            "#,
+            id,
+            req.question_type.as_ref(),
+            req.content,
+            req.options.as_deref(),
+            req.answer_key,
+            req.points,
+            &req.knowledge_points,
+            created_by,
+            now
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(question)
+    }
+
+    /// 根据ID查找题目
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let question = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, question_type, content, options, answer_key, points, knowledge_points,
+                   created_by, created_at, updated_at
+            FROM questions
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(question)
+    }
+
+    /// 根据ID批量查找题目
+    pub async fn find_by_ids<'e, E: PgExecutor<'e>>(
+        executor: E,
+        ids: &[Uuid],
+    ) -> Result<Vec<Self>, Error> {
+        let questions = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, question_type, content, options, answer_key, points, knowledge_points,
+                   created_by, created_at, updated_at
+            FROM questions
+            WHERE id = ANY($1)
+            "#,
+            ids
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(questions)
+    }
+
+    /// 按筛选条件分页查询题目，按创建时间倒序排列
+    pub async fn query(
+        pool: &PgPool,
+        filter: &QuestionFilter,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let question_type = filter.question_type.as_ref().map(AsRef::as_ref);
+
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM questions
+            WHERE ($1::text IS NULL OR question_type = $1)
+              AND ($2::text IS NULL OR $2 = ANY(knowledge_points))
+              AND ($3::text IS NULL OR content ILIKE '%' || $3 || '%')
+            "#,
+            question_type,
+            filter.knowledge_point,
+            filter.keyword
+        )
+        .fetch_one(pool)
+        .await?;
+
+        let questions = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, question_type, content, options, answer_key, points, knowledge_points,
+                   created_by, created_at, updated_at
+            FROM questions
+            WHERE ($1::text IS NULL OR question_type = $1)
+              AND ($2::text IS NULL OR $2 = ANY(knowledge_points))
+              AND ($3::text IS NULL OR content ILIKE '%' || $3 || '%')
+            ORDER BY created_at DESC, id DESC
+            LIMIT $4 OFFSET $5
+            "#,
+            question_type,
+            filter.knowledge_point,
+            filter.keyword,
+            page.limit(),
+            page.offset()
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Page::new(questions, total, page))
+    }
+
+    /// 更新题目，选项和知识点由调用方整理
+    pub async fn update(
+        pool: &PgPool,
+        question: Self,
+        req: UpdateQuestionRequest,
+    ) -> Result<Self, Error> {
+        let question_type = req
+            .question_type
+            .as_ref()
+            .map_or(question.question_type, |kind| kind.as_ref().to_string());
+        let content = req.content.unwrap_or(question.content);
+        let options = req.options.or(question.options);
+        let answer_key = req.answer_key.or(question.answer_key);
+        let points = req.points.unwrap_or(question.points);
+        let knowledge_points = req.knowledge_points.unwrap_or(question.knowledge_points);
+        let now = OffsetDateTime::now_utc();
+
+        let question = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE questions
+            SET question_type = $1, content = $2, options = $3, answer_key = $4, points = $5,
+                knowledge_points = $6, updated_at = $7
+            WHERE id = $8
+            RETURNING id, question_type, content, options, answer_key, points, knowledge_points,
+                      created_by, created_at, updated_at
+            "#,
+            question_type,
+            content,
+            options.as_deref(),
+            answer_key,
+            points,
+            &knowledge_points,
+            now,
+            question.id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(question)
+    }
+
+    /// 删除题目，已被试卷使用的题目由外键约束拒绝
+    pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
+        let result = sqlx::query!("DELETE FROM questions WHERE id = $1", id)
+            .execute(pool)
+            .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
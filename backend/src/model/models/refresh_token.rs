@@ -0,0 +1,143 @@
+//! 刷新令牌模型
+//!
+//! 提供刷新令牌的数据结构和数据库操作方法，令牌只保存哈希
+
+use serde::Serialize;
+use sqlx::{Error, PgExecutor};
+use time::OffsetDateTime;
+use uuid::Uuid;
+
+/// 刷新令牌结构体
+#[derive(Debug, Clone, Serialize)]
+pub struct RefreshToken {
+    /// 令牌ID
+    pub id: Uuid,
+    /// 用户ID
+    pub user_id: Uuid,
+    /// 令牌族ID
+    pub family_id: Uuid,
+    /// 令牌哈希
+    #[serde(skip_serializing)]
+    pub token_hash: String,
+    /// 过期时间
+    pub expires_at: OffsetDateTime,
+    /// 轮换时间
+    pub rotated_at: Option<OffsetDateTime>,
+    /// 撤销时间
+    pub revoked_at: Option<OffsetDateTime>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+}
+
+impl RefreshToken {
+    /// 创建刷新令牌
+    pub async fn create<'e, E: PgExecutor<'e>>(
+        executor: E,
+        user_id: Uuid,
+        family_id: Uuid,
+        token_hash: &str,
+        expires_at: OffsetDateTime,
+    ) -> Result<Self, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+
+        let token = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO refresh_tokens (id, user_id, family_id, token_hash, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, user_id, family_id, token_hash, expires_at, rotated_at, revoked_at, created_at
+            "#,
+            id,
+            user_id,
+            family_id,
+            token_hash,
+            expires_at,
+            now
+        )
+        .fetch_one(executor)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// 根据令牌哈希查找刷新令牌并加锁
+    pub async fn find_by_token_hash_for_update<'e, E: PgExecutor<'e>>(
+        executor: E,
+        token_hash: &str,
+    ) -> Result<Option<Self>, Error> {
+        let token = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, user_id, family_id, token_hash, expires_at, rotated_at, revoked_at, created_at
+            FROM refresh_tokens
+            WHERE token_hash = $1
+            FOR UPDATE
+            "#,
+            token_hash
+        )
+        .fetch_optional(executor)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// 将刷新令牌标记为已轮换
+    pub async fn mark_rotated<'e, E: PgExecutor<'e>>(executor: E, id: Uuid) -> Result<(), Error> {
+        let now = OffsetDateTime::now_utc();
+
+        sqlx::query!(
+            "UPDATE refresh_tokens SET rotated_at = $1 WHERE id = $2",
+            now,
+            id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(())
+    }
+
+    /// 撤销整个令牌族，返回撤销的令牌数量
+    pub async fn revoke_family<'e, E: PgExecutor<'e>>(
+        executor: E,
+        family_id: Uuid,
+    ) -> Result<u64, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = $1
+            WHERE family_id = $2 AND revoked_at IS NULL
+            "#,
+            now,
+            family_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// 撤销用户的全部刷新令牌，返回撤销的令牌数量
+    pub async fn revoke_all_for_user<'e, E: PgExecutor<'e>>(
+        executor: E,
+        user_id: Uuid,
+    ) -> Result<u64, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let result = sqlx::query!(
+            r#"
+            UPDATE refresh_tokens
+            SET revoked_at = $1
+            WHERE user_id = $2 AND revoked_at IS NULL
+            "#,
+            now,
+            user_id
+        )
+        .execute(executor)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,338 @@
+//! 报告单模型
+//!
+//! 汇总学生在指定期间的上课记录、作业和考试成绩，按模板渲染后生成 PDF 报告单
+
+use minijinja::{Environment, Value, context};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, postgres::PgPool};
+use std::collections::HashMap;
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use super::exam_record::ExamRecord;
+use super::user::{User, UserWithDetails};
+use crate::config::ReportConfig;
+use crate::error::{AppError, AppErrorType};
+use crate::model::date_format;
+use crate::model::pdf::TextDocument;
+
+/// 内置的报告单模板
+const DEFAULT_TEMPLATE: &str = include_str!("../../../templates/report_card.txt");
+
+/// 生成报告单的统计期间
+#[derive(Debug, Default, Deserialize)]
+pub struct ReportCardParams {
+    /// 日期起始（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    /// 日期截止（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+}
+
+impl ReportCardParams {
+    /// 判断日期是否在统计期间内
+    fn contains(&self, date: Date) -> bool {
+        self.start_date.is_none_or(|start| date >= start)
+            && self.end_date.is_none_or(|end| date <= end)
+    }
+}
+
+/// 报告单中的学生
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportStudent {
+    /// 学生ID
+    pub id: Uuid,
+    /// 用户名
+    pub username: String,
+    /// 显示名称
+    pub display_name: Option<String>,
+    /// 年级
+    pub grade: Option<i32>,
+}
+
+/// 报告单中的一次上课
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportCourse {
+    /// 上课日期
+    #[serde(with = "date_format::date")]
+    pub class_date: Date,
+    /// 课程名称
+    pub course_name: String,
+    /// 上课内容
+    pub content: String,
+    /// 上课表现
+    pub performance: Option<String>,
+}
+
+/// 报告单中的一次作业
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportHomework {
+    /// 提交日期
+    #[serde(with = "date_format::date")]
+    pub submission_date: Date,
+    /// 作业标题
+    pub title: String,
+    /// 评分
+    pub grade: Option<String>,
+    /// 反馈
+    pub feedback: Option<String>,
+}
+
+/// 报告单中的一次考试
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportExam {
+    /// 完成日期
+    #[serde(with = "date_format::date")]
+    pub completion_date: Date,
+    /// 试卷标题
+    pub title: String,
+    /// 分数
+    pub score: Option<Decimal>,
+    /// 同场考试的平均分
+    pub cohort_mean: Option<Decimal>,
+    /// 备注
+    pub notes: Option<String>,
+}
+
+/// 学生报告单，即模板的渲染数据
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportCard {
+    /// 学生
+    pub student: ReportStudent,
+    /// 统计期间起始
+    #[serde(with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    /// 统计期间截止
+    #[serde(with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+    /// 生成日期
+    #[serde(with = "date_format::date")]
+    pub generated_on: Date,
+    /// 按日期排列的上课记录
+    pub courses: Vec<ReportCourse>,
+    /// 按日期排列的作业
+    pub homeworks: Vec<ReportHomework>,
+    /// 按日期排列的考试
+    pub exams: Vec<ReportExam>,
+    /// 期间内有分数的考试的平均分，保留两位小数
+    pub exam_average: Option<Decimal>,
+}
+
+/// 生成报告单所需的模板和字体
+#[derive(Debug, Clone)]
+pub struct ReportAssets {
+    /// 模板源码
+    template: String,
+    /// TrueType 字体文件内容
+    font: Vec<u8>,
+}
+
+impl ReportAssets {
+    /// 按配置读取模板和字体
+    pub async fn load(config: &ReportConfig) -> Result<Self, AppError> {
+        let template = match &config.template_path {
+            Some(path) => tokio::fs::read_to_string(path).await.map_err(|e| {
+                AppError::new_message(
+                    &format!("无法读取报告单模板 {}: {}", path, e),
+                    AppErrorType::Internal,
+                )
+            })?,
+            None => DEFAULT_TEMPLATE.to_string(),
+        };
+        let font = tokio::fs::read(&config.font_path).await.map_err(|e| {
+            AppError::new_message(
+                &format!("无法读取报告单字体 {}: {}", config.font_path, e),
+                AppErrorType::Internal,
+            )
+        })?;
+
+        Ok(Self { template, font })
+    }
+}
+
+impl ReportCard {
+    /// 汇总学生的报告单，用户不存在或不是学生时返回空
+    pub async fn for_student(
+        pool: &PgPool,
+        student_id: Uuid,
+        params: &ReportCardParams,
+    ) -> Result<Option<Self>, Error> {
+        match User::find_with_details(pool, student_id).await? {
+            Some(details) if details.user.role.to_lowercase() == "student" => {
+                Ok(Some(Self::from_details(pool, details, params).await?))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// 汇总指定年级学生的报告单，按用户名排列
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn for_grade(
+        pool: &PgPool,
+        grade: i32,
+        students: Option<&[Uuid]>,
+        params: &ReportCardParams,
+    ) -> Result<Vec<Self>, Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT id FROM users
+            WHERE role = 'student' AND grade = $1 AND ($2::uuid[] IS NULL OR id = ANY($2))
+            ORDER BY username
+            "#,
+            grade,
+            students
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let mut cards = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(card) = Self::for_student(pool, id, params).await? {
+                cards.push(card);
+            }
+        }
+        Ok(cards)
+    }
+
+    /// 从学生详细信息中筛选统计期间内的记录，并补充课程名称、试卷标题和平均分
+    async fn from_details(
+        pool: &PgPool,
+        details: UserWithDetails,
+        params: &ReportCardParams,
+    ) -> Result<Self, Error> {
+        let UserWithDetails {
+            user,
+            mut course_records,
+            mut homeworks,
+            mut exam_records,
+        } = details;
+        course_records.retain(|record| params.contains(record.class_date));
+        homeworks.retain(|homework| params.contains(homework.submission_date));
+        exam_records.retain(|record| params.contains(record.completion_date));
+
+        let course_ids: Vec<Uuid> = course_records.iter().map(|r| r.course_id).collect();
+        let course_names: HashMap<Uuid, String> = sqlx::query!(
+            "SELECT id, name FROM courses WHERE id = ANY($1)",
+            &course_ids
+        )
+        .fetch_all(pool)
+        .await?
+        .into_iter()
+        .map(|row| (row.id, row.name))
+        .collect();
+
+        let exam_ids: Vec<Uuid> = exam_records.iter().map(|r| r.exam_id).collect();
+        let exam_titles: HashMap<Uuid, String> =
+            sqlx::query!("SELECT id, title FROM exams WHERE id = ANY($1)", &exam_ids)
+                .fetch_all(pool)
+                .await?
+                .into_iter()
+                .map(|row| (row.id, row.title))
+                .collect();
+        let cohort_means = ExamRecord::cohort_means(pool, &exam_ids).await?;
+
+        course_records.sort_by_key(|record| (record.class_date, record.created_at));
+        homeworks.sort_by_key(|homework| (homework.submission_date, homework.created_at));
+        exam_records.sort_by_key(|record| (record.completion_date, record.created_at));
+
+        let scores: Vec<Decimal> = exam_records.iter().filter_map(|r| r.score).collect();
+        let exam_average = (!scores.is_empty())
+            .then(|| (scores.iter().sum::<Decimal>() / Decimal::from(scores.len())).round_dp(2));
+
+        Ok(Self {
+            student: ReportStudent {
+                id: user.id,
+                username: user.username,
+                display_name: user.display_name,
+                grade: user.grade,
+            },
+            start_date: params.start_date,
+            end_date: params.end_date,
+            generated_on: OffsetDateTime::now_utc().date(),
+            courses: course_records
+                .into_iter()
+                .map(|record| ReportCourse {
+                    class_date: record.class_date,
+                    course_name: course_names
+                        .get(&record.course_id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    content: record.content,
+                    performance: record.performance,
+                })
+                .collect(),
+            homeworks: homeworks
+                .into_iter()
+                .map(|homework| ReportHomework {
+                    submission_date: homework.submission_date,
+                    title: homework.title,
+                    grade: homework.grade,
+                    feedback: homework.feedback,
+                })
+                .collect(),
+            exams: exam_records
+                .into_iter()
+                .map(|record| ReportExam {
+                    completion_date: record.completion_date,
+                    title: exam_titles
+                        .get(&record.exam_id)
+                        .cloned()
+                        .unwrap_or_default(),
+                    score: record.score,
+                    cohort_mean: cohort_means.get(&record.exam_id).copied(),
+                    notes: record.notes,
+                })
+                .collect(),
+            exam_average,
+        })
+    }
+
+    /// 报告单文件名，不含扩展名
+    pub fn file_name(&self) -> String {
+        let name = match &self.student.display_name {
+            Some(name) => format!("{}-{}", self.student.username, name),
+            None => self.student.username.clone(),
+        };
+        name.replace(['/', '\\'], "_")
+    }
+
+    /// 按模板渲染正文，模板中还可以使用 `school.name` 和 `school.header`
+    pub fn render(&self, template: &str, config: &ReportConfig) -> Result<String, AppError> {
+        let mut env = Environment::new();
+        env.set_trim_blocks(true);
+        env.set_lstrip_blocks(true);
+
+        let ctx = context! {
+            school => context! { name => &config.school_name, header => &config.header },
+            ..Value::from_serialize(self)
+        };
+        env.render_str(template, ctx).map_err(|e| {
+            AppError::new_message(&format!("报告单模板错误: {}", e), AppErrorType::Internal)
+        })
+    }
+
+    /// 生成 PDF 报告单
+    pub fn to_pdf(
+        &self,
+        assets: &ReportAssets,
+        config: &ReportConfig,
+    ) -> Result<Vec<u8>, AppError> {
+        let body = self.render(&assets.template, config)?;
+        let name = self
+            .student
+            .display_name
+            .as_deref()
+            .unwrap_or(&self.student.username);
+
+        TextDocument {
+            title: &format!("{} 学习报告单", name),
+            header_title: &config.school_name,
+            header_lines: &config.header,
+            body: &body,
+        }
+        .to_pdf(&assets.font)
+    }
+}
@@ -0,0 +1,538 @@
+//! 作业提交模型
+//!
+//! 记录每个学生对作业任务的提交，状态依次为已布置、已提交（或逾期提交）、已评分和已发还
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+use validator::Validate;
+
+use super::assignment::Assignment;
+use super::grading_scale::GradingScale;
+use super::late_policy::{LatePolicy, Penalty};
+use crate::model::{date_format, validate};
+
+/// 提交状态枚举
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmissionStatus {
+    /// 已布置，尚未提交
+    Assigned,
+    /// 已提交
+    Submitted,
+    /// 逾期提交
+    Late,
+    /// 已评分
+    Graded,
+    /// 已发还学生
+    Returned,
+}
+
+impl AsRef<str> for SubmissionStatus {
+    fn as_ref(&self) -> &str {
+        match self {
+            SubmissionStatus::Assigned => "assigned",
+            SubmissionStatus::Submitted => "submitted",
+            SubmissionStatus::Late => "late",
+            SubmissionStatus::Graded => "graded",
+            SubmissionStatus::Returned => "returned",
+        }
+    }
+}
+
+/// 作业提交结构体
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Submission {
+    /// 提交ID
+    pub id: Uuid,
+    /// 作业任务ID
+    pub assignment_id: Uuid,
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 提交状态
+    pub status: String,
+    /// 作业文件路径
+    pub file_path: Option<String>,
+    /// 提交日期，未提交时为空
+    #[serde(with = "date_format::date::option")]
+    pub submission_date: Option<Date>,
+    /// 评分，有分数时为按评分等级制换算的等级
+    pub grade: Option<String>,
+    /// 反馈
+    pub feedback: Option<String>,
+    /// 提交时的迟交天数，按时提交为0
+    pub days_late: i32,
+    /// 扣分前的分数
+    pub raw_score: Option<Decimal>,
+    /// 迟交扣除的百分比
+    pub late_penalty: Option<Decimal>,
+    /// 扣分后的分数
+    pub score: Option<Decimal>,
+    /// 创建时间
+    pub created_at: OffsetDateTime,
+    /// 更新时间
+    pub updated_at: OffsetDateTime,
+}
+
+/// 布置作业的请求数据结构
+#[derive(Debug, Default, Deserialize, Validate)]
+pub struct AssignRequest {
+    /// 学生ID列表，为空时布置给课程中所有在读的学生
+    pub student_ids: Option<Vec<Uuid>>,
+}
+
+/// 提交作业的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct SubmitRequest {
+    /// 作业任务ID
+    pub assignment_id: Uuid,
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 提交日期，默认为当天，学生只能按当天提交
+    #[serde(default, with = "date_format::date::option")]
+    pub submission_date: Option<Date>,
+}
+
+/// 评分提交的请求数据结构
+#[derive(Debug, Deserialize, Validate)]
+pub struct GradeSubmissionRequest {
+    /// 评分
+    #[validate(length(max = 10, message = "评分不能超过10个字符"))]
+    pub grade: Option<String>,
+    /// 分数，按课程的迟交扣分规则扣分后记录，评分按课程的评分等级制换算
+    #[validate(custom(function = "validate::score"))]
+    pub score: Option<Decimal>,
+    /// 反馈
+    pub feedback: Option<String>,
+}
+
+/// 记录分数的结果
+#[derive(Debug, Clone)]
+pub struct ScoreRecord {
+    /// 扣分前的分数
+    pub raw_score: Decimal,
+    /// 迟交扣分
+    pub penalty: Penalty,
+    /// 按评分等级制显示的评分
+    pub grade: String,
+}
+
+/// 查询提交的筛选条件
+#[derive(Debug, Default, Deserialize)]
+pub struct SubmissionFilter {
+    /// 提交状态
+    pub status: Option<SubmissionStatus>,
+}
+
+/// 未提交作业的学生
+#[derive(Debug, Clone, Serialize)]
+pub struct MissingSubmission {
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 用户名
+    pub username: String,
+    /// 显示名称
+    pub display_name: Option<String>,
+    /// 年级
+    pub grade: Option<i32>,
+    /// 提交ID，尚未布置给该学生时为空
+    pub submission_id: Option<Uuid>,
+}
+
+/// 逾期未交的作业
+#[derive(Debug, Clone, Serialize)]
+pub struct OverdueWork {
+    /// 作业任务ID
+    pub assignment_id: Uuid,
+    /// 课程ID
+    pub course_id: Option<Uuid>,
+    /// 作业标题
+    pub title: String,
+    /// 截止日期
+    #[serde(with = "date_format::date")]
+    pub due_date: Date,
+    /// 布置作业的教师ID
+    pub teacher_id: Option<Uuid>,
+    /// 学生ID
+    pub student_id: Uuid,
+    /// 用户名
+    pub username: String,
+    /// 显示名称
+    pub display_name: Option<String>,
+    /// 提交ID，尚未布置给该学生时为空
+    pub submission_id: Option<Uuid>,
+    /// 逾期天数
+    pub days_overdue: i32,
+}
+
+impl Submission {
+    /// 将作业布置给学生，已布置过的学生跳过，返回新布置的提交
+    pub async fn assign<'e, E: PgExecutor<'e>>(
+        executor: E,
+        assignment_id: Uuid,
+        student_ids: &[Uuid],
+    ) -> Result<Vec<Self>, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let submissions = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO submissions (id, assignment_id, student_id, status, created_at, updated_at)
+            SELECT gen_random_uuid(), $1, student_id, 'assigned', $3, $3
+            FROM UNNEST($2::uuid[]) AS student_id
+            ON CONFLICT (assignment_id, student_id) DO NOTHING
+            RETURNING id, assignment_id, student_id, status, file_path, submission_date, grade, feedback,
+                      days_late, raw_score, late_penalty, score, created_at, updated_at
+            "#,
+            assignment_id,
+            student_ids,
+            now
+        )
+        .fetch_all(executor)
+        .await?;
+
+        Ok(submissions)
+    }
+
+    /// 提交作业，尚未布置给该学生时一并布置
+    ///
+    /// 按截止日期记录迟交天数，迟交时状态为逾期提交。发还后重新提交时清除原有的评分、分数和反馈。
+    /// 已评分未发还的作业不能重新提交，此时返回空
+    pub async fn submit(
+        pool: &PgPool,
+        assignment_id: Uuid,
+        student_id: Uuid,
+        submission_date: Date,
+        due_date: Option<Date>,
+    ) -> Result<Option<Self>, Error> {
+        let id = Uuid::new_v4();
+        let now = OffsetDateTime::now_utc();
+        let days_late = days_late(submission_date, due_date);
+        let status = if days_late > 0 {
+            SubmissionStatus::Late
+        } else {
+            SubmissionStatus::Submitted
+        };
+
+        let submission = sqlx::query_as!(
+            Self,
+            r#"
+            INSERT INTO submissions (id, assignment_id, student_id, status, submission_date, days_late, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $7)
+            ON CONFLICT (assignment_id, student_id) DO UPDATE
+            SET status = EXCLUDED.status, submission_date = EXCLUDED.submission_date,
+                days_late = EXCLUDED.days_late, grade = NULL, raw_score = NULL, late_penalty = NULL,
+                score = NULL, feedback = NULL, updated_at = EXCLUDED.updated_at
+            WHERE submissions.status <> 'graded'
+            RETURNING id, assignment_id, student_id, status, file_path, submission_date, grade, feedback,
+                      days_late, raw_score, late_penalty, score, created_at, updated_at
+            "#,
+            id,
+            assignment_id,
+            student_id,
+            status.as_ref(),
+            submission_date,
+            days_late,
+            now
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(submission)
+    }
+
+    /// 判断学生能否提交作业任务：已布置给该学生，或学生在读作业任务所属的课程
+    pub async fn can_submit(
+        pool: &PgPool,
+        assignment_id: Uuid,
+        student_id: Uuid,
+    ) -> Result<bool, Error> {
+        let allowed = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM submissions WHERE assignment_id = $1 AND student_id = $2)
+                OR EXISTS (
+                    SELECT 1
+                    FROM assignments a
+                    JOIN enrollments e ON e.course_id = a.course_id
+                    WHERE a.id = $1 AND e.student_id = $2 AND e.dropped_on IS NULL
+                ) AS "allowed!"
+            "#,
+            assignment_id,
+            student_id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(allowed)
+    }
+
+    /// 根据ID查找提交
+    pub async fn find_by_id(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let submission = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, assignment_id, student_id, status, file_path, submission_date, grade, feedback,
+                      days_late, raw_score, late_penalty, score, created_at, updated_at
+            FROM submissions
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(submission)
+    }
+
+    /// 获取作业任务的提交，按学生用户名排列
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn find_by_assignment(
+        pool: &PgPool,
+        assignment_id: Uuid,
+        filter: &SubmissionFilter,
+        students: Option<&[Uuid]>,
+    ) -> Result<Vec<Self>, Error> {
+        let submissions = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT s.id, s.assignment_id, s.student_id, s.status, s.file_path, s.submission_date,
+                   s.grade, s.feedback, s.days_late, s.raw_score, s.late_penalty, s.score,
+                   s.created_at, s.updated_at
+            FROM submissions s
+            JOIN users u ON u.id = s.student_id
+            WHERE s.assignment_id = $1
+              AND ($2::text IS NULL OR s.status = $2)
+              AND ($3::uuid[] IS NULL OR s.student_id = ANY($3))
+            ORDER BY u.username, s.id
+            "#,
+            assignment_id,
+            filter.status.as_ref().map(AsRef::as_ref),
+            students
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(submissions)
+    }
+
+    /// 获取尚未提交作业的学生，按年级和用户名排列
+    ///
+    /// 包括课程中在读但没有提交的学生和已布置但没有提交的学生，
+    /// `students` 限定学生范围，为空时不限
+    pub async fn find_missing(
+        pool: &PgPool,
+        assignment_id: Uuid,
+        students: Option<&[Uuid]>,
+    ) -> Result<Vec<MissingSubmission>, Error> {
+        let missing = sqlx::query_as!(
+            MissingSubmission,
+            r#"
+            WITH expected AS (
+                SELECT e.student_id
+                FROM enrollments e
+                JOIN assignments a ON a.course_id = e.course_id
+                WHERE a.id = $1 AND e.dropped_on IS NULL
+                UNION
+                SELECT student_id FROM submissions WHERE assignment_id = $1
+            )
+            SELECT u.id AS student_id, u.username, u.display_name, u.grade, s.id AS "submission_id?"
+            FROM expected x
+            JOIN users u ON u.id = x.student_id
+            LEFT JOIN submissions s ON s.assignment_id = $1 AND s.student_id = u.id
+            WHERE (s.id IS NULL OR s.status = 'assigned')
+              AND ($2::uuid[] IS NULL OR u.id = ANY($2))
+            ORDER BY u.grade NULLS LAST, u.username
+            "#,
+            assignment_id,
+            students
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(missing)
+    }
+
+    /// 获取截至 `today` 已过截止日期仍未提交的作业，按截止日期和用户名排列
+    ///
+    /// 与 [`Self::find_missing`] 相同，应交作业的学生包括课程中在读的学生和已布置的学生。
+    /// `student_id`、`teacher_id` 分别按学生和布置作业的教师筛选，`students` 限定学生范围，为空时不限
+    pub async fn find_overdue(
+        pool: &PgPool,
+        student_id: Option<Uuid>,
+        teacher_id: Option<Uuid>,
+        today: Date,
+        students: Option<&[Uuid]>,
+    ) -> Result<Vec<OverdueWork>, Error> {
+        let overdue = sqlx::query_as!(
+            OverdueWork,
+            r#"
+            WITH expected AS (
+                SELECT a.id AS assignment_id, e.student_id
+                FROM assignments a
+                JOIN enrollments e ON e.course_id = a.course_id AND e.dropped_on IS NULL
+                WHERE a.due_date < $3
+                UNION
+                SELECT s.assignment_id, s.student_id
+                FROM submissions s
+                JOIN assignments a ON a.id = s.assignment_id
+                WHERE a.due_date < $3
+            )
+            SELECT a.id AS assignment_id, a.course_id, a.title, a.due_date AS "due_date!",
+                   a.teacher_id, u.id AS student_id, u.username, u.display_name,
+                   s.id AS "submission_id?", ($3 - a.due_date) AS "days_overdue!"
+            FROM expected x
+            JOIN assignments a ON a.id = x.assignment_id
+            JOIN users u ON u.id = x.student_id
+            LEFT JOIN submissions s ON s.assignment_id = a.id AND s.student_id = u.id
+            WHERE (s.id IS NULL OR s.status = 'assigned')
+              AND ($1::uuid IS NULL OR u.id = $1)
+              AND ($2::uuid IS NULL OR a.teacher_id = $2)
+              AND ($4::uuid[] IS NULL OR u.id = ANY($4))
+            ORDER BY a.due_date, u.username, a.id
+            "#,
+            student_id,
+            teacher_id,
+            today,
+            students
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(overdue)
+    }
+
+    /// 评分，只能评分已提交的作业，评分后状态变为已评分
+    ///
+    /// 记录分数时见 [`Self::score_record`]，评分取换算的等级，调用方拒绝同时填写评分和分数
+    pub async fn grade(
+        pool: &PgPool,
+        submission: &Self,
+        req: GradeSubmissionRequest,
+    ) -> Result<Option<Self>, Error> {
+        let record = match req.score {
+            Some(raw_score) => Some(
+                Self::score_record(
+                    pool,
+                    submission.assignment_id,
+                    submission.days_late,
+                    raw_score,
+                )
+                .await?,
+            ),
+            None => None,
+        };
+        let grade = match &record {
+            Some(record) => Some(record.grade.clone()),
+            None => req.grade,
+        };
+        let now = OffsetDateTime::now_utc();
+
+        let submission = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE submissions
+            SET grade = COALESCE($1, grade), feedback = COALESCE($2, feedback),
+                raw_score = COALESCE($3, raw_score), late_penalty = COALESCE($4, late_penalty),
+                score = COALESCE($5, score), status = 'graded', updated_at = $6
+            WHERE id = $7 AND status <> 'assigned'
+            RETURNING id, assignment_id, student_id, status, file_path, submission_date, grade, feedback,
+                      days_late, raw_score, late_penalty, score, created_at, updated_at
+            "#,
+            grade,
+            req.feedback,
+            req.score,
+            record.as_ref().map(|record| record.penalty.percent),
+            record.as_ref().map(|record| record.penalty.score),
+            now,
+            submission.id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(submission)
+    }
+
+    /// 计算作业任务的提交要记录的分数
+    ///
+    /// 先按课程的迟交扣分规则扣分，再按作业任务的满分和课程的评分等级制把扣分后的分数换算为评分
+    pub async fn score_record(
+        pool: &PgPool,
+        assignment_id: Uuid,
+        days_late: i32,
+        raw_score: Decimal,
+    ) -> Result<ScoreRecord, Error> {
+        let policy = LatePolicy::find_by_assignment(pool, assignment_id).await?;
+        let penalty = LatePolicy::apply(policy.as_ref(), raw_score, days_late);
+
+        let scale = GradingScale::find_by_assignment(pool, assignment_id).await?;
+        let max_score = Assignment::find_by_id(pool, assignment_id)
+            .await?
+            .and_then(|assignment| assignment.max_score);
+        let grade = GradingScale::convert(scale.as_ref(), penalty.score, max_score).value;
+
+        Ok(ScoreRecord {
+            raw_score,
+            penalty,
+            grade,
+        })
+    }
+
+    /// 将已评分的作业发还学生
+    pub async fn return_to_student(pool: &PgPool, id: Uuid) -> Result<Option<Self>, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let submission = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE submissions
+            SET status = 'returned', updated_at = $1
+            WHERE id = $2 AND status = 'graded'
+            RETURNING id, assignment_id, student_id, status, file_path, submission_date, grade, feedback,
+                      days_late, raw_score, late_penalty, score, created_at, updated_at
+            "#,
+            now,
+            id
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(submission)
+    }
+
+    /// 更新提交的文件路径
+    pub async fn update_file_path(
+        pool: &PgPool,
+        id: Uuid,
+        file_path: Option<&str>,
+    ) -> Result<Self, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let submission = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE submissions
+            SET file_path = $1, updated_at = $2
+            WHERE id = $3
+            RETURNING id, assignment_id, student_id, status, file_path, submission_date, grade, feedback,
+                      days_late, raw_score, late_penalty, score, created_at, updated_at
+            "#,
+            file_path,
+            now,
+            id
+        )
+        .fetch_one(pool)
+        .await?;
+
+        Ok(submission)
+    }
+}
+
+/// 提交日期晚于截止日期的天数，没有截止日期或按时提交时为0
+pub fn days_late(submission_date: Date, due_date: Option<Date>) -> i32 {
+    due_date.map_or(0, |due_date| {
+        (submission_date - due_date).whole_days().max(0) as i32
+    })
+}
@@ -0,0 +1,158 @@
+//! 学生时间线模型
+//!
+//! 将学生的上课记录、作业、试卷记录和考勤按时间合并为统一的事件流
+
+use serde::{Deserialize, Serialize};
+use sqlx::{Error, FromRow, Postgres, QueryBuilder, postgres::PgPool};
+use time::{Date, OffsetDateTime};
+use uuid::Uuid;
+
+use crate::model::date_format;
+use crate::model::query::{self, Page, PageParams, SortOrder};
+
+/// 合并各类事件的公共表表达式，跟在绑定学生ID的 `student` 公共表表达式之后
+const EVENTS: &str = r#"
+    , events AS (
+        SELECT 'class' AS event_type, cr.id, cr.class_date AS date, cr.created_at AS occurred_at,
+               c.name AS title, cr.content AS detail
+        FROM course_records cr
+        JOIN courses c ON c.id = cr.course_id
+        WHERE cr.student_id = (SELECT id FROM student)
+        UNION ALL
+        SELECT 'homework_submitted', s.id, s.submission_date, s.created_at, a.title, a.instructions
+        FROM submissions s
+        JOIN assignments a ON a.id = s.assignment_id
+        WHERE s.student_id = (SELECT id FROM student) AND s.submission_date IS NOT NULL
+        UNION ALL
+        SELECT 'homework_graded', s.id, s.updated_at::date, s.updated_at, a.title,
+               concat_ws(' ', s.grade, s.feedback)
+        FROM submissions s
+        JOIN assignments a ON a.id = s.assignment_id
+        WHERE s.student_id = (SELECT id FROM student) AND s.grade IS NOT NULL
+        UNION ALL
+        SELECT 'exam', er.id, er.completion_date, er.created_at, e.title, er.score::text
+        FROM exam_records er
+        JOIN exams e ON e.id = er.exam_id
+        WHERE er.student_id = (SELECT id FROM student)
+        UNION ALL
+        SELECT 'attendance', a.id, a.class_date, a.created_at, c.name, concat_ws(' ', a.status, a.reason)
+        FROM attendance a
+        JOIN courses c ON c.id = a.course_id
+        WHERE a.student_id = (SELECT id FROM student)
+    )
+"#;
+
+/// 时间线事件类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimelineEventType {
+    /// 上课
+    Class,
+    /// 提交作业
+    HomeworkSubmitted,
+    /// 作业评分
+    HomeworkGraded,
+    /// 考试
+    Exam,
+    /// 考勤
+    Attendance,
+}
+
+impl AsRef<str> for TimelineEventType {
+    fn as_ref(&self) -> &str {
+        match self {
+            TimelineEventType::Class => "class",
+            TimelineEventType::HomeworkSubmitted => "homework_submitted",
+            TimelineEventType::HomeworkGraded => "homework_graded",
+            TimelineEventType::Exam => "exam",
+            TimelineEventType::Attendance => "attendance",
+        }
+    }
+}
+
+/// 时间线事件
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct TimelineEvent {
+    /// 事件类型
+    pub event_type: String,
+    /// 相关记录ID（课程记录、作业、试卷记录或考勤的ID）
+    pub id: Uuid,
+    /// 事件日期
+    #[serde(with = "date_format::date")]
+    pub date: Date,
+    /// 记录时间，同一天的事件按此排序
+    pub occurred_at: OffsetDateTime,
+    /// 标题（课程名称、作业标题或试卷标题）
+    pub title: String,
+    /// 详情（上课内容、作业描述、评分和反馈、分数或出勤状态）
+    pub detail: Option<String>,
+}
+
+/// 查询时间线的筛选条件
+#[derive(Debug, Default, Deserialize)]
+pub struct TimelineFilter {
+    /// 事件类型
+    pub event_type: Option<TimelineEventType>,
+    /// 日期起始（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub start_date: Option<Date>,
+    /// 日期截止（含）
+    #[serde(default, with = "date_format::date::option")]
+    pub end_date: Option<Date>,
+    /// 排序方向，默认为降序（最新的在前）
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+impl TimelineEvent {
+    /// 分页获取学生的时间线
+    pub async fn find_by_student_id(
+        pool: &PgPool,
+        student_id: Uuid,
+        filter: &TimelineFilter,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let mut builder = Self::events(student_id);
+        builder.push("SELECT COUNT(*) FROM events WHERE TRUE");
+        Self::push_filter(&mut builder, filter);
+        let total: i64 = builder.build_query_scalar().fetch_one(pool).await?;
+
+        let mut builder = Self::events(student_id);
+        builder
+            .push("SELECT event_type, id, date, occurred_at, title, detail FROM events WHERE TRUE");
+        Self::push_filter(&mut builder, filter);
+        let order = filter.order.as_sql();
+        builder.push(format!(
+            " ORDER BY date {order}, occurred_at {order}, id {order}"
+        ));
+        query::push_page(&mut builder, page);
+        let events = builder.build_query_as::<Self>().fetch_all(pool).await?;
+
+        Ok(Page::new(events, total, page))
+    }
+
+    /// 创建以学生全部事件为数据源的查询
+    fn events(student_id: Uuid) -> QueryBuilder<'static, Postgres> {
+        let mut builder = QueryBuilder::new("WITH student AS (SELECT ");
+        builder
+            .push_bind(student_id)
+            .push("::uuid AS id)")
+            .push(EVENTS);
+        builder
+    }
+
+    /// 追加筛选条件
+    fn push_filter(builder: &mut QueryBuilder<'_, Postgres>, filter: &TimelineFilter) {
+        if let Some(event_type) = filter.event_type {
+            builder
+                .push(" AND event_type = ")
+                .push_bind(event_type.as_ref().to_string());
+        }
+        if let Some(start_date) = filter.start_date {
+            builder.push(" AND date >= ").push_bind(start_date);
+        }
+        if let Some(end_date) = filter.end_date {
+            builder.push(" AND date <= ").push_bind(end_date);
+        }
+    }
+}
@@ -4,45 +4,61 @@
 
 use bcrypt;
 use serde::{Deserialize, Serialize};
-use sqlx::{Error, postgres::PgPool};
+use sqlx::{Error, PgExecutor, postgres::PgPool};
+use std::str::FromStr;
 use time::OffsetDateTime;
 use uuid::Uuid;
+use validator::Validate;
+
+use crate::model::query::{Page, PageParams};
+use crate::model::validate;
 
 /// 用户角色枚举
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
 #[serde(rename_all = "lowercase")]
 pub enum UserRole {
+    /// 管理员角色
+    Admin,
     /// 教师角色
     Teacher,
     /// 学生角色
+    #[default]
     Student,
-}
-
-impl Default for UserRole {
-    fn default() -> Self {
-        Self::Student
-    }
+    /// 家长角色
+    Parent,
 }
 
 impl AsRef<str> for UserRole {
     fn as_ref(&self) -> &str {
         match self {
+            UserRole::Admin => "admin",
             UserRole::Teacher => "teacher",
             UserRole::Student => "student",
+            UserRole::Parent => "parent",
         }
     }
 }
 
-impl From<String> for UserRole {
-    fn from(s: String) -> Self {
-        match s.to_lowercase().as_str() {
-            "teacher" => UserRole::Teacher,
-            "student" => UserRole::Student,
-            _ => UserRole::Student,
+impl FromStr for UserRole {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "admin" => Ok(UserRole::Admin),
+            "teacher" => Ok(UserRole::Teacher),
+            "student" => Ok(UserRole::Student),
+            "parent" => Ok(UserRole::Parent),
+            _ => Err(format!("未知的用户角色: {}", s)),
         }
     }
 }
 
+impl std::fmt::Display for UserRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.as_ref())
+    }
+}
+
 /// 用户结构体（整合了学生信息）
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct User {
@@ -80,15 +96,22 @@ pub struct User {
 }
 
 /// 创建用户的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct CreateUserRequest {
     /// 用户名
+    #[validate(length(min = 3, max = 50, message = "用户名长度必须在3到50个字符之间"))]
     pub username: String,
     /// 电子邮件
+    #[validate(
+        email(message = "电子邮件格式不正确"),
+        length(max = 100, message = "电子邮件不能超过100个字符")
+    )]
     pub email: String,
     /// 密码
+    #[validate(custom(function = "validate::password_strength"))]
     pub password: String,
     /// 显示名称
+    #[validate(length(max = 100, message = "显示名称不能超过100个字符"))]
     pub display_name: Option<String>,
     /// 头像URL
     pub avatar_url: Option<String>,
@@ -97,27 +120,39 @@ pub struct CreateUserRequest {
     /// 用户角色
     pub role: Option<String>,
     /// 年级（仅学生用户）
+    #[validate(range(min = 1, max = 3, message = "年级必须在1到3之间"))]
     pub grade: Option<i32>,
     /// 家长姓名（仅学生用户）
+    #[validate(length(max = 100, message = "家长姓名不能超过100个字符"))]
     pub parent_name: Option<String>,
     /// 家长电话（仅学生用户）
+    #[validate(regex(path = *validate::PHONE_REGEX, message = "家长电话格式不正确"))]
     pub parent_phone: Option<String>,
     /// 地址（仅学生用户）
     pub address: Option<String>,
     /// 备注（仅学生用户）
     pub notes: Option<String>,
+    /// 邀请令牌（仅注册时使用）
+    pub invitation_token: Option<String>,
 }
 
 /// 更新用户的请求数据结构
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Validate)]
 pub struct UpdateUserRequest {
     /// 用户名
+    #[validate(length(min = 3, max = 50, message = "用户名长度必须在3到50个字符之间"))]
     pub username: Option<String>,
     /// 电子邮件
+    #[validate(
+        email(message = "电子邮件格式不正确"),
+        length(max = 100, message = "电子邮件不能超过100个字符")
+    )]
     pub email: Option<String>,
     /// 密码
+    #[validate(custom(function = "validate::password_strength"))]
     pub password: Option<String>,
     /// 显示名称
+    #[validate(length(max = 100, message = "显示名称不能超过100个字符"))]
     pub display_name: Option<String>,
     /// 头像URL
     pub avatar_url: Option<String>,
@@ -126,10 +161,13 @@ pub struct UpdateUserRequest {
     /// 用户角色
     pub role: Option<String>,
     /// 年级（仅学生用户）
+    #[validate(range(min = 1, max = 3, message = "年级必须在1到3之间"))]
     pub grade: Option<i32>,
     /// 家长姓名（仅学生用户）
+    #[validate(length(max = 100, message = "家长姓名不能超过100个字符"))]
     pub parent_name: Option<String>,
     /// 家长电话（仅学生用户）
+    #[validate(regex(path = *validate::PHONE_REGEX, message = "家长电话格式不正确"))]
     pub parent_phone: Option<String>,
     /// 地址（仅学生用户）
     pub address: Option<String>,
@@ -161,7 +199,10 @@ pub struct UserWithDetails {
 
 impl User {
     /// 创建新用户（包含学生信息）
-    pub async fn create(pool: &PgPool, req: CreateUserRequest) -> Result<Self, Error> {
+    pub async fn create<'e, E: PgExecutor<'e>>(
+        executor: E,
+        req: CreateUserRequest,
+    ) -> Result<Self, Error> {
         let id = Uuid::new_v4();
         let now = OffsetDateTime::now_utc();
         let role = req.role.unwrap_or_else(|| "student".to_string());
@@ -196,7 +237,7 @@ impl User {
             now,
             now
         )
-        .fetch_one(pool)
+        .fetch_one(executor)
         .await?;
 
         Ok(user)
@@ -356,6 +397,33 @@ impl User {
         }
     }
 
+    /// 根据用户名设置用户角色
+    pub async fn set_role_by_username(
+        pool: &PgPool,
+        username: &str,
+        role: UserRole,
+    ) -> Result<Option<Self>, Error> {
+        let now = OffsetDateTime::now_utc();
+
+        let user = sqlx::query_as!(
+            Self,
+            r#"
+            UPDATE users
+            SET role = $1, updated_at = $2
+            WHERE username = $3
+            RETURNING id, username, email, password_hash, display_name, avatar_url, bio, role, 
+                     grade, parent_name, parent_phone, address, notes, created_at, updated_at
+            "#,
+            role.as_ref(),
+            now,
+            username
+        )
+        .fetch_optional(pool)
+        .await?;
+
+        Ok(user)
+    }
+
     /// 删除用户
     pub async fn delete(pool: &PgPool, id: Uuid) -> Result<bool, Error> {
         let result = sqlx::query!("DELETE FROM users WHERE id = $1", id)
@@ -367,11 +435,8 @@ impl User {
 
     /// 验证用户密码
     pub async fn verify_password(&self, password: &str) -> bool {
-        // 使用bcrypt验证密码
-        match bcrypt::verify(password, &self.password_hash) {
-            Ok(result) => result,
-            Err(_) => false, // 验证过程出错，返回验证失败
-        }
+        // 使用bcrypt验证密码，验证过程出错时返回验证失败
+        bcrypt::verify(password, &self.password_hash).unwrap_or_default()
     }
 
     /// 用户登录
@@ -433,40 +498,148 @@ impl User {
         }
     }
 
-    /// 按年级获取学生用户
-    pub async fn find_students_by_grade(pool: &PgPool, grade: i32) -> Result<Vec<Self>, Error> {
+    /// 查找用户名或电子邮件在给定列表中的用户
+    pub async fn find_by_usernames_or_emails(
+        pool: &PgPool,
+        usernames: &[String],
+        emails: &[String],
+    ) -> Result<Vec<Self>, Error> {
+        let users = sqlx::query_as!(
+            Self,
+            r#"
+            SELECT id, username, email, password_hash, display_name, avatar_url, bio, role,
+                   grade, parent_name, parent_phone, address, notes, created_at, updated_at
+            FROM users
+            WHERE username = ANY($1) OR email = ANY($2)
+            "#,
+            usernames,
+            emails
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(users)
+    }
+
+    /// 按年级分页获取学生用户
+    ///
+    /// `students` 限定学生范围，为空时不限
+    pub async fn find_students_by_grade(
+        pool: &PgPool,
+        grade: i32,
+        students: Option<&[Uuid]>,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM users
+            WHERE role = 'student' AND grade = $1 AND ($2::uuid[] IS NULL OR id = ANY($2))
+            "#,
+            grade,
+            students
+        )
+        .fetch_one(pool)
+        .await?;
+
         let students = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, username, email, password_hash, display_name, avatar_url, bio, role, 
+            SELECT id, username, email, password_hash, display_name, avatar_url, bio, role,
                    grade, parent_name, parent_phone, address, notes, created_at, updated_at
             FROM users
-            WHERE role = 'student' AND grade = $1
-            ORDER BY created_at DESC
+            WHERE role = 'student' AND grade = $1 AND ($2::uuid[] IS NULL OR id = ANY($2))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            grade,
+            students,
+            page.limit(),
+            page.offset()
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(Page::new(students, total, page))
+    }
+
+    /// 返回给定ID中属于学生的用户ID
+    pub async fn find_student_ids(pool: &PgPool, ids: &[Uuid]) -> Result<Vec<Uuid>, Error> {
+        let ids = sqlx::query_scalar!(
+            "SELECT id FROM users WHERE id = ANY($1) AND role = 'student'",
+            ids
+        )
+        .fetch_all(pool)
+        .await?;
+
+        Ok(ids)
+    }
+
+    /// 获取教师授课的学生ID
+    ///
+    /// 教师在课程记录或作业中关联过的学生，以及其负责班级中在读的学生都视为其授课学生
+    pub async fn find_taught_student_ids(
+        pool: &PgPool,
+        teacher_id: Uuid,
+    ) -> Result<Vec<Uuid>, Error> {
+        let ids = sqlx::query_scalar!(
+            r#"
+            SELECT student_id AS "student_id!" FROM course_records WHERE teacher_id = $1
+            UNION
+            SELECT s.student_id AS "student_id!"
+            FROM submissions s
+            JOIN assignments a ON a.id = s.assignment_id
+            WHERE a.teacher_id = $1
+            UNION
+            SELECT e.student_id AS "student_id!"
+            FROM enrollments e
+            JOIN class_groups g ON g.id = e.class_group_id
+            WHERE g.teacher_id = $1 AND e.dropped_on IS NULL
             "#,
-            grade
+            teacher_id
         )
         .fetch_all(pool)
         .await?;
 
-        Ok(students)
+        Ok(ids)
     }
 
-    /// 获取所有学生用户
-    pub async fn find_all_students(pool: &PgPool) -> Result<Vec<Self>, Error> {
+    /// 分页获取所有学生用户
+    ///
+    /// `students` 不为空时只返回其中的学生
+    pub async fn find_all_students(
+        pool: &PgPool,
+        students: Option<&[Uuid]>,
+        page: &PageParams,
+    ) -> Result<Page<Self>, Error> {
+        let total = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM users
+            WHERE role = 'student' AND ($1::uuid[] IS NULL OR id = ANY($1))
+            "#,
+            students
+        )
+        .fetch_one(pool)
+        .await?;
+
         let students = sqlx::query_as!(
             Self,
             r#"
-            SELECT id, username, email, password_hash, display_name, avatar_url, bio, role, 
+            SELECT id, username, email, password_hash, display_name, avatar_url, bio, role,
                    grade, parent_name, parent_phone, address, notes, created_at, updated_at
             FROM users
-            WHERE role = 'student'
-            ORDER BY created_at DESC
-            "#
+            WHERE role = 'student' AND ($1::uuid[] IS NULL OR id = ANY($1))
+            ORDER BY created_at DESC, id DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            students,
+            page.limit(),
+            page.offset()
         )
         .fetch_all(pool)
         .await?;
 
-        Ok(students)
+        Ok(Page::new(students, total, page))
     }
 }
@@ -0,0 +1,210 @@
+//! PDF 文档模块
+//!
+//! 将按行标记的文本排版为 A4 纵向的 PDF 文件，每页带有页眉和页码。
+//! 文本的标记方式：以 `# ` 开头的行为标题，以 `## ` 开头的行为小节标题，
+//! `---` 为分隔线，空行为段落间距，其余为正文，超出页宽时自动换行
+
+use printpdf::{
+    IndirectFontRef, Line, Mm, PdfDocument, PdfDocumentReference, PdfLayerReference, Point,
+};
+use std::io::Cursor;
+
+use crate::error::{AppError, AppErrorType};
+
+/// 页面宽度（毫米）
+const PAGE_WIDTH: f32 = 210.0;
+/// 页面高度（毫米）
+const PAGE_HEIGHT: f32 = 297.0;
+/// 页边距（毫米）
+const MARGIN: f32 = 20.0;
+/// 1磅对应的毫米数
+const PT_TO_MM: f32 = 0.3528;
+/// 行高与字号之比
+const LINE_SPACING: f32 = 1.6;
+
+/// 标题字号（磅）
+const TITLE_SIZE: f32 = 18.0;
+/// 小节标题字号（磅）
+const HEADING_SIZE: f32 = 13.0;
+/// 正文字号（磅）
+const TEXT_SIZE: f32 = 10.5;
+/// 页眉标题字号（磅）
+const HEADER_TITLE_SIZE: f32 = 14.0;
+/// 页眉文字和页码字号（磅）
+const HEADER_SIZE: f32 = 9.0;
+
+/// 待排版的文本文档
+#[derive(Debug, Clone)]
+pub struct TextDocument<'a> {
+    /// 文档标题，写入 PDF 元数据
+    pub title: &'a str,
+    /// 页眉标题，如学校名称
+    pub header_title: &'a str,
+    /// 页眉标题下方的文字
+    pub header_lines: &'a [String],
+    /// 按行标记的正文
+    pub body: &'a str,
+}
+
+impl TextDocument<'_> {
+    /// 使用指定的 TrueType 字体排版为 PDF 文件，字体会完整嵌入文件中
+    pub fn to_pdf(&self, font: &[u8]) -> Result<Vec<u8>, AppError> {
+        let failed = |e: printpdf::Error| {
+            AppError::new_message(&format!("生成 PDF 文件失败: {}", e), AppErrorType::Internal)
+        };
+
+        let (doc, page, layer) =
+            PdfDocument::new(self.title, Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "内容");
+        let font = doc.add_external_font(Cursor::new(font)).map_err(failed)?;
+        let layer = doc.get_page(page).get_layer(layer);
+
+        let mut writer = PageWriter {
+            document: self,
+            doc: &doc,
+            font,
+            layer,
+            page: 1,
+            y: 0.0,
+        };
+        writer.start_page();
+        for line in self.body.lines() {
+            writer.write_line(line.trim_end());
+        }
+
+        doc.save_to_bytes().map_err(failed)
+    }
+}
+
+/// 逐行写入页面，写满时换页
+struct PageWriter<'a> {
+    document: &'a TextDocument<'a>,
+    doc: &'a PdfDocumentReference,
+    font: IndirectFontRef,
+    layer: PdfLayerReference,
+    /// 当前页码
+    page: usize,
+    /// 下一行的顶部位置（毫米，自页面底部起算）
+    y: f32,
+}
+
+impl PageWriter<'_> {
+    /// 写入一行标记文本
+    fn write_line(&mut self, line: &str) {
+        if let Some(title) = line.strip_prefix("# ") {
+            self.write_text(title.trim(), TITLE_SIZE, true);
+            self.y -= line_height(TEXT_SIZE) / 2.0;
+        } else if let Some(heading) = line.strip_prefix("## ") {
+            self.y -= line_height(TEXT_SIZE) / 2.0;
+            self.write_text(heading.trim(), HEADING_SIZE, false);
+        } else if line.trim() == "---" {
+            self.ensure_space(line_height(TEXT_SIZE));
+            let y = self.y - line_height(TEXT_SIZE) / 2.0;
+            self.rule(y);
+            self.y -= line_height(TEXT_SIZE);
+        } else if line.trim().is_empty() {
+            self.y -= line_height(TEXT_SIZE) / 2.0;
+        } else {
+            self.write_text(line, TEXT_SIZE, false);
+        }
+    }
+
+    /// 写入文本，超出页宽时换行
+    fn write_text(&mut self, text: &str, size: f32, centered: bool) {
+        for line in wrap(text, size, PAGE_WIDTH - 2.0 * MARGIN) {
+            self.ensure_space(line_height(size));
+            self.y -= line_height(size);
+            let x = if centered {
+                (PAGE_WIDTH - text_width(&line, size)) / 2.0
+            } else {
+                MARGIN
+            };
+            self.layer
+                .use_text(line, size, Mm(x), Mm(self.y + baseline(size)), &self.font);
+        }
+    }
+
+    /// 剩余空间不足时换页
+    fn ensure_space(&mut self, height: f32) {
+        if self.y - height < MARGIN {
+            let (page, layer) = self.doc.add_page(Mm(PAGE_WIDTH), Mm(PAGE_HEIGHT), "内容");
+            self.layer = self.doc.get_page(page).get_layer(layer);
+            self.page += 1;
+            self.start_page();
+        }
+    }
+
+    /// 绘制页眉和页码，并将位置移到正文开始处
+    fn start_page(&mut self) {
+        self.y = PAGE_HEIGHT - MARGIN / 2.0;
+
+        let document = self.document;
+        self.write_text(document.header_title, HEADER_TITLE_SIZE, true);
+        for line in document.header_lines {
+            self.write_text(line, HEADER_SIZE, true);
+        }
+        self.y -= line_height(HEADER_SIZE) / 2.0;
+        self.rule(self.y);
+        self.y -= line_height(HEADER_SIZE);
+
+        let page_number = format!("第 {} 页", self.page);
+        let x = (PAGE_WIDTH - text_width(&page_number, HEADER_SIZE)) / 2.0;
+        self.layer.use_text(
+            page_number,
+            HEADER_SIZE,
+            Mm(x),
+            Mm(MARGIN / 2.0),
+            &self.font,
+        );
+    }
+
+    /// 在指定高度绘制横贯版心的分隔线
+    fn rule(&self, y: f32) {
+        self.layer.set_outline_thickness(0.5);
+        self.layer.add_line(Line {
+            points: vec![
+                (Point::new(Mm(MARGIN), Mm(y)), false),
+                (Point::new(Mm(PAGE_WIDTH - MARGIN), Mm(y)), false),
+            ],
+            is_closed: false,
+        });
+    }
+}
+
+/// 行高（毫米）
+fn line_height(size: f32) -> f32 {
+    size * LINE_SPACING * PT_TO_MM
+}
+
+/// 基线距行底部的高度（毫米）
+fn baseline(size: f32) -> f32 {
+    (LINE_SPACING - 1.0) / 2.0 * size * PT_TO_MM + 0.2 * size * PT_TO_MM
+}
+
+/// 估算字符宽度（毫米），中文等全角字符占一个字号宽，ASCII 字符占约一半
+fn char_width(c: char, size: f32) -> f32 {
+    let em = if c.is_ascii() { 0.55 } else { 1.0 };
+    em * size * PT_TO_MM
+}
+
+/// 估算文本宽度（毫米）
+fn text_width(text: &str, size: f32) -> f32 {
+    text.chars().map(|c| char_width(c, size)).sum()
+}
+
+/// 按宽度将文本拆分为多行
+fn wrap(text: &str, size: f32, width: f32) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut line_width = 0.0;
+    for c in text.chars() {
+        let w = char_width(c, size);
+        if line_width + w > width && !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+            line_width = 0.0;
+        }
+        line.push(c);
+        line_width += w;
+    }
+    lines.push(line);
+    lines
+}
@@ -0,0 +1,139 @@
+//! 查询参数模块
+//!
+//! 提供列表查询共用的分页和排序参数，各模型的筛选条件通过 `QueryBuilder` 以 AND 组合
+
+use serde::{Deserialize, Deserializer, Serialize};
+use sqlx::{Postgres, QueryBuilder};
+
+/// 默认每页记录数
+pub const DEFAULT_PAGE_LIMIT: i64 = 20;
+/// 每页记录数上限
+pub const MAX_PAGE_LIMIT: i64 = 100;
+
+/// 分页参数
+///
+/// 使用 `page` 按页码翻页，或将上一页返回的 `next_cursor` 作为 `cursor` 传回以读取下一页
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct PageParams {
+    /// 页码，从1开始
+    pub page: Option<u32>,
+    /// 每页记录数，默认20，最大100
+    pub limit: Option<u32>,
+    /// 分页游标，优先于页码
+    pub cursor: Option<Cursor>,
+}
+
+impl PageParams {
+    /// 每页记录数，限制在1到上限之间
+    pub fn limit(&self) -> i64 {
+        self.limit.map_or(DEFAULT_PAGE_LIMIT, |limit| {
+            (limit as i64).clamp(1, MAX_PAGE_LIMIT)
+        })
+    }
+
+    /// 本页第一条记录的偏移量
+    pub fn offset(&self) -> i64 {
+        match self.cursor {
+            Some(Cursor(offset)) => offset,
+            None => (self.page.unwrap_or(1).max(1) as i64 - 1) * self.limit(),
+        }
+    }
+}
+
+/// 分页游标，记录下一页第一条记录的位置
+#[derive(Debug, Clone, Copy)]
+pub struct Cursor(i64);
+
+impl<'de> Deserialize<'de> for Cursor {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse::<u32>()
+            .map(|offset| Cursor(offset as i64))
+            .map_err(|_| serde::de::Error::custom("无效的分页游标"))
+    }
+}
+
+/// 分页结果
+#[derive(Debug, Clone, Serialize)]
+pub struct Page<T> {
+    /// 本页记录
+    pub items: Vec<T>,
+    /// 符合条件的记录总数
+    pub total: i64,
+    /// 当前页码
+    pub page: i64,
+    /// 每页记录数
+    pub limit: i64,
+    /// 下一页的游标，没有下一页时为空
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// 根据本页记录、记录总数和分页参数创建分页结果
+    pub fn new(items: Vec<T>, total: i64, params: &PageParams) -> Self {
+        let limit = params.limit();
+        let offset = params.offset();
+        let next = offset + items.len() as i64;
+
+        Self {
+            page: offset / limit + 1,
+            limit,
+            next_cursor: (!items.is_empty() && next < total).then(|| next.to_string()),
+            items,
+            total,
+        }
+    }
+}
+
+/// 排序方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    /// 升序
+    Asc,
+    /// 降序
+    #[default]
+    Desc,
+}
+
+impl SortOrder {
+    /// 对应的 SQL 关键字
+    pub fn as_sql(self) -> &'static str {
+        match self {
+            SortOrder::Asc => "ASC",
+            SortOrder::Desc => "DESC",
+        }
+    }
+}
+
+/// 可排序字段，映射到数据库列名
+pub trait SortField: Copy {
+    /// 数据库列名
+    fn column(self) -> &'static str;
+}
+
+/// 追加 LIMIT 和 OFFSET 子句
+pub fn push_page(builder: &mut QueryBuilder<'_, Postgres>, params: &PageParams) {
+    builder
+        .push(" LIMIT ")
+        .push_bind(params.limit())
+        .push(" OFFSET ")
+        .push_bind(params.offset());
+}
+
+/// 追加 ORDER BY 子句
+///
+/// 空值排在最后，并以 id 作为次要排序保证结果顺序稳定
+pub fn push_order_by<F: SortField>(
+    builder: &mut QueryBuilder<'_, Postgres>,
+    field: F,
+    order: SortOrder,
+) {
+    builder
+        .push(" ORDER BY ")
+        .push(field.column())
+        .push(" ")
+        .push(order.as_sql())
+        .push(" NULLS LAST, id ")
+        .push(order.as_sql());
+}
@@ -0,0 +1,256 @@
+//! 表格文件模块
+//!
+//! 读取 CSV 和 XLSX 文件，统一转换为表头加字符串单元格的行；
+//! 以及将表头加单元格的行写为 CSV 或 XLSX 文件
+
+use calamine::{Reader, Xlsx};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use rust_xlsxwriter::{Format, Workbook, XlsxError};
+use serde::Deserialize;
+use std::borrow::Cow;
+use std::io::Cursor;
+
+use crate::error::{AppError, AppErrorType};
+
+/// UTF-8 字节顺序标记，Excel 导出的 CSV 文件通常带有
+const UTF8_BOM: &[u8] = b"\xEF\xBB\xBF";
+
+/// 表格文件格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SheetFormat {
+    /// 逗号分隔值
+    #[default]
+    Csv,
+    /// Excel 工作簿
+    Xlsx,
+}
+
+impl SheetFormat {
+    /// 根据文件扩展名判断格式
+    pub fn from_file_name(name: &str) -> Option<Self> {
+        let (_, ext) = name.rsplit_once('.')?;
+        match ext.to_ascii_lowercase().as_str() {
+            "csv" => Some(SheetFormat::Csv),
+            "xlsx" => Some(SheetFormat::Xlsx),
+            _ => None,
+        }
+    }
+
+    /// 文件扩展名
+    pub fn extension(self) -> &'static str {
+        match self {
+            SheetFormat::Csv => "csv",
+            SheetFormat::Xlsx => "xlsx",
+        }
+    }
+
+    /// MIME类型
+    pub fn content_type(self) -> &'static str {
+        match self {
+            SheetFormat::Csv => "text/csv; charset=utf-8",
+            SheetFormat::Xlsx => {
+                "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"
+            }
+        }
+    }
+}
+
+/// 表格数据
+#[derive(Debug, Clone, Default)]
+pub struct Sheet {
+    /// 表头，即第一行
+    pub headers: Vec<String>,
+    /// 数据行，不包含空行
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Sheet {
+    /// 解析表格文件，XLSX 只读取第一个工作表，单元格去掉首尾空白
+    pub fn parse(format: SheetFormat, data: &[u8]) -> Result<Self, AppError> {
+        let rows = match format {
+            SheetFormat::Csv => read_csv(data)?,
+            SheetFormat::Xlsx => read_xlsx(data)?,
+        };
+
+        let mut rows = rows
+            .into_iter()
+            .filter(|row| row.iter().any(|cell| !cell.is_empty()));
+        let headers = rows
+            .next()
+            .ok_or_else(|| AppError::new_message("表格文件没有表头", AppErrorType::BadRequest))?;
+
+        Ok(Self {
+            headers,
+            rows: rows.collect(),
+        })
+    }
+
+    /// 查找表头所在的列
+    pub fn column(&self, header: &str) -> Option<usize> {
+        self.headers.iter().position(|h| h == header)
+    }
+}
+
+/// 导出表格的单元格
+#[derive(Debug, Clone, PartialEq)]
+pub enum Cell {
+    /// 空单元格
+    Empty,
+    /// 文本
+    Text(String),
+    /// 数值，XLSX 中写为数字单元格
+    Number(Decimal),
+}
+
+impl Cell {
+    /// 单元格的文本形式，文本按 [`escape_formula`] 转义
+    fn to_text(&self) -> String {
+        match self {
+            Cell::Empty => String::new(),
+            Cell::Text(text) => escape_formula(text).into_owned(),
+            Cell::Number(number) => number.to_string(),
+        }
+    }
+}
+
+/// 防止表格软件把导出的文本当作公式执行
+///
+/// 以 `=`、`+`、`-`、`@`、制表符或回车开头的文本前加单引号
+fn escape_formula(text: &str) -> Cow<'_, str> {
+    if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        Cow::Owned(format!("'{}", text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// 导出表格
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    /// 表头
+    pub headers: Vec<String>,
+    /// 数据行
+    pub rows: Vec<Vec<Cell>>,
+}
+
+impl Table {
+    /// 按指定格式写出表格文件，表头和文本单元格按 [`escape_formula`] 转义
+    pub fn write(&self, format: SheetFormat, sheet_name: &str) -> Result<Vec<u8>, AppError> {
+        match format {
+            SheetFormat::Csv => self.to_csv(),
+            SheetFormat::Xlsx => self.to_xlsx(sheet_name).map_err(|e| {
+                AppError::new_message(
+                    &format!("生成 XLSX 文件失败: {}", e),
+                    AppErrorType::Internal,
+                )
+            }),
+        }
+    }
+
+    /// 写为带 UTF-8 字节顺序标记的 CSV，便于 Excel 正确识别中文
+    fn to_csv(&self) -> Result<Vec<u8>, AppError> {
+        let failed = |e: &dyn std::fmt::Display| {
+            AppError::new_message(&format!("生成 CSV 文件失败: {}", e), AppErrorType::Internal)
+        };
+
+        let mut writer = csv::Writer::from_writer(UTF8_BOM.to_vec());
+        writer
+            .write_record(
+                self.headers
+                    .iter()
+                    .map(|header| escape_formula(header).into_owned()),
+            )
+            .map_err(|e| failed(&e))?;
+        for row in &self.rows {
+            writer
+                .write_record(row.iter().map(Cell::to_text))
+                .map_err(|e| failed(&e))?;
+        }
+        writer.into_inner().map_err(|e| failed(&e))
+    }
+
+    /// 写为只有一个工作表的 XLSX，表头加粗并冻结
+    fn to_xlsx(&self, sheet_name: &str) -> Result<Vec<u8>, XlsxError> {
+        let mut workbook = Workbook::new();
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(sheet_name)?;
+
+        let bold = Format::new().set_bold();
+        for (col, header) in self.headers.iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, escape_formula(header), &bold)?;
+        }
+        for (index, row) in self.rows.iter().enumerate() {
+            let row_num = index as u32 + 1;
+            for (col, cell) in row.iter().enumerate() {
+                match cell {
+                    Cell::Empty => {}
+                    Cell::Text(text) => {
+                        worksheet.write_string(row_num, col as u16, escape_formula(text))?;
+                    }
+                    Cell::Number(number) => match number.to_f64() {
+                        Some(value) => {
+                            worksheet.write_number(row_num, col as u16, value)?;
+                        }
+                        None => {
+                            worksheet.write_string(row_num, col as u16, number.to_string())?;
+                        }
+                    },
+                }
+            }
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        worksheet.autofit();
+
+        workbook.save_to_buffer()
+    }
+}
+
+/// 读取 CSV 文件的全部行
+fn read_csv(data: &[u8]) -> Result<Vec<Vec<String>>, AppError> {
+    let data = data.strip_prefix(UTF8_BOM).unwrap_or(data);
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(data);
+
+    reader
+        .records()
+        .map(|record| {
+            record
+                .map(|record| record.iter().map(|cell| cell.trim().to_string()).collect())
+                .map_err(|e| {
+                    AppError::new_message(
+                        &format!("CSV 文件格式错误（需使用 UTF-8 编码）: {}", e),
+                        AppErrorType::BadRequest,
+                    )
+                })
+        })
+        .collect()
+}
+
+/// 读取 XLSX 文件第一个工作表的全部行
+fn read_xlsx(data: &[u8]) -> Result<Vec<Vec<String>>, AppError> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        AppError::new_message(
+            &format!("XLSX 文件格式错误: {}", e),
+            AppErrorType::BadRequest,
+        )
+    };
+
+    let mut workbook = Xlsx::new(Cursor::new(data)).map_err(|e| invalid(&e))?;
+    let range = workbook
+        .worksheet_range_at(0)
+        .ok_or_else(|| AppError::new_message("XLSX 文件没有工作表", AppErrorType::BadRequest))?
+        .map_err(|e| invalid(&e))?;
+
+    Ok(range
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.to_string().trim().to_string())
+                .collect()
+        })
+        .collect())
+}
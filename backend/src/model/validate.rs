@@ -0,0 +1,95 @@
+//! 数据校验规则模块
+//!
+//! 请求结构体通过 `#[derive(Validate)]` 声明校验规则，
+//! 这里提供内置规则之外的自定义规则，供 `#[validate(custom(function = ...))]` 使用
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use rust_decimal::Decimal;
+use std::borrow::Cow;
+use validator::ValidationError;
+
+/// 电话号码格式：手机号或带区号的固定电话
+pub static PHONE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(1[3-9]\d{9}|0\d{2,3}-?\d{7,8})$").unwrap());
+
+/// 密码最小长度
+const PASSWORD_MIN_LEN: usize = 8;
+/// 密码最大长度，bcrypt 只使用前72个字节
+const PASSWORD_MAX_LEN: usize = 72;
+
+/// 分数上限，对应数据库中的 DECIMAL(5,2)
+const SCORE_MAX: Decimal = Decimal::from_parts(99999, 0, 0, false, 2);
+
+/// 创建带说明的校验错误
+fn error(code: &'static str, message: &'static str) -> ValidationError {
+    ValidationError::new(code).with_message(Cow::Borrowed(message))
+}
+
+/// 密码强度：8到72个字节，且同时包含字母和数字
+pub fn password_strength(password: &str) -> Result<(), ValidationError> {
+    if password.len() < PASSWORD_MIN_LEN || password.len() > PASSWORD_MAX_LEN {
+        return Err(error("password_length", "密码长度必须在8到72个字符之间"));
+    }
+
+    let has_letter = password.chars().any(|c| c.is_ascii_alphabetic());
+    let has_digit = password.chars().any(|c| c.is_ascii_digit());
+    if !has_letter || !has_digit {
+        return Err(error("password_strength", "密码必须同时包含字母和数字"));
+    }
+
+    Ok(())
+}
+
+/// 分数范围：0到999.99，最多两位小数
+pub fn score(score: &Decimal) -> Result<(), ValidationError> {
+    if score.is_sign_negative() || *score > SCORE_MAX {
+        return Err(error("score_range", "分数必须在0到999.99之间"));
+    }
+    if score.normalize().scale() > 2 {
+        return Err(error("score_scale", "分数最多保留两位小数"));
+    }
+
+    Ok(())
+}
+
+/// 正分数：大于0且不超过999.99，最多两位小数
+pub fn positive_score(score: &Decimal) -> Result<(), ValidationError> {
+    self::score(score)?;
+    if score.is_zero() {
+        return Err(error("score_positive", "分数必须大于0"));
+    }
+
+    Ok(())
+}
+
+/// 百分比：0到100，最多两位小数
+pub fn percent(value: &Decimal) -> Result<(), ValidationError> {
+    if value.is_sign_negative() || *value > Decimal::ONE_HUNDRED {
+        return Err(error("percent_range", "百分比必须在0到100之间"));
+    }
+    if value.normalize().scale() > 2 {
+        return Err(error("percent_scale", "百分比最多保留两位小数"));
+    }
+
+    Ok(())
+}
+
+/// 正百分比：大于0且不超过100，最多两位小数
+pub fn positive_percent(value: &Decimal) -> Result<(), ValidationError> {
+    percent(value)?;
+    if value.is_zero() {
+        return Err(error("percent_positive", "百分比必须大于0"));
+    }
+
+    Ok(())
+}
+
+/// 去掉首尾空白后不能为空
+pub fn not_blank(value: &str) -> Result<(), ValidationError> {
+    if value.trim().is_empty() {
+        return Err(error("blank", "不能为空"));
+    }
+
+    Ok(())
+}
@@ -12,11 +12,13 @@ use tower::ServiceBuilder;
 /// 创建应用的所有路由
 pub fn create_routes(pool: Arc<Pool<Postgres>>) -> Router {
     Router::new()
-        .nest("/api", api::create_routes().with_state(pool))
+        .nest("/api", api::create_routes(&pool).with_state(pool))
         //中间件
         .layer(
             ServiceBuilder::new()
+                .layer(middleware::request_id::create_set_layer())
                 .layer(middleware::trace_layer::create_layer())
+                .layer(middleware::request_id::create_propagate_layer())
                 .layer(middleware::cors::create_layer()),
         )
 }
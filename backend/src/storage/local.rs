@@ -0,0 +1,75 @@
+//! 本地文件系统存储
+//!
+//! 将文件保存在配置的根目录下，键即为相对路径
+
+use async_trait::async_trait;
+use std::path::{Component, Path, PathBuf};
+use tokio::fs;
+
+use super::FileStorage;
+use crate::error::{AppError, AppErrorType};
+
+/// 本地文件系统存储后端
+#[derive(Debug, Clone)]
+pub struct LocalFileStorage {
+    root: PathBuf,
+}
+
+impl LocalFileStorage {
+    /// 创建本地存储，根目录不存在时自动创建
+    pub fn new<P: AsRef<Path>>(root: P) -> Result<Self, AppError> {
+        let root = root.as_ref().to_path_buf();
+        std::fs::create_dir_all(&root).map_err(|e| AppError::new(e, AppErrorType::Internal))?;
+        Ok(Self { root })
+    }
+
+    /// 将存储键解析为根目录下的路径，拒绝绝对路径和 `..`
+    fn resolve(&self, key: &str) -> Result<PathBuf, AppError> {
+        let relative = Path::new(key);
+        if key.is_empty()
+            || relative
+                .components()
+                .any(|c| !matches!(c, Component::Normal(_)))
+        {
+            return Err(AppError::new_message(
+                "无效的文件存储键",
+                AppErrorType::Internal,
+            ));
+        }
+        Ok(self.root.join(relative))
+    }
+}
+
+#[async_trait]
+impl FileStorage for LocalFileStorage {
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), AppError> {
+        let path = self.resolve(key)?;
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .await
+                .map_err(|e| AppError::new(e, AppErrorType::Internal))?;
+        }
+        fs::write(&path, data)
+            .await
+            .map_err(|e| AppError::new(e, AppErrorType::Internal))
+    }
+
+    async fn get(&self, key: &str) -> Result<Vec<u8>, AppError> {
+        let path = self.resolve(key)?;
+        fs::read(&path).await.map_err(|e| match e.kind() {
+            std::io::ErrorKind::NotFound => {
+                AppError::new_message("文件不存在", AppErrorType::Notfound)
+            }
+            _ => AppError::new(e, AppErrorType::Internal),
+        })
+    }
+
+    async fn delete(&self, key: &str) -> Result<(), AppError> {
+        let path = self.resolve(key)?;
+        match fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(AppError::new(e, AppErrorType::Internal)),
+        }
+    }
+}
@@ -0,0 +1,78 @@
+//! 文件存储模块
+//!
+//! 定义可插拔的文件存储后端接口，目前提供本地文件系统实现
+
+mod local;
+
+use async_trait::async_trait;
+use once_cell::sync::OnceCell;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+
+use crate::config::StorageConfig;
+use crate::error::{AppError, AppErrorType};
+
+pub use local::LocalFileStorage;
+
+// 全局存储后端单例
+static STORAGE: OnceCell<Arc<dyn FileStorage>> = OnceCell::new();
+
+/// 文件存储后端接口
+#[async_trait]
+pub trait FileStorage: Send + Sync {
+    /// 保存文件内容到指定键
+    async fn put(&self, key: &str, data: &[u8]) -> Result<(), AppError>;
+
+    /// 读取指定键的文件内容
+    async fn get(&self, key: &str) -> Result<Vec<u8>, AppError>;
+
+    /// 删除指定键的文件
+    async fn delete(&self, key: &str) -> Result<(), AppError>;
+}
+
+/// 获取全局存储后端
+pub fn get_storage() -> &'static Arc<dyn FileStorage> {
+    STORAGE.get().expect("存储后端未初始化")
+}
+
+/// 根据配置初始化全局存储后端
+pub fn init_storage(config: &StorageConfig) -> Result<(), AppError> {
+    let storage: Arc<dyn FileStorage> = match config.backend.as_str() {
+        "local" => Arc::new(LocalFileStorage::new(&config.root)?),
+        other => {
+            return Err(AppError::new_message(
+                &format!("不支持的存储后端: {}", other),
+                AppErrorType::Internal,
+            ));
+        }
+    };
+
+    STORAGE
+        .set(storage)
+        .map_err(|_| AppError::new_message("存储后端已经初始化过", AppErrorType::Internal))
+}
+
+/// 计算文件内容的SHA-256哈希（十六进制）
+pub fn content_hash(data: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(data))
+}
+
+/// 校验文件MIME类型是否在允许列表中
+pub fn check_mime_type(config: &StorageConfig, content_type: &str) -> Result<(), AppError> {
+    // 忽略参数部分，如 "text/plain; charset=utf-8"
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_lowercase();
+
+    if config.allowed_mime_types.contains(&mime) {
+        Ok(())
+    } else {
+        Err(AppError::new_message(
+            &format!("不支持的文件类型: {}", mime),
+            AppErrorType::UnsupportedFileType,
+        ))
+    }
+}
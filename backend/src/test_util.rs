@@ -0,0 +1,142 @@
+//! 单元测试共用的辅助函数
+//!
+//! 需要数据库的测试使用 `#[sqlx::test]` 获取按迁移建好的临时数据库，再通过 [`TestApp`] 向完整的路由发送请求
+
+use axum::Router;
+use axum::body::{Body, to_bytes};
+use axum::http::{Method, Request, StatusCode, header};
+use rust_decimal::Decimal;
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::{Arc, Once};
+use time::OffsetDateTime;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::config::{self, Config};
+use crate::middleware::auth::generate_token;
+use crate::model::models::permission::RolePermission;
+use crate::model::models::user::User;
+use crate::routes;
+
+/// 解析十进制数
+pub fn dec(value: &str) -> Decimal {
+    value.parse().unwrap()
+}
+
+/// 解析一组十进制数
+pub fn decs(values: &[&str]) -> Vec<Decimal> {
+    values.iter().map(|value| dec(value)).collect()
+}
+
+/// 读取响应中的十进制数，响应中的十进制数序列化为字符串
+pub fn json_dec(value: &Value) -> Decimal {
+    dec(value.as_str().expect("不是十进制数"))
+}
+
+/// 测试用户
+pub struct TestUser {
+    pub id: Uuid,
+    pub token: String,
+}
+
+/// 连接临时数据库的完整应用
+pub struct TestApp {
+    pub pool: PgPool,
+    router: Router,
+}
+
+impl TestApp {
+    /// 使用默认配置和数据库中的角色权限创建应用
+    pub async fn new(pool: PgPool) -> Self {
+        static CONFIG: Once = Once::new();
+        CONFIG.call_once(|| config::init_config(Config::default()));
+        RolePermission::load(&pool).await.unwrap();
+
+        let router = routes::create_routes(Arc::new(pool.clone()));
+        Self { pool, router }
+    }
+
+    /// 创建指定角色的用户并签发令牌
+    pub async fn user(&self, role: &str) -> TestUser {
+        let id = Uuid::new_v4();
+        sqlx::query(
+            "INSERT INTO users (id, username, email, password_hash, role) VALUES ($1, $2, $3, '', $4)",
+        )
+        .bind(id)
+        .bind(format!("{role}-{id}"))
+        .bind(format!("{id}@example.com"))
+        .bind(role)
+        .execute(&self.pool)
+        .await
+        .unwrap();
+
+        let user = User::find_by_id(&self.pool, id).await.unwrap().unwrap();
+        TestUser {
+            id,
+            token: generate_token(&user).unwrap(),
+        }
+    }
+
+    /// 创建课程和教师负责的班级，返回课程ID
+    pub async fn course(&self, teacher: &TestUser) -> Uuid {
+        let course_id = Uuid::new_v4();
+        sqlx::query("INSERT INTO courses (id, name) VALUES ($1, '测试课程')")
+            .bind(course_id)
+            .execute(&self.pool)
+            .await
+            .unwrap();
+        sqlx::query(
+            "INSERT INTO class_groups (id, course_id, name, term, teacher_id) VALUES ($1, $2, '一班', '2025春', $3)",
+        )
+        .bind(Uuid::new_v4())
+        .bind(course_id)
+        .bind(teacher.id)
+        .execute(&self.pool)
+        .await
+        .unwrap();
+        course_id
+    }
+
+    /// 学生选课并分到课程的班级
+    pub async fn enroll(&self, course_id: Uuid, student: &TestUser) {
+        sqlx::query(
+            "INSERT INTO enrollments (id, student_id, course_id, class_group_id, enrolled_on)
+             SELECT $1, $2, $3, id, $4 FROM class_groups WHERE course_id = $3",
+        )
+        .bind(Uuid::new_v4())
+        .bind(student.id)
+        .bind(course_id)
+        .bind(OffsetDateTime::now_utc().date())
+        .execute(&self.pool)
+        .await
+        .unwrap();
+    }
+
+    /// 以指定用户发送 JSON 请求，返回状态码和响应体
+    pub async fn send(
+        &self,
+        method: Method,
+        uri: &str,
+        user: &TestUser,
+        body: Option<Value>,
+    ) -> (StatusCode, Value) {
+        let request = Request::builder()
+            .method(method)
+            .uri(format!("/api{uri}"))
+            .header(header::AUTHORIZATION, format!("Bearer {}", user.token))
+            .header(header::CONTENT_TYPE, "application/json");
+        let body = body.map_or_else(Body::empty, |body| Body::from(body.to_string()));
+
+        let response = self
+            .router
+            .clone()
+            .oneshot(request.body(body).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body = serde_json::from_slice(&bytes).unwrap_or(Value::Null);
+        (status, body)
+    }
+}